@@ -0,0 +1,28 @@
+import { Suspense } from 'react';
+import { PageParamsProps } from '@/app/types/router';
+import BuildView from '@/app/components/BuildView';
+import { fetchAllByFile } from '@/app/utils/request';
+import { Item } from '@/app/types/item';
+import { setRequestLocale } from 'next-intl/server';
+
+type BuildProps = PageParamsProps
+
+export default async function Build({ params }: BuildProps) {
+
+    const { locale } = await params;
+    setRequestLocale(locale);
+    const items = fetchAllByFile<Item[]>('items.json');// 获取物品
+
+    return (
+        <div className="min-h-screen bg-gray-50 dark:bg-gray-900 py-8 px-4">
+            <main className="max-w-7xl mx-auto">
+                <Suspense>
+                    <BuildView items={items} locale={locale} />
+                </Suspense>
+            </main>
+        </div>
+    )
+}
+
+export { generateStaticParams } from '@/lib/getStatic';
+export const dynamic = 'force-static';
@@ -1,111 +1,12 @@
+// 存档相关类型由 savefile-parse 的 Rust 类型生成，修改 Rust 类型后在 savefile-parse 目录执行
+// `UPDATE_BINDINGS=1 cargo test bindings` 重新生成
+import type { SaveParseError as GeneratedSaveParseError } from '@/savefile-parse/bindings/savefile-parse';
 
-export interface QuestSaveData {
-  __type: string;
-  id: number;
-  complete: boolean;
-  needInspection: boolean;
-  questGiverID: number;
-  // taskStatus: TaskStatusEntry[];
-  // rewardStatus: RewardStatusEntry[];
-}
-
-export interface QuestData {
-  activeQuestsData: QuestSaveData[]
-  historyQuestsData: QuestSaveData[]
-  everInspectedData: number[]
-}
-
-export interface MasterKey {
-  id: number
-  active: boolean
-}
-
-export interface WorldPosition {
-  x: number
-  y: number
-  z: number
-}
+export type * from '@/savefile-parse/bindings/savefile-parse';
 
-export interface ItemVariable {
-  key: string
-  dataType: number
-  data: string
-  display: boolean
-}
-
-export interface SlotContent {
-  slot: string
-  instanceID: number
-}
-
-export interface InventoryItem {
-  position: number
-  instanceID: number
-}
-
-export interface ItemEntry {
-  instanceID: number
-  typeID: number
-  variables: ItemVariable[]
-  slotContents: SlotContent[]
-  inventory: InventoryItem[]
-  // inventorySortLocks: any[]
-}
+// wasm 抛出的错误额外带有 displayMessage
+export type SaveParseError = GeneratedSaveParseError & { displayMessage?: string }
 
-export interface ItemTreeData {
-  rootInstanceID: number
-  entries: ItemEntry[]
+export function isSaveParseError(value: unknown): value is Required<SaveParseError> {
+  return typeof value === 'object' && value !== null && 'kind' in value && 'displayMessage' in value
 }
-
-export interface StashData {
-  valid: boolean
-  raidID: number
-  subSceneID: string
-  worldPosition: WorldPosition
-  itemTreeData: ItemTreeData
-  spawned: boolean
-  touched: boolean
-}
-
-export interface InventoryItemData {
-  inventoryPosition: number
-  itemTreeData: ItemTreeData
-}
-
-export interface Inventory {
-  capacity: number
-  entries: InventoryItemData[]
-}
-
-export interface Kill {
-  masterName: string
-  count: number
-}
-
-export interface EconomyData {
-  money: number,
-  unlockedItems: number[],
-  unlockesWaitingForConfirm: number[],
-}
-
-export interface GameLock {
-  days: number;
-}
-
-export interface Archive {
-  version: string
-  level: number
-  exp: number
-  health: number
-  gameClock: GameLock
-  economyData: EconomyData
-  masterKeys: MasterKey[]
-  deathList: StashData[]
-  quests: QuestData
-  inventory: number[]
-  kills: Kill[]
-  save_time: number
-  playerStorage: Inventory
-  inventorySafe: Inventory
-  characterItemData: ItemTreeData
-}
\ No newline at end of file
@@ -3,10 +3,14 @@
 import {
     Archive,
     Inventory,
+    isSaveParseError,
     ItemEntry,
     ItemTreeData,
     MasterKey,
+    ParseProgress,
+    SaveFolder,
     SlotContent,
+    Timeline,
 } from '@/app/[locale]/archived/types';
 import { ItemLink } from '@/app/components/ClientProxy';
 import React, { useRef, useState } from 'react';
@@ -19,8 +23,22 @@ import { LocaleLink } from '@/app/components/LocaleLink';
 import { IQuestGraph } from '@/app/types/quest';
 import { ItemLinkProps } from '@/app/components/ItemCard';
 import { LinkProps } from 'next/link';
-import { parseVariable } from '@/app/utils/format';
 import { getItemKey, getItemName, getMonsterName } from '@/app/utils/lang';
+import { collectDroppedFiles, NamedFile } from '@/app/utils/files';
+import SaveFolderPanel from '@/app/components/SaveFolderPanel';
+import ProgressTimeline from '@/app/components/ProgressTimeline';
+import WasmWorkerClient, { isAbortError } from '@/app/utils/wasm-worker';
+import type { ParseSaveRequest } from '@/app/workers/save-worker';
+
+// 存档在专用 Worker 中解析，页面保持响应
+let saveWorker: WasmWorkerClient<ParseSaveRequest, ParseProgress, Archive> | null = null;
+
+function getSaveWorker() {
+    saveWorker ??= new WasmWorkerClient(
+        () => new Worker(new URL('../workers/save-worker.ts', import.meta.url), { type: 'module' })
+    );
+    return saveWorker;
+}
 
 
 type ArchiveUploadProps = {
@@ -314,12 +332,8 @@ const Inventorys: React.FC<InventorysProps> = ({
 
     const mapFn = (itemEntry: ItemEntry) => {
         const item = getItemById(itemEntry.typeID);
-        const counts = itemEntry.variables
-            .filter((item) => item.key === 'Count')
-            .reduce((acc, item) => {
-                acc += parseVariable(item) as number;
-                return acc;
-            }, 0);
+        const countValue = itemEntry.decoded?.Count;
+        const counts = countValue?.type === 'int' ? countValue.value : 0;
         total += Number(item?.priceEach) * (counts || 1) || 0;
 
         allItems.push({
@@ -438,41 +452,140 @@ export const ArchiveUpload: React.FC<ArchiveUploadProps> = ({
     const [loading, setLoading] = useState(false);
     const [error, setError] = useState<string | null>(null);
     const [isDragging, setIsDragging] = useState(false);
+    const [buildCopied, setBuildCopied] = useState(false);
+    const [folder, setFolder] = useState<{ result: SaveFolder; files: Map<string, File> } | null>(null);
+    const [timeline, setTimeline] = useState<Timeline | null>(null);
+    const [progress, setProgress] = useState<ParseProgress | null>(null);
+    const abortRef = useRef<AbortController | null>(null);
 
 
     const fileInputRef = useRef<HTMLInputElement>(null);
+    const folderInputRef = useRef<HTMLInputElement>(null);
     const nodes = questData?.nodes || [];
     async function processTextFile(file: File) {
         if (!file.name.endsWith('.sav')) {
             setError(t('archived.upload.error_format'));
             return;
         }
+        await parseSave(file);
+    }
 
+    async function parseSave(file: File) {
         setLoading(true);
         setError(null);
         setArchive(null);
 
+        abortRef.current?.abort();
+        const controller = new AbortController();
+        abortRef.current = controller;
+        setProgress(null);
+
         try {
             const arrayBuffer = await file.arrayBuffer();
             const uint8Array = new Uint8Array(arrayBuffer);
 
-            const wasm = await import('@/public/wasm-save/');
-            await wasm.default();
-
-            const resultJson = wasm.process_file(uint8Array);
-            localStorage.setItem('archived', resultJson);// 暂时保存
-            const parsedArchive: Archive = JSON.parse(resultJson);
+            // 传入物品数据库，由 wasm 计算名称、价格与各容器合计
+            const parsedArchive = await getSaveWorker().run(
+                { data: uint8Array, catalog: JSON.stringify(items) },
+                { onProgress: setProgress, signal: controller.signal }
+            );
+            localStorage.setItem('archived', JSON.stringify(parsedArchive));// 暂时保存
 
             setArchive(parsedArchive);
         } catch (error) {
+            if (isAbortError(error)) {
+                return;
+            }
             setError(
-                error instanceof Error ? error.message : t('archived.upload.error_parse')
+                error instanceof Error
+                    ? error.message
+                    : isSaveParseError(error)
+                        ? error.displayMessage
+                        : t('archived.upload.error_parse')
+            );
+        } finally {
+            if (abortRef.current === controller) {
+                abortRef.current = null;
+                setLoading(false);
+                setProgress(null);
+            }
+        }
+    }
+
+    function progressText(progress: ParseProgress | null) {
+        if (!progress) {
+            return t('archived.upload.loading');
+        }
+        return progress.stage === 'section'
+            ? t('archived.upload.progress.section', { done: progress.done, total: progress.total })
+            : t(`archived.upload.progress.${progress.stage}`);
+    }
+
+    // 生成配装分享链接并复制到剪贴板
+    async function shareBuild() {
+        if (!archive) {
+            return;
+        }
+        const wasm = await import('@/public/wasm-save/');
+        await wasm.default();
+        const code = wasm.encode_build(archive.loadout);
+        await navigator.clipboard.writeText(
+            `${window.location.origin}/${locale}/archived/build?code=${code}`
+        );
+        setBuildCopied(true);
+    }
+
+    // 解析整个存档目录，按存档位列出可用的版本
+    async function processFolder(files: NamedFile[]) {
+        setLoading(true);
+        setError(null);
+        setFolder(null);
+        setTimeline(null);
+
+        try {
+            const wasm = await import('@/public/wasm-save/');
+            await wasm.default();
+
+            const contents = await Promise.all(
+                files.map(async ({ file }) => new Uint8Array(await file.arrayBuffer()))
             );
+            const result: SaveFolder = wasm.process_folder(
+                files.map(({ name }) => name),
+                contents,
+                undefined
+            );
+            setFolder({ result, files: new Map(files.map(({ name, file }) => [name, file])) });
+        } catch (error) {
+            setError(isSaveParseError(error) ? error.displayMessage : t('archived.upload.error_parse'));
         } finally {
             setLoading(false);
         }
     }
 
+    // 同一存档位的多个版本从旧到新生成进度时间线
+    async function showTimeline(names: string[]) {
+        if (!folder) {
+            return;
+        }
+        const wasm = await import('@/public/wasm-save/');
+        await wasm.default();
+
+        const contents = await Promise.all(
+            names.map(async (name) => new Uint8Array(await folder.files.get(name)!.arrayBuffer()))
+        );
+        setTimeline(wasm.progress_timeline(contents, undefined));
+    }
+
+    function handleFolderChange(e: React.ChangeEvent<HTMLInputElement>) {
+        const files = Array.from(e.target.files || []).map((file) => ({
+            name: file.webkitRelativePath || file.name,
+            file,
+        }));
+        if (files.length > 0) {
+            void processFolder(files);
+        }
+    }
+
     function handleFileChange(e: React.ChangeEvent<HTMLInputElement>) {
         const file = e.target.files?.[0];
         if (file) {
@@ -494,6 +607,14 @@ export const ArchiveUpload: React.FC<ArchiveUploadProps> = ({
         e.preventDefault();
         setIsDragging(false);
 
+        // 拖入目录或多个文件时按存档目录处理
+        const isFolder = e.dataTransfer.files.length > 1
+            || Array.from(e.dataTransfer.items).some((item) => item.webkitGetAsEntry()?.isDirectory);
+        if (isFolder) {
+            void collectDroppedFiles(e.dataTransfer.items).then(processFolder);
+            return;
+        }
+
         const file = e.dataTransfer.files?.[0];
         if (file) {
             void processTextFile(file);
@@ -539,6 +660,14 @@ export const ArchiveUpload: React.FC<ArchiveUploadProps> = ({
                     onChange={handleFileChange}
                     className="hidden"
                 />
+                <input
+                    ref={folderInputRef}
+                    type="file"
+                    multiple
+                    {...{ webkitdirectory: '' }}
+                    onChange={handleFolderChange}
+                    className="hidden"
+                />
 
                 <div className="space-y-4">
                     <svg
@@ -562,6 +691,12 @@ export const ArchiveUpload: React.FC<ArchiveUploadProps> = ({
                         >
                             {t('archived.upload.button')}
                         </button>
+                        <button
+                            onClick={() => folderInputRef.current?.click()}
+                            className="ml-2 inline-flex items-center px-4 py-2 border border-gray-300 text-sm font-medium rounded-md text-gray-700 bg-white hover:bg-gray-50"
+                        >
+                            {t('archived.upload.folder_button')}
+                        </button>
                         <p className="mt-2 text-sm text-gray-500">
                             {t('archived.upload.drag_drop')}
                         </p>
@@ -572,13 +707,42 @@ export const ArchiveUpload: React.FC<ArchiveUploadProps> = ({
                 </div>
             </div>
 
+            {/* 存档目录 */}
+            {folder && (
+                <SaveFolderPanel
+                    folder={folder.result}
+                    onSelect={(name) => {
+                        const file = folder.files.get(name);
+                        if (file) {
+                            void parseSave(file);
+                        }
+                    }}
+                    onTimeline={(names) => void showTimeline(names)}
+                />
+            )}
+            {timeline && <ProgressTimeline timeline={timeline} />}
+
             {/* 加载状态 */}
             {loading && (
                 <div className="mt-8 bg-white rounded-lg shadow p-6 text-center">
                     <div className="inline-block animate-spin rounded-full h-8 w-8 border-b-2 border-blue-600"></div>
                     <p className="mt-2 text-gray-600">
-                        {t('archived.upload.loading')}
+                        {progressText(progress)}
                     </p>
+                    {progress?.stage === 'section' && (
+                        <div className="mt-2 h-2 bg-gray-200 rounded-full overflow-hidden">
+                            <div
+                                className="h-full bg-blue-600"
+                                style={{ width: `${(progress.done / progress.total) * 100}%` }}
+                            />
+                        </div>
+                    )}
+                    <button
+                        onClick={() => abortRef.current?.abort()}
+                        className="mt-4 px-4 py-2 border border-gray-300 text-gray-700 rounded-md hover:bg-gray-50"
+                    >
+                        {t('archived.upload.cancel')}
+                    </button>
                 </div>
             )}
 
@@ -625,14 +789,6 @@ export const ArchiveUpload: React.FC<ArchiveUploadProps> = ({
                                     {archive.version}
                                 </p>
                             </div>
-                            <div>
-                                <p className="text-sm text-gray-500">
-                                    {t('archived.basic_info.level')}
-                                </p>
-                                <p className="mt-1 text-lg font-medium text-gray-900">
-                                    {archive.level}
-                                </p>
-                            </div>
                             <div>
                                 <p className="text-sm text-gray-500">
                                     {t('archived.basic_info.exp')}
@@ -676,6 +832,16 @@ export const ArchiveUpload: React.FC<ArchiveUploadProps> = ({
                         />
                     )}
 
+                    {/* 分享配装 */}
+                    <div className="text-right">
+                        <button
+                            onClick={() => void shareBuild()}
+                            className="px-4 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700"
+                        >
+                            {buildCopied ? t('archived.build.copied') : t('archived.build.share')}
+                        </button>
+                    </div>
+
                     {/* 主钥匙 */}
                     {archive.masterKeys &&
                         archive.masterKeys.length > 0 && (
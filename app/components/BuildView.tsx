@@ -0,0 +1,47 @@
+'use client';
+
+import React, { useEffect, useState } from 'react';
+import { useSearchParams } from 'next/navigation';
+import { useTranslations } from 'next-intl';
+import { isSaveParseError, Loadout } from '@/app/[locale]/archived/types';
+import { Item } from '@/app/types/item';
+import { ItemLinkProps } from '@/app/components/ItemCard';
+import LoadoutPanel from '@/app/components/LoadoutPanel';
+
+type BuildViewProps = {
+    items: Item[];
+} & Pick<ItemLinkProps, 'locale'>;
+
+// 根据链接中的分享码显示配装，不需要原存档
+const BuildView: React.FC<BuildViewProps> = ({ items, locale }) => {
+    const t = useTranslations();
+    const code = useSearchParams().get('code');
+    const [loadout, setLoadout] = useState<Loadout | null>(null);
+    const [error, setError] = useState<string | null>(null);
+
+    useEffect(() => {
+        if (!code) {
+            setError(t('archived.build.invalid'));
+            return;
+        }
+        (async () => {
+            const wasm = await import('@/public/wasm-save/');
+            await wasm.default();
+            setLoadout(wasm.decode_build(code));
+        })().catch((error) => {
+            setError(isSaveParseError(error) ? error.displayMessage : t('archived.build.invalid'));
+        });
+    }, [code, t]);
+
+    return (
+        <>
+            <h1 className="text-3xl font-bold text-gray-900 text-center mb-8">
+                {t('archived.build.title')}
+            </h1>
+            {error && <p className="text-center text-red-600">{error}</p>}
+            {loadout && <LoadoutPanel loadout={loadout} items={items} locale={locale} />}
+        </>
+    );
+};
+
+export default BuildView;
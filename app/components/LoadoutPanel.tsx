@@ -0,0 +1,84 @@
+'use client';
+
+import React from 'react';
+import Image from 'next/image';
+import { useTranslations } from 'next-intl';
+import { Loadout, LoadoutItem } from '@/app/[locale]/archived/types';
+import { Item } from '@/app/types/item';
+import { ItemLinkProps } from '@/app/components/ItemCard';
+import { LocaleLink } from '@/app/components/LocaleLink';
+import { getQualityConfig } from '@/app/constants';
+import { getItemName } from '@/app/utils/lang';
+
+type LoadoutPanelProps = {
+    loadout: Loadout;
+    items: Item[];
+} & Pick<ItemLinkProps, 'locale'>;
+
+// 按游戏中的槽位名称排列，用于显示槽位翻译
+function loadoutSlots(loadout: Loadout): [string, LoadoutItem | null][] {
+    return [
+        ['PrimaryWeapon', loadout.primaryWeapon],
+        ['SecondaryWeapon', loadout.secondaryWeapon],
+        ['MeleeWeapon', loadout.meleeWeapon],
+        ['Helmat', loadout.helmet],
+        ['Armor', loadout.armor],
+        ['FaceMask', loadout.faceMask],
+        ['Headset', loadout.headset],
+        ['Backpack', loadout.backpack],
+        ...loadout.totems.map((totem, idx) => [`Totem${idx + 1}`, totem] as [string, LoadoutItem]),
+    ];
+}
+
+const LoadoutPanel: React.FC<LoadoutPanelProps> = ({ loadout, items, locale }) => {
+    const t = useTranslations();
+
+    return (
+        <div className="grid grid-cols-2 md:grid-cols-5 gap-6 p-4 bg-gray-800 rounded-lg shadow">
+            {loadoutSlots(loadout).map(([slotName, slotItem]) => {
+                const item = items.find((item) => item.id === slotItem?.typeID);
+                const qualityConfig = getQualityConfig(item?.quality || 0);
+                const attachments = Object.entries(slotItem?.slots || {});
+
+                return (
+                    <div key={slotName}>
+                        <LocaleLink
+                            href={item ? `/inventory/${item.id}` : ''}
+                            locale={locale}
+                            className="relative cursor-pointer transform transition-all duration-200 hover:scale-105"
+                        >
+                            <div className={`border-2 p-4 ${qualityConfig.bgColor} relative rounded-2xl overflow-hidden aspect-square`}>
+                                <Image
+                                    src={item ? `/images/${item.icon}` : '/images/cross.webp'}
+                                    alt={item?.displayName || 'not found'}
+                                    fill
+                                    loading="lazy"
+                                    className="object-contain"
+                                />
+                                {item && (
+                                    <div className={`text-center right-0 dark:bg-black/50 p-1 ${qualityConfig.textColor} absolute bottom-0`}>
+                                        {getItemName(t, item)}
+                                    </div>
+                                )}
+                            </div>
+                        </LocaleLink>
+                        <p className="text-white font-medium text-center text-sm py-2">
+                            {t(`slot.${slotName}`)}
+                        </p>
+                        {attachments.map(([attachmentSlot, attachment]) => {
+                            const attachmentItem = items.find((item) => item.id === attachment?.typeID);
+                            return (
+                                <p key={attachmentSlot} className="text-gray-300 text-xs text-center">
+                                    {t(`slot.accessories.${attachmentSlot}`)}:{' '}
+                                    {attachmentItem ? getItemName(t, attachmentItem) : attachment?.typeID}
+                                </p>
+                            );
+                        })}
+                    </div>
+                );
+            })}
+        </div>
+    );
+};
+
+export default LoadoutPanel;
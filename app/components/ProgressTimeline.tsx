@@ -0,0 +1,67 @@
+'use client';
+
+import React from 'react';
+import { useTranslations } from 'next-intl';
+import { Timeline, TimelinePoint } from '@/app/[locale]/archived/types';
+
+type ProgressTimelineProps = {
+    timeline: Timeline;
+};
+
+const WIDTH = 320;
+const HEIGHT = 120;
+
+// 单个指标随 SaveTime 变化的折线
+const Sparkline: React.FC<{ title: string; points: TimelinePoint[]; value: (point: TimelinePoint) => number }> = ({
+    title,
+    points,
+    value,
+}) => {
+    const times = points.map((point) => point.saveTime);
+    const values = points.map(value);
+    const [minTime, maxTime] = [Math.min(...times), Math.max(...times)];
+    const [minValue, maxValue] = [Math.min(...values), Math.max(...values)];
+    const x = (time: number) => (maxTime === minTime ? WIDTH / 2 : ((time - minTime) / (maxTime - minTime)) * WIDTH);
+    const y = (v: number) => (maxValue === minValue ? HEIGHT / 2 : HEIGHT - ((v - minValue) / (maxValue - minValue)) * HEIGHT);
+    const path = points.map((point, idx) => `${x(times[idx])},${y(values[idx])}`).join(' ');
+
+    return (
+        <div className="p-4">
+            <p className="text-sm text-gray-500">
+                {title}: {values[values.length - 1]}
+            </p>
+            <svg viewBox={`-4 -4 ${WIDTH + 8} ${HEIGHT + 8}`} className="w-full h-32">
+                <polyline points={path} fill="none" stroke="currentColor" strokeWidth={2} className="text-blue-600" />
+                {points.map((point, idx) => (
+                    <circle key={point.index} cx={x(times[idx])} cy={y(values[idx])} r={3} className="fill-blue-600" />
+                ))}
+            </svg>
+        </div>
+    );
+};
+
+const ProgressTimeline: React.FC<ProgressTimelineProps> = ({ timeline }) => {
+    const t = useTranslations();
+    if (timeline.points.length === 0) {
+        return null;
+    }
+
+    return (
+        <div className="mt-8 bg-white dark:bg-gray-800 rounded-lg shadow">
+            <div className="grid grid-cols-1 md:grid-cols-3">
+                <Sparkline title={t('archived.basic_info.money')} points={timeline.points} value={(point) => point.money} />
+                <Sparkline title={t('archived.basic_info.exp')} points={timeline.points} value={(point) => point.exp} />
+                <Sparkline title={t('archived.kills.title')} points={timeline.points} value={(point) => point.totalKills} />
+            </div>
+            {timeline.issues.length > 0 && (
+                <ul className="px-6 pb-4 text-sm text-amber-600">
+                    {timeline.issues.map((issue, idx) => (
+                        <li key={idx}>{t(`archived.timeline.${issue.kind}`, { index: issue.index + 1 })}</li>
+                    ))}
+                </ul>
+            )}
+        </div>
+    );
+};
+
+export default ProgressTimeline;
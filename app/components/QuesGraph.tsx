@@ -1,7 +1,9 @@
 'use client'
 import { nanoid } from 'nanoid';
+import { useEffect, useState } from 'react';
 
 import { GraphConnection, GraphNode } from '../types/quest';
+import { Archive, QuestProgress } from '../[locale]/archived/types';
 import { ReactFlow, Background, Controls, Node, Edge } from '@xyflow/react';
 import '@xyflow/react/dist/style.css';
 
@@ -11,12 +13,50 @@ interface QuestListProps {
     // searchTerm: string;
 }
 
+// 读取存档页面保存的解析结果，按任务 id 索引进度
+function loadQuestProgress(): Map<number, QuestProgress> {
+    const progress = new Map<number, QuestProgress>();
+    try {
+        const stored = localStorage.getItem('archived');
+        const archive = stored ? (JSON.parse(stored) as Partial<Archive>) : null;
+        archive?.questProgress?.forEach(item => progress.set(item.id, item));
+    } catch {
+        // 旧版本或损坏的缓存，忽略
+    }
+    return progress;
+}
+
+function progressStyle(progress?: QuestProgress) {
+    if (!progress) {
+        return undefined;
+    }
+    if (progress.complete) {
+        return { border: '2px solid #22c55e' };
+    }
+    return progress.active ? { border: '2px solid #f59e0b' } : undefined;
+}
+
+function progressLabel(name: string, progress?: QuestProgress) {
+    if (!progress || progress.tasksTotal === 0) {
+        return name;
+    }
+    return `${name}\n[${progress.tasksFinished}/${progress.tasksTotal}]`;
+}
+
 export default function QuestGraph({ edges, nodes }: QuestListProps) {
+    const [questProgress, setQuestProgress] = useState<Map<number, QuestProgress>>(new Map());
+
+    useEffect(() => {
+        setQuestProgress(loadQuestProgress());
+    }, []);
+
     const reactFlowNodes: Node[] = nodes.map((node: GraphNode) => {
+        const progress = node.questID === undefined ? undefined : questProgress.get(node.questID);
         return {
             id: node.$id,
             position: node.position,
-            data: { label: node.name },
+            data: { label: progressLabel(node.name, progress) },
+            style: progressStyle(progress),
             // type: 'input',
         } as Node
     });
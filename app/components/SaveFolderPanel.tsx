@@ -0,0 +1,71 @@
+'use client';
+
+import React from 'react';
+import { useTranslations } from 'next-intl';
+import { SaveFolder } from '@/app/[locale]/archived/types';
+
+type SaveFolderPanelProps = {
+    folder: SaveFolder;
+    onSelect: (name: string) => void;
+    onTimeline: (names: string[]) => void;
+};
+
+// 存档目录中各存档位选中的版本，点击后加载对应文件
+const SaveFolderPanel: React.FC<SaveFolderPanelProps> = ({ folder, onSelect, onTimeline }) => {
+    const t = useTranslations();
+
+    return (
+        <div className="mt-8 bg-white dark:bg-gray-800 rounded-lg shadow divide-y divide-gray-200 dark:divide-gray-700">
+            {folder.slots.map((slot) => (
+                <div key={slot.slot} className="px-6 py-4">
+                    <div className="flex items-center justify-between">
+                        <div>
+                            <p className="font-semibold text-gray-900 dark:text-white">{slot.slot}</p>
+                            {slot.summary && (
+                                <p className="text-sm text-gray-500">
+                                    {t('archived.basic_info.exp')} {slot.summary.exp} ·{' '}
+                                    {t('archived.basic_info.money')} {slot.summary.money} ·{' '}
+                                    {t('archived.basic_info.save_time')}{' '}
+                                    {new Date(slot.summary.saveTime * 1000).toLocaleString()}
+                                </p>
+                            )}
+                        </div>
+                        <div>
+                            {slot.files.filter((file) => !file.error).length > 1 && (
+                                <button
+                                    onClick={() =>
+                                        // files 按 SaveTime 从新到旧排列，时间线需要从旧到新
+                                        onTimeline(slot.files.filter((file) => !file.error).map((file) => file.name).reverse())
+                                    }
+                                    className="mr-2 px-4 py-2 border border-gray-300 text-gray-700 rounded-md hover:bg-gray-50"
+                                >
+                                    {t('archived.slots.timeline')}
+                                </button>
+                            )}
+                            {slot.selected && (
+                                <button
+                                    onClick={() => onSelect(slot.selected!)}
+                                    className="px-4 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700"
+                                >
+                                    {t('archived.slots.load')}
+                                </button>
+                            )}
+                        </div>
+                    </div>
+                    {slot.primaryError && (
+                        <p className="mt-2 text-sm text-amber-600">
+                            {slot.selected
+                                ? t('archived.slots.fallback', { file: slot.selected })
+                                : t('archived.slots.unusable')}
+                            {slot.primaryError.kind === 'missing_file'
+                                ? ` (${slot.primaryError.name})`
+                                : ` (${slot.primaryError.kind})`}
+                        </p>
+                    )}
+                </div>
+            ))}
+        </div>
+    );
+};
+
+export default SaveFolderPanel;
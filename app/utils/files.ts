@@ -0,0 +1,38 @@
+// 读取拖入的文件或目录，返回文件及其相对路径
+export type NamedFile = {
+    name: string;
+    file: File;
+};
+
+function readEntries(reader: FileSystemDirectoryReader): Promise<FileSystemEntry[]> {
+    return new Promise((resolve, reject) => reader.readEntries(resolve, reject));
+}
+
+async function collectEntry(entry: FileSystemEntry, files: NamedFile[]) {
+    if (entry.isFile) {
+        const file = await new Promise<File>((resolve, reject) =>
+            (entry as FileSystemFileEntry).file(resolve, reject)
+        );
+        files.push({ name: entry.fullPath.replace(/^\//, ''), file });
+        return;
+    }
+    const reader = (entry as FileSystemDirectoryEntry).createReader();
+    // readEntries 每次最多返回一部分条目，需要读到为空
+    for (let batch = await readEntries(reader); batch.length > 0; batch = await readEntries(reader)) {
+        for (const child of batch) {
+            await collectEntry(child, files);
+        }
+    }
+}
+
+export async function collectDroppedFiles(items: DataTransferItemList): Promise<NamedFile[]> {
+    // 必须在第一次 await 之前取出所有条目
+    const entries = Array.from(items)
+        .map((item) => item.webkitGetAsEntry())
+        .filter((entry): entry is FileSystemEntry => entry !== null);
+    const files: NamedFile[] = [];
+    for (const entry of entries) {
+        await collectEntry(entry, files);
+    }
+    return files;
+}
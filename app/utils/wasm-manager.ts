@@ -6,6 +6,8 @@ import {
     run_web,
     default as init,
 } from '@/public/wasm';
+import WasmWorkerClient from '@/app/utils/wasm-worker';
+import type { BakeSceneRequest, BakeStage } from '@/app/workers/scene-worker';
 
 export
 enum SceneLoadingState {
@@ -71,6 +73,7 @@ type WasmModule = {
     Commander: {
         change_scene: (path: string) => void;
         set_scene_path: (path: string) => void;
+        set_scene_baker: (baker: (scenePath: string) => Promise<Uint8Array>) => void;
     };
 };
 
@@ -78,6 +81,9 @@ export default class WasmManager {
     private static instance: WasmManager;
     private wasmModule: SyncInitInput & WasmModule | null = null;
     private initialized: boolean = false;
+    // .unity 场景在专用 Worker 中解析烘焙，切换场景时取消上一次还没完成的烘焙
+    private sceneWorker: WasmWorkerClient<BakeSceneRequest, BakeStage, Uint8Array> | null = null;
+    private sceneAbort: AbortController | null = null;
     // private commander: Commander | null = null
 
     private constructor() {}
@@ -99,6 +105,7 @@ export default class WasmManager {
             await wasm.default();
             console.log(wasm, 'wasm');
             this.wasmModule = wasm as unknown as SyncInitInput & WasmModule;
+            this.wasmModule.Commander.set_scene_baker((scenePath) => this.bakeScene(scenePath));
             // this.commander = new this.wasmModule.Commander();
             this.initialized = true;
             console.log('WASM module initialized successfully');
@@ -161,6 +168,21 @@ export default class WasmManager {
         return this.wasmModule.Commander.change_scene(path);
     }
 
+    private bakeScene(scenePath: string): Promise<Uint8Array> {
+        this.sceneWorker ??= new WasmWorkerClient(
+            () => new Worker(new URL('../workers/scene-worker.ts', import.meta.url), { type: 'module' })
+        );
+        this.sceneAbort?.abort();
+        this.sceneAbort = new AbortController();
+        return this.sceneWorker.run(
+            { scenePath },
+            {
+                onProgress: (stage) => console.log('Baking scene:', scenePath, stage),
+                signal: this.sceneAbort.signal,
+            }
+        );
+    }
+
     runWeb() {
         if (!this.initialized || !this.wasmModule) {
             throw new Error('WASM module not initialized');
@@ -0,0 +1,113 @@
+'use client';
+
+// 在专用 Worker 中运行 wasm 任务的主线程客户端，消息协议见 wasm-worker crate：
+// 请求为 `{ id, cancel, ...payload }`，Worker 按 id 回复若干 progress，最后以 done / error / cancelled 结束。
+
+export type WorkerEvent<P, R> =
+    | { type: 'progress'; id: number; progress: P }
+    | { type: 'done'; id: number; result: R }
+    | { type: 'error'; id: number; error: unknown }
+    | { type: 'cancelled'; id: number };
+
+export type WorkerRequest<T> = T & {
+    id: number;
+    // 取消标志，主线程写入 1 后 Worker 在下一个阶段开始前停止
+    cancel?: Int32Array;
+};
+
+export type RunOptions<P> = {
+    onProgress?: (progress: P) => void;
+    signal?: AbortSignal;
+};
+
+type Pending<P, R> = {
+    resolve: (result: R) => void;
+    reject: (error: unknown) => void;
+    onProgress?: (progress: P) => void;
+};
+
+function abortError() {
+    return new DOMException('Aborted', 'AbortError');
+}
+
+export function isAbortError(error: unknown): boolean {
+    return error instanceof DOMException && error.name === 'AbortError';
+}
+
+export default class WasmWorkerClient<T, P, R> {
+    private worker: Worker | null = null;
+    private nextId = 1;
+    private pending = new Map<number, Pending<P, R>>();
+
+    constructor(private readonly create: () => Worker) {}
+
+    run(payload: T, { onProgress, signal }: RunOptions<P> = {}): Promise<R> {
+        if (signal?.aborted) {
+            return Promise.reject(abortError());
+        }
+        const id = this.nextId++;
+        // 没有 SharedArrayBuffer（页面未跨源隔离）时只能终止整个 Worker 来取消
+        const cancel = typeof SharedArrayBuffer !== 'undefined' && self.crossOriginIsolated
+            ? new Int32Array(new SharedArrayBuffer(4))
+            : undefined;
+
+        return new Promise<R>((resolve, reject) => {
+            this.pending.set(id, { resolve, reject, onProgress });
+            signal?.addEventListener('abort', () => {
+                if (!this.pending.has(id)) {
+                    return;
+                }
+                if (cancel) {
+                    Atomics.store(cancel, 0, 1);
+                } else {
+                    this.terminate(abortError());
+                }
+            }, { once: true });
+
+            const request: WorkerRequest<T> = { ...payload, id, cancel };
+            this.ensureWorker().postMessage(request);
+        });
+    }
+
+    // 终止 Worker，所有未完成的任务以 error 拒绝，下次 run 时重新创建
+    terminate(error: unknown = abortError()) {
+        this.worker?.terminate();
+        this.worker = null;
+        const pending = [...this.pending.values()];
+        this.pending.clear();
+        pending.forEach((task) => task.reject(error));
+    }
+
+    private ensureWorker(): Worker {
+        if (this.worker) {
+            return this.worker;
+        }
+        const worker = this.create();
+        worker.onmessage = (event: MessageEvent<WorkerEvent<P, R>>) => this.handle(event.data);
+        worker.onerror = (event: ErrorEvent) => this.terminate(new Error(event.message));
+        this.worker = worker;
+        return worker;
+    }
+
+    private handle(event: WorkerEvent<P, R>) {
+        const task = this.pending.get(event.id);
+        if (!task) {
+            return;
+        }
+        switch (event.type) {
+            case 'progress':
+                task.onProgress?.(event.progress);
+                return;
+            case 'done':
+                task.resolve(event.result);
+                break;
+            case 'error':
+                task.reject(event.error);
+                break;
+            case 'cancelled':
+                task.reject(abortError());
+                break;
+        }
+        this.pending.delete(event.id);
+    }
+}
@@ -0,0 +1,17 @@
+// 在专用 Worker 中解析存档，避免大存档阻塞页面；进度与结果由 wasm 直接 postMessage 发回
+import init, { worker_process_file } from '@/public/wasm-save/';
+import type { WorkerRequest } from '@/app/utils/wasm-worker';
+
+export type ParseSaveRequest = {
+    data: Uint8Array;
+    password?: string;
+    catalog?: string;
+};
+
+const ready = init();
+
+self.onmessage = async (event: MessageEvent<WorkerRequest<ParseSaveRequest>>) => {
+    const { id, data, password, catalog, cancel } = event.data;
+    await ready;
+    worker_process_file(id, data, password, catalog, cancel);
+};
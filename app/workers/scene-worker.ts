@@ -0,0 +1,18 @@
+// 在专用 Worker 中解析并烘焙 .unity 场景，主线程的渲染器只需上传 GPU 资源；进度与烘焙包由 wasm 直接 postMessage 发回
+import init, { worker_bake_scene } from '@/public/wasm/';
+import type { WorkerRequest } from '@/app/utils/wasm-worker';
+
+export type BakeSceneRequest = {
+    scenePath: string;
+};
+
+// 与 wgpu-renderer 的 BakeStage 一致
+export type BakeStage = 'manifest' | 'scene' | 'prefabs' | 'assets' | 'bake';
+
+const ready = init();
+
+self.onmessage = async (event: MessageEvent<WorkerRequest<BakeSceneRequest>>) => {
+    const { id, scenePath, cancel } = event.data;
+    await ready;
+    await worker_bake_scene(id, scenePath, cancel);
+};
@@ -0,0 +1,124 @@
+use criterion::{BatchSize, Criterion, Throughput, criterion_group, criterion_main};
+use savefile_parse::archived::Inventory;
+use savefile_parse::extractor::SaveData;
+use savefile_parse::format::{self, fix_json_format};
+use savefile_parse::parse_archive;
+use serde::Deserialize;
+use serde_json::Value;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const SAMPLE: &str = include_str!("../fixtures/sample.sav");
+
+// 统计堆内存峰值
+struct PeakAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOC: PeakAlloc = PeakAlloc;
+
+fn peak_during<T>(f: impl FnOnce() -> T) -> usize {
+    let base = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(base, Ordering::Relaxed);
+    drop(black_box(f()));
+    PEAK.load(Ordering::Relaxed) - base
+}
+
+// 在样例存档基础上追加大量击杀计数，并把仓库替换为大量物品，模拟长期游玩后的大存档
+fn large_save(items: usize, counters: usize) -> String {
+    let mut text = SAMPLE
+        .replacen("\"Inventory/PlayerStorage\"", "\"Inventory/PlayerStorage_Sample\"", 1)
+        .trim_end()
+        .trim_end_matches('}')
+        .trim_end()
+        .to_string();
+
+    for i in 0..counters {
+        text.push_str(&format!(",\n\t\"Count/Kills/Bench_{}\" : {{\n\t\t\"__type\" : \"int\"{}\n\t}}", i, i));
+    }
+
+    text.push_str(",\n\t\"Inventory/PlayerStorage\" : {\n\t\t\"__type\" : \"ItemStatsSystem.Data.InventoryData,ItemStatsSystem\",\n\t\t\"value\" : {\n\t\t\t\"capacity\" : 99999,\n\t\t\t\"entries\" : [");
+    for i in 0..items {
+        if i > 0 {
+            text.push(',');
+        }
+        text.push_str(&format!(
+            "\n\t\t\t\t{{\"inventoryPosition\" : {i},\"itemTreeData\" : {{\"rootInstanceID\" : {i},\"entries\" : [{{\"instanceID\" : {i},\"typeID\" : {},\"variables\" : [{{\"key\" : \"Durability\",\"dataType\" : 1,\"data\" : \"AACvQg==\",\"display\" : true}},{{\"key\" : \"Count\",\"dataType\" : 2,\"data\" : \"HgAAAA==\",\"display\" : true}}],\"slotContents\" : [],\"inventory\" : [],\"inventorySortLocks\" : []}}]}}}}",
+            i % 900
+        ));
+    }
+    text.push_str("\n\t\t\t]\n\t\t}\n\t}\n}");
+    text
+}
+
+#[derive(Deserialize)]
+struct Wrapper<T> {
+    value: T,
+}
+
+// 旧做法：整个存档先转成 Value 树，每次读取段落再 clone 后反序列化
+fn value_tree_storage(save: &str) -> Inventory {
+    let data: HashMap<String, Value> = format::from_str(save).unwrap();
+    let wrapper: Wrapper<Inventory> = serde_json::from_value(data["Inventory/PlayerStorage"].clone()).unwrap();
+    wrapper.value
+}
+
+fn lazy_storage(save: String) -> Inventory {
+    SaveData::from_es3(save).unwrap().get_player_storage().unwrap().unwrap()
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let save = large_save(20_000, 2_000);
+
+    let mib = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+    eprintln!(
+        "存档 {:.1} MiB，读取仓库时的内存峰值：Value 树 {:.1} MiB，按需解析 {:.1} MiB",
+        mib(save.len()),
+        mib(peak_during(|| value_tree_storage(&save))),
+        mib(peak_during(|| lazy_storage(save.clone())) - save.len()),
+    );
+
+    let mut group = c.benchmark_group("large_save");
+    group.throughput(Throughput::Bytes(save.len() as u64));
+    group.sample_size(20);
+
+    group.bench_function("index_sections", |b| {
+        b.iter(|| format::index_sections(black_box(&save)).unwrap())
+    });
+    group.bench_function("fix_json_format_then_serde_json", |b| {
+        b.iter(|| serde_json::from_str::<HashMap<String, Value>>(&fix_json_format(black_box(&save))).unwrap())
+    });
+    group.bench_function("value_tree_storage", |b| {
+        b.iter(|| value_tree_storage(black_box(&save)))
+    });
+    group.bench_function("lazy_storage", |b| {
+        b.iter_batched(|| save.clone(), lazy_storage, BatchSize::LargeInput)
+    });
+    group.bench_function("parse_archive", |b| {
+        b.iter(|| parse_archive(black_box(save.as_bytes())).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse);
+criterion_main!(benches);
@@ -0,0 +1,103 @@
+// WASM 版本的解析耗时与内存，与 benches/parse.rs 使用同样构造的大存档，便于和原生结果对照
+//
+//   wasm-pack build --release --target nodejs --out-dir target/wasm-bench -- --no-default-features --features bench
+//   node --expose-gc benches/wasm.mjs
+//
+// process_file 是前端实际调用的入口，包含把 Archive 转换为 JS 对象的开销；
+// value_tree_storage / lazy_storage 对应 parse.rs 中旧的 Value 树做法与按需解析，只读取仓库
+import { readFileSync } from 'node:fs';
+import { createRequire } from 'node:module';
+import { performance } from 'node:perf_hooks';
+
+const require = createRequire(import.meta.url);
+const SAMPLE = readFileSync(new URL('../fixtures/sample.sav', import.meta.url), 'utf8');
+
+// 与 parse.rs 中的 large_save 相同：追加击杀计数，并把仓库替换为大量物品
+function largeSave(items, counters) {
+    let text = SAMPLE.replace('"Inventory/PlayerStorage"', '"Inventory/PlayerStorage_Sample"')
+        .trimEnd()
+        .replace(/}+$/, '')
+        .trimEnd();
+
+    const parts = [text];
+    for (let i = 0; i < counters; i++) {
+        parts.push(`,\n\t"Count/Kills/Bench_${i}" : {\n\t\t"__type" : "int"${i}\n\t}`);
+    }
+
+    parts.push(
+        ',\n\t"Inventory/PlayerStorage" : {\n\t\t"__type" : "ItemStatsSystem.Data.InventoryData,ItemStatsSystem",\n\t\t"value" : {\n\t\t\t"capacity" : 99999,\n\t\t\t"entries" : ['
+    );
+    for (let i = 0; i < items; i++) {
+        parts.push(
+            `${i > 0 ? ',' : ''}\n\t\t\t\t{"inventoryPosition" : ${i},"itemTreeData" : {"rootInstanceID" : ${i},"entries" : [{"instanceID" : ${i},"typeID" : ${i % 900},"variables" : [{"key" : "Durability","dataType" : 1,"data" : "AACvQg==","display" : true},{"key" : "Count","dataType" : 2,"data" : "HgAAAA==","display" : true}],"slotContents" : [],"inventory" : [],"inventorySortLocks" : []}]}}`
+        );
+    }
+    parts.push('\n\t\t\t]\n\t\t}\n\t}\n}');
+    return parts.join('');
+}
+
+function measure(name, bytes, samples, f) {
+    for (let i = 0; i < 3; i++) {
+        f();
+    }
+    const times = [];
+    for (let i = 0; i < samples; i++) {
+        const start = performance.now();
+        f();
+        times.push(performance.now() - start);
+    }
+    times.sort((a, b) => a - b);
+    const median = times[Math.floor(times.length / 2)];
+    const mib = bytes / (1024 * 1024);
+    return `${name}: 中位数 ${median.toFixed(1)} ms，${(mib / (median / 1000)).toFixed(1)} MiB/s`;
+}
+
+const MODULE = require.resolve('../target/wasm-bench/savefile_parse.js');
+
+// 每次重新加载得到新的 WASM 实例；线性内存只增不减，分开实例才能比较各自的峰值
+function loadModule() {
+    delete require.cache[MODULE];
+    return require(MODULE);
+}
+
+const mib = (bytes) => (bytes / (1024 * 1024)).toFixed(1);
+
+// 在新实例中运行一次，返回线性内存的增长量（近似 WASM 堆峰值）和保留结果时的 JS 堆增长量
+function memory(f) {
+    const wasm = loadModule();
+    globalThis.gc?.();
+    const wasmBefore = wasm.__wasm.memory.buffer.byteLength;
+    const jsBefore = process.memoryUsage().heapUsed;
+    const result = f(wasm);
+    globalThis.gc?.();
+    const js = process.memoryUsage().heapUsed - jsBefore;
+    const linear = wasm.__wasm.memory.buffer.byteLength - wasmBefore;
+    return { result, linear, js };
+}
+
+const save = new TextEncoder().encode(largeSave(20_000, 2_000));
+const cases = [
+    ['process_file', (wasm) => wasm.process_file(save, undefined, undefined)],
+    ['value_tree_storage', (wasm) => wasm.bench_value_tree_storage(save)],
+    ['lazy_storage', (wasm) => wasm.bench_lazy_storage(save)],
+];
+
+// 模块初始化时启用了 console_log，测量期间关闭日志输出
+const { log, info, debug } = console;
+console.info = console.debug = () => {};
+console.log = () => {};
+const results = [];
+try {
+    for (const [name, f] of cases) {
+        const { linear, js } = memory(f);
+        const wasm = loadModule();
+        const time = measure(`large_save/${name}`, save.length, 20, () => f(wasm));
+        results.push(`${time}，WASM 内存增长 ${mib(linear)} MiB，JS 堆增长 ${mib(js)} MiB`);
+    }
+} finally {
+    Object.assign(console, { log, info, debug });
+}
+console.log(`存档 ${mib(save.length)} MiB${globalThis.gc ? '' : '（未使用 --expose-gc，JS 堆数据含未回收的垃圾）'}`);
+for (const line of results) {
+    console.log(line);
+}
@@ -0,0 +1,205 @@
+// 由 savefile-parse 的 Rust 类型生成，请勿手动修改
+
+export type Archive = { version: string, exp: number, health: number, gameClock: GameClock, economyData: EconomyData, masterKeys: Array<MasterKey>, deathList: Array<StashData>, quests: QuestData, questProgress: Array<QuestProgress>, kills: Array<Kill>, save_time: number, playerStorage: Inventory, inventorySafe: Inventory, characterItemData: ItemTreeData, itemTrees: ResolvedItemTrees, loadout: Loadout, customFace: CustomFaceSettingData | null, visitedScenes: Array<string>, constructionSites: { [key in string]?: boolean }, merchantStock: { [key in string]?: StockShopData }, valuation?: Valuation | null, warnings: Array<SaveParseError>, };
+
+export type Color = { r: number, g: number, b: number, a: number, };
+
+export type ContainerTotals = { count: number, weight?: number, value: number, };
+
+export type CustomFaceSettingData = { savedSetting: boolean, headSetting: HeadSetting, hairId: number, hairInfo: PartInfo, eyeId: number, eyeInfo: PartInfo, eyebrowId: number, eyebrowInfo: PartInfo, mouthId: number, mouthInfo: PartInfo, tailId: number, tailInfo: PartInfo, footId: number, footInfo: PartInfo, wingId: number, wingInfo: PartInfo, };
+
+export type Delta = { before: number, after: number, delta: number, };
+
+export type EconomyData = { money: number, unlockedItems: Array<number>, unlockesWaitingForConfirm: Array<number>, };
+
+export type Finding = { severity: Severity, code: string, path: string, message: string, };
+
+export type GameClock = { days: number, secondsOfDay: number, realTimePlayedTicks: number, };
+
+export type HeadSetting = { mainColor: Color, headScaleOffset: number, foreheadHeight: number, foreheadRound: number, };
+
+export type Inventory = { capacity: number, entries: Array<InventoryItemData>, };
+
+export type InventoryItem = { position: number, instanceID: number, };
+
+export type InventoryItemData = { inventoryPosition: number, itemTreeData: ItemTreeData, };
+
+export type ItemCountChange = { typeID: number, before: number, after: number, count: number, };
+
+export type ItemEntry = { instanceID: number, typeID: number, variables: Array<ItemVariable>, slotContents: Array<SlotContent>, inventory: Array<InventoryItem>, inventorySortLocks: Array<unknown>, decoded?: { [key in string]?: VariableValue }, info?: ItemInfo | null, };
+
+export type ItemInfo = { name: string, displayName: string, quality: number, price: number, weight?: number, };
+
+export type ItemTreeData = { rootInstanceID: number, entries: Array<ItemEntry>, };
+
+export type ItemVariable = { key: string, dataType: number, data: string, display: boolean, };
+
+export type KeyGroup = { prefix: string, count: number, size: number, 
+/**
+ * 组内出现过的 `__type`，已去重排序
+ */
+typeNames: Array<string>, 
+/**
+ * 组内的键是否全部已有 getter 读取
+ */
+extracted: boolean, };
+
+export type KeyInfo = { key: string, 
+/**
+ * `__type` 的值，段落不是对象或没有该字段时为 None
+ */
+typeName: string | null, 
+/**
+ * 值在原文中的字节数
+ */
+size: number, 
+/**
+ * 是否已有 getter 读取
+ */
+extracted: boolean, 
+/**
+ * 所属分组的前缀
+ */
+group: string | null, };
+
+export type KeyReport = { 
+/**
+ * 按原文顺序排列
+ */
+keys: Array<KeyInfo>, 
+/**
+ * 按前缀排序
+ */
+groups: Array<KeyGroup>, };
+
+export type Kill = { masterName: string, count: number, };
+
+export type KillChange = { monsterName: string, before: number, after: number, delta: number, };
+
+export type Loadout = { primaryWeapon: LoadoutItem | null, secondaryWeapon: LoadoutItem | null, meleeWeapon: LoadoutItem | null, helmet: LoadoutItem | null, armor: LoadoutItem | null, faceMask: LoadoutItem | null, backpack: LoadoutItem | null, headset: LoadoutItem | null, 
+/**
+ * 按槽位顺序排列（Totem1、Totem2 …）
+ */
+totems: Array<LoadoutItem>, 
+/**
+ * 未识别的槽位，原样保留
+ */
+other: { [key in string]?: LoadoutItem }, };
+
+export type LoadoutItem = { typeID: number, slots: { [key in string]?: LoadoutItem }, };
+
+export type MasterKey = { id: number, active: boolean, };
+
+export type ParseProgress = { "stage": "decode" } | { "stage": "fix_format" } | { "stage": "section", name: string, done: number, total: number, } | { "stage": "items" };
+
+export type PartInfo = { radius: number, color: Color, height: number, heightOffset: number, scale: number, twist: number, distanceAngle: number, leftRightAngle: number, };
+
+export type QuestData = { activeQuestsData: Array<QuestSaveData>, historyQuestsData: Array<QuestSaveData>, everInspectedQuest: Array<number>, };
+
+export type QuestProgress = { id: number, active: boolean, complete: boolean, needInspection: boolean, tasksFinished: number, tasksTotal: number, rewardsClaimed: number, rewardsTotal: number, };
+
+export type QuestSaveData = { __type: string, id: number, complete: boolean, needInspection: boolean, questGiverID: number, taskStatus: Array<TaskStatusEntry>, rewardStatus: Array<RewardStatusEntry>, };
+
+export type ResolvedItem = { instanceID: number, typeID: number, variables: { [key in string]?: VariableValue }, slots: { [key in string]?: ResolvedItem }, inventory: { [key in number]?: ResolvedItem }, };
+
+export type ResolvedItemTrees = { characterItemData: ResolvedTree, 
+/**
+ * 键为 inventoryPosition
+ */
+playerStorage: { [key in number]?: ResolvedTree }, inventorySafe: { [key in number]?: ResolvedTree }, 
+/**
+ * 与 deathList 一一对应
+ */
+deathList: Array<ResolvedTree>, };
+
+export type ResolvedTree = { rootInstanceID: number, root: ResolvedItem | null, issues: Array<TreeIssue>, };
+
+export type RewardStatusEntry = { id: number, claimed: boolean, };
+
+export type SaveCandidate = { 
+/**
+ * 传入时的文件名（可能带有相对路径）
+ */
+name: string, backup: boolean, saveTime?: number, 
+/**
+ * 解析失败的原因
+ */
+error?: SaveParseError, };
+
+export type SaveDiff = { schemaVersion: number, money: Delta, exp: Delta, itemsGained: Array<ItemCountChange>, itemsLost: Array<ItemCountChange>, questsCompleted: Array<number>, kills: Array<KillChange>, newDeathStashes: Array<StashSummary>, unlockedItems: Array<number>, };
+
+export type SaveFolder = { 
+/**
+ * 按存档位名称排序
+ */
+slots: Array<SaveSlot>, 
+/**
+ * 不是存档文件而被忽略的文件名
+ */
+ignored: Array<string>, };
+
+export type SaveParseError = { "kind": "wrong_password" } | { "kind": "truncated", message: string, } | { "kind": "decompress", message: string, } | { "kind": "invalid_utf8", valid_up_to: number, } | { "kind": "json_syntax", line: number, column: number, message: string, } | { "kind": "missing_key", key: string, } | { "kind": "missing_file", name: string, } | { "kind": "section_type", key: string, message: string, } | { "kind": "unknown_version", version: string, } | { "kind": "item_variable", instance_id: number, key: string, data_type: number, message: string, } | { "kind": "item_tree", root_instance_id: number, issue: TreeIssue, } | { "kind": "build_code", message: string, } | { "kind": "cancelled" } | { "kind": "serialize", message: string, };
+
+export type SaveSlot = { slot: string, 
+/**
+ * 可用的文件按 SaveTime 从新到旧排列，主文件在时间相同时优先，解析失败的排在最后
+ */
+files: Array<SaveCandidate>, 
+/**
+ * 选中的文件名，所有文件都无法解析时为 None
+ */
+selected: string | null, summary: SlotSummary | null, 
+/**
+ * 主文件缺失或解析失败而改用备份时的原因
+ */
+primaryError?: SaveParseError, };
+
+export type Severity = "warning" | "error";
+
+export type SlotContent = { slot: string, instanceID: number, };
+
+export type SlotSummary = { version: string, exp: number, money: number, days: number, saveTime: number, 
+/**
+ * 解析时产生的警告数量
+ */
+warnings: number, };
+
+export type StashData = { valid: boolean, raidID: number, subSceneID: string, worldPosition: WorldPosition, itemTreeData: ItemTreeData, spawned: boolean, touched: boolean, };
+
+export type StashSummary = { raidID: number, subSceneID: string, itemCount: number, };
+
+export type StockItem = { itemTypeID: number, stock: number, };
+
+export type StockShopData = { lastTimeRefreshedStock: number, stockCounts: Array<StockItem>, };
+
+export type TaskStatusEntry = { id: number, finished: boolean, count?: number | null, };
+
+export type Timeline = { points: Array<TimelinePoint>, 
+/**
+ * 出现过的所有敌人类型，用作击杀折线的图例
+ */
+monsters: Array<string>, issues: Array<TimelineIssue>, };
+
+export type TimelineIssue = { "kind": "unreadable", index: number, error: SaveParseError, } | { "kind": "out_of_order", index: number, previous: number, save_time: number, } | { "kind": "rollback", index: number, metrics: Array<string>, } | { "kind": "gap", index: number, seconds: number, };
+
+export type TimelinePoint = { 
+/**
+ * 在输入中的位置
+ */
+index: number, saveTime: number, 
+/**
+ * GameClock 中的游戏天数
+ */
+days: number, money: number, exp: number, 
+/**
+ * 按敌人类型统计的累计击杀
+ */
+kills: Record<string, number>, totalKills: number, questsCompleted: number, unlockedItems: number, };
+
+export type TreeIssue = { "kind": "missing_root", instance_id: number, } | { "kind": "duplicate_instance", instance_id: number, } | { "kind": "dangling", parent: number, instance_id: number, } | { "kind": "cycle", parent: number, instance_id: number, } | { "kind": "shared_child", parent: number, instance_id: number, } | { "kind": "orphan", instance_id: number, } | { "kind": "too_deep", parent: number, instance_id: number, };
+
+export type Valuation = { character: ContainerTotals, playerStorage: ContainerTotals, inventorySafe: ContainerTotals, deathList: Array<ContainerTotals>, missingTypeIDs: Array<number>, };
+
+export type VariableValue = { "type": "float", "value": number } | { "type": "int", "value": number } | { "type": "bool", "value": boolean } | { "type": "string", "value": string } | { "type": "raw", "value": Array<number> };
+
+export type WorldPosition = { x: number, y: number, z: number, };
@@ -1,10 +1,14 @@
-use std::collections::HashMap;
-use log::info;
+use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use ts_rs::TS;
+use crate::catalog::{ItemInfo, Valuation};
+use crate::error::SaveParseError;
+use crate::extractor::{CustomFaceSettingData, SaveData, StockShopData};
+use crate::loadout::Loadout;
+use crate::tree::ResolvedItemTrees;
+use crate::variable::VariableValue;
 
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct QuestSaveData {
     #[serde(rename = "__type")]
     pub type_name: String,
@@ -14,22 +18,68 @@ pub struct QuestSaveData {
     pub need_inspection: bool,
     #[serde(rename = "questGiverID")]
     pub quest_giver_id: u32,
-    // #[serde(rename = "taskStatus")]
-    // pub task_status: Vec<TaskStatusEntry>,
-    // #[serde(rename = "rewardStatus")]
-    // pub reward_status: Vec<RewardStatusEntry>,
+    #[serde(rename = "taskStatus", default)]
+    pub task_status: Vec<TaskStatusEntry>,
+    #[serde(rename = "rewardStatus", default)]
+    pub reward_status: Vec<RewardStatusEntry>,
+}
+
+/// 任务中单个子目标的进度
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TaskStatusEntry {
+    pub id: i32,
+    pub finished: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<i32>, // 计数类目标（击杀、提交物品等）的当前数量
+}
+
+/// 任务奖励的领取状态
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct RewardStatusEntry {
+    pub id: i32,
+    pub claimed: bool,
+}
+
+/// 单个任务的进度汇总，供任务图叠加显示
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestProgress {
+    pub id: u32,
+    pub active: bool,
+    pub complete: bool,
+    pub need_inspection: bool,
+    pub tasks_finished: usize,
+    pub tasks_total: usize,
+    pub rewards_claimed: usize,
+    pub rewards_total: usize,
+}
+
+impl QuestProgress {
+    fn new(quest: &QuestSaveData, active: bool) -> Self {
+        QuestProgress {
+            id: quest.id,
+            active,
+            complete: quest.complete,
+            need_inspection: quest.need_inspection,
+            tasks_finished: quest.task_status.iter().filter(|task| task.finished).count(),
+            tasks_total: quest.task_status.len(),
+            rewards_claimed: quest.reward_status.iter().filter(|reward| reward.claimed).count(),
+            rewards_total: quest.reward_status.len(),
+        }
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, TS)]
 pub struct GameClock {
     pub days: i32,
-    #[serde(rename = "realTimePlayedTicks")]
-    pub seconds_of_day: i64,
     #[serde(rename = "secondsOfDay")]
-    pub real_time_played_ticks: f64,
+    pub seconds_of_day: f64,
+    #[serde(rename = "realTimePlayedTicks")]
+    #[ts(type = "number")]
+    pub real_time_played_ticks: i64, // .NET Ticks，1 tick = 100 纳秒
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, TS)]
 pub struct QuestData {
     #[serde(rename = "activeQuestsData")]
     pub active_quests: Vec<QuestSaveData>,
@@ -39,7 +89,21 @@ pub struct QuestData {
     pub ever_inspected: Vec<i32>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+impl QuestData {
+    /// 按任务 id 排序的进度汇总，进行中的任务优先于历史记录
+    pub fn progress(&self) -> Vec<QuestProgress> {
+        let mut progress: BTreeMap<u32, QuestProgress> = BTreeMap::new();
+        for quest in &self.history_quests {
+            progress.insert(quest.id, QuestProgress::new(quest, false));
+        }
+        for quest in &self.active_quests {
+            progress.insert(quest.id, QuestProgress::new(quest, true));
+        }
+        progress.into_values().collect()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, TS)]
 pub struct EconomyData {
     pub money: i32,
     #[serde(rename = "unlockedItems")]
@@ -48,13 +112,13 @@ pub struct EconomyData {
     pub unlockes_waiting_for_confirm: Vec<i32>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, TS)]
 pub struct MasterKey{
     pub id: i32,
     pub active: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct StashData {
     pub valid: bool,
@@ -68,14 +132,14 @@ pub struct StashData {
     pub touched: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct WorldPosition {
     pub x: f32,
     pub y: f32,
     pub z: f32,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct ItemTreeData {
     #[serde(rename = "rootInstanceID")]
@@ -83,7 +147,7 @@ pub struct ItemTreeData {
     pub entries: Vec<ItemEntry>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct ItemEntry {
     #[serde(rename = "instanceID")]
@@ -93,10 +157,25 @@ pub struct ItemEntry {
     pub variables: Vec<ItemVariable>,
     pub slot_contents: Vec<SlotContent>,
     pub inventory: Vec<InventoryItem>,
+    #[ts(type = "Array<unknown>")]
     pub inventory_sort_locks: Vec<serde_json::Value>, // 空数组，类型待定
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub decoded: BTreeMap<String, VariableValue>, // 按 key 解码后的变量
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info: Option<ItemInfo>, // 物品数据库中的名称、品质和价格
+}
+
+impl ItemEntry {
+    /// 堆叠数量，记录在 Count 变量里，没有时按 1 个计
+    pub fn stack_count(&self) -> i64 {
+        self.decoded
+            .get("Count")
+            .and_then(|value| value.as_i32())
+            .map_or(1, i64::from)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct ItemVariable {
     pub key: String,
@@ -105,45 +184,46 @@ pub struct ItemVariable {
     pub display: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct SlotContent {
     pub slot: String,
     #[serde(rename = "instanceID")]
     pub instance_id: i32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct InventoryItem {
     pub position: i32,
     #[serde(rename = "instanceID")]
     pub instance_id: i32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct InventoryItemData {
     pub inventory_position: i32,
     pub item_tree_data: ItemTreeData,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct Inventory{
     pub capacity : i32,
     pub entries: Vec<InventoryItemData>,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-struct Kill {
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct Kill {
     #[serde(rename = "masterName")]
-    monster_name: String,
-    count: i64,
+    pub monster_name: String,
+    #[ts(type = "number")]
+    pub count: i64,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
 pub struct Archive{
     pub version: String,// 版本号
-    pub level: i32,
+    // 存档只记录经验，等级要按游戏的等级经验表换算；拿到经验表之前不提供等级字段
     pub exp: i32,
     pub health: f32,
     #[serde(rename = "gameClock")]
@@ -155,7 +235,8 @@ pub struct Archive{
     #[serde(rename = "deathList")]
     pub death_list: Vec<StashData>,// 死亡列表？
     pub quests: QuestData,
-    pub inventory: Vec<i16>,
+    #[serde(rename = "questProgress", default)]
+    pub quest_progress: Vec<QuestProgress>, // 每个任务的完成进度
     pub kills: Vec<Kill>, // 击杀
     pub save_time: i32,// 保存时间
     #[serde(rename = "playerStorage")]
@@ -164,26 +245,61 @@ pub struct Archive{
     pub inventory_safe: Inventory, // 背包
     #[serde(rename = "characterItemData")]
     pub character_item_data: ItemTreeData, // Item/MainCharacterItemData
+    #[serde(rename = "itemTrees", default)]
+    pub item_trees: ResolvedItemTrees, // 还原为嵌套结构的物品树
+    #[serde(default)]
+    pub loadout: Loadout, // 角色当前配装
+    #[serde(rename = "customFace", default)]
+    pub custom_face: Option<CustomFaceSettingData>, // 角色外观
+    #[serde(rename = "visitedScenes", default)]
+    pub visited_scenes: Vec<String>, // 去过的场景
+    #[serde(rename = "constructionSites", default)]
+    pub construction_sites: BTreeMap<String, bool>, // 基地建筑是否已建造
+    #[serde(rename = "merchantStock", default)]
+    pub merchant_stock: BTreeMap<String, StockShopData>, // 按商人分组的库存
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valuation: Option<Valuation>, // 提供物品数据库时计算的各容器合计
+    pub warnings: Vec<SaveParseError>, // 解析失败但不影响整体的段落
     // 获取技能等级解锁/GameObject/LevelManager/_boundGraphSerialization
 }
 
 impl Archive {
-    pub fn extract_kill_counts(&mut self, data: &HashMap<String, Value>) {
-        let kills = data.iter()
-            .filter(|(key, _)| key.starts_with("Count/Kills/"))
-            .filter_map(|(key, value)| {
-                // 提取敌人类型名称 (例如: "Count/Kills/Cname_Prison" -> "Cname_Prison")
-                let enemy_type = key.strip_prefix("Count/Kills/")?;
-                // 获取 count 值
-                let count = value.get("value")?.as_i64()?;
-                Some(Kill {
-                    monster_name: enemy_type.to_string(),
-                    count,
-                })
-            })
+    /// 存档中所有的物品树：角色装备、仓库、安全箱以及死亡掉落
+    pub fn item_trees(&self) -> impl Iterator<Item = &ItemTreeData> {
+        std::iter::once(&self.character_item_data)
+            .chain(self.player_storage.entries.iter().map(|e| &e.item_tree_data))
+            .chain(self.inventory_safe.entries.iter().map(|e| &e.item_tree_data))
+            .chain(self.death_list.iter().map(|s| &s.item_tree_data))
+    }
+
+    pub fn item_trees_mut(&mut self) -> impl Iterator<Item = &mut ItemTreeData> {
+        std::iter::once(&mut self.character_item_data)
+            .chain(self.player_storage.entries.iter_mut().map(|e| &mut e.item_tree_data))
+            .chain(self.inventory_safe.entries.iter_mut().map(|e| &mut e.item_tree_data))
+            .chain(self.death_list.iter_mut().map(|s| &mut s.item_tree_data))
+    }
+
+    /// 解码所有物品变量，失败的记录到 warnings
+    pub fn decode_item_variables(&mut self) {
+        let warnings: Vec<SaveParseError> = self
+            .item_trees_mut()
+            .flat_map(|tree| tree.decode_variables())
             .collect();
+        self.warnings.extend(warnings);
+    }
 
-        self.kills = kills;
+    /// 提取击杀统计，类型不符的段落记录到 warnings
+    pub fn extract_kill_counts(&mut self, save_data: &SaveData) {
+        // 键形如 "Count/Kills/Cname_Prison"，按敌人类型名称排序
+        let (kills, errors) = save_data.get_kill_stats();
+        self.warnings.extend(errors);
+        self.kills = kills
+            .into_iter()
+            .map(|(monster_name, count)| Kill {
+                monster_name,
+                count: count.into(),
+            })
+            .collect();
     }
 }
 
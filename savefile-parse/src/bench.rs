@@ -0,0 +1,41 @@
+//! 供 benches/wasm.mjs 调用的对照实现，只在 `bench` feature 下编译，不进入前端使用的构建
+
+use std::collections::HashMap;
+use serde::Deserialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use crate::archived::Inventory;
+use crate::extractor::SaveData;
+use crate::format;
+
+#[derive(Deserialize)]
+struct Wrapper<T> {
+    value: T,
+}
+
+fn to_text(data: &[u8]) -> Result<String, JsValue> {
+    String::from_utf8(data.to_vec()).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// 旧做法：整个存档先转成 Value 树，再 clone 段落反序列化仓库，与 benches/parse.rs 的 value_tree_storage 相同
+#[wasm_bindgen]
+pub fn bench_value_tree_storage(data: &[u8]) -> Result<usize, JsValue> {
+    let save = to_text(data)?;
+    let data: HashMap<String, Value> = format::from_str(&save).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let section = data
+        .get("Inventory/PlayerStorage")
+        .cloned()
+        .ok_or_else(|| JsValue::from_str("缺少 Inventory/PlayerStorage"))?;
+    let wrapper: Wrapper<Inventory> = serde_json::from_value(section).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(wrapper.value.entries.len())
+}
+
+/// 按需解析读取仓库，与 benches/parse.rs 的 lazy_storage 相同
+#[wasm_bindgen]
+pub fn bench_lazy_storage(data: &[u8]) -> Result<usize, JsValue> {
+    let storage = SaveData::from_es3(to_text(data)?)
+        .and_then(|save_data| save_data.get_player_storage())
+        .map_err(|e| e.to_js())?
+        .ok_or_else(|| JsValue::from_str("缺少 Inventory/PlayerStorage"))?;
+    Ok(storage.entries.len())
+}
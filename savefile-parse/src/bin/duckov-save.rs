@@ -0,0 +1,320 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use savefile_parse::archived::Archive;
+use savefile_parse::catalog::ItemCatalog;
+use savefile_parse::container::DEFAULT_PASSWORD;
+use savefile_parse::diff::SaveDiff;
+use savefile_parse::error::SaveParseError;
+use savefile_parse::validate::{Severity, ValidateOptions, validate};
+use savefile_parse::extractor::SaveData;
+use savefile_parse::keys::{KeyReport, key_report};
+use savefile_parse::slots::ingest_saves;
+use savefile_parse::timeline::{Timeline, TimelineOptions};
+use savefile_parse::{ParseOptions, decode_save_text, parse_archive_with};
+
+/// 《逃离鸭科夫》存档命令行工具
+#[derive(Debug, Parser)]
+#[command(name = "duckov-save", version)]
+struct Cli {
+    /// ES3 加密存档的密码
+    #[arg(long, global = true, default_value = DEFAULT_PASSWORD)]
+    password: String,
+
+    /// items.json 格式的物品数据库，提供时输出名称、价格与各容器合计
+    #[arg(long, global = true)]
+    catalog: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// 解析存档并输出 Archive
+    Inspect {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+        format: OutputFormat,
+    },
+    /// 比较两个存档，`before` 为较早的存档
+    Diff { before: PathBuf, after: PathBuf },
+    /// 输出存档中某个顶层键的内容
+    Extract {
+        file: PathBuf,
+        section: String,
+        /// 保留 `__type` 包装，不只输出 value
+        #[arg(long)]
+        raw: bool,
+    },
+    /// 输出角色当前配装的分享码
+    Build { file: PathBuf },
+    /// 解析存档目录，列出每个存档位选中的版本及备份
+    Slots { dir: PathBuf },
+    /// 列出所有顶层键及其 `__type`、大小和是否已被解析
+    Keys {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+        format: OutputFormat,
+        /// 只列出还没有被解析的键
+        #[arg(long)]
+        unextracted: bool,
+    },
+    /// 按给出的顺序（从早到晚）生成同一角色多个存档的进度时间线
+    Timeline {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// 相邻存档间隔超过该天数时报告为 gap
+        #[arg(long, default_value_t = 7)]
+        gap_days: i64,
+    },
+    /// 检查存档，有解析失败的段落或一致性错误时以非零状态退出
+    Validate { file: PathBuf },
+    /// 转换为标准 JSON
+    Convert {
+        file: PathBuf,
+        /// 输出文件，默认写到标准输出
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Json,
+    Table,
+}
+
+enum CliError {
+    Io(PathBuf, std::io::Error),
+    Parse(SaveParseError),
+}
+
+impl From<SaveParseError> for CliError {
+    fn from(err: SaveParseError) -> Self {
+        CliError::Parse(err)
+    }
+}
+
+// 退出码：0 成功，1 读写文件失败，2 参数错误（clap），9 一致性检查发现错误，其余按错误类别区分
+fn exit_code(err: &SaveParseError) -> u8 {
+    match err {
+        SaveParseError::WrongPassword => 3,
+        SaveParseError::Truncated { .. } | SaveParseError::Decompress { .. } => 4,
+        SaveParseError::InvalidUtf8 { .. } | SaveParseError::JsonSyntax { .. } | SaveParseError::BuildCode { .. } => 5,
+        SaveParseError::MissingKey { .. }
+        | SaveParseError::MissingFile { .. }
+        | SaveParseError::SectionType { .. }
+        | SaveParseError::UnknownVersion { .. } => 6,
+        SaveParseError::ItemVariable { .. } | SaveParseError::ItemTree { .. } => 7,
+        SaveParseError::Serialize { .. } => 8,
+        SaveParseError::Cancelled => 10,
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, CliError> {
+    fs::read(path).map_err(|e| CliError::Io(path.to_path_buf(), e))
+}
+
+// 递归读取目录下的所有文件，文件名为相对于 root 的路径
+fn read_dir(root: &Path, dir: &Path, files: &mut Vec<(String, Vec<u8>)>) -> Result<(), CliError> {
+    let entries = fs::read_dir(dir).map_err(|e| CliError::Io(dir.to_path_buf(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| CliError::Io(dir.to_path_buf(), e))?.path();
+        if path.is_dir() {
+            read_dir(root, &path, files)?;
+        } else {
+            let name = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().into_owned();
+            files.push((name, read(&path)?));
+        }
+    }
+    Ok(())
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, CliError> {
+    serde_json::to_string_pretty(value)
+        .map_err(|e| CliError::Parse(SaveParseError::Serialize { message: e.to_string() }))
+}
+
+fn print_table(archive: &Archive) {
+    let quests_done = archive.quest_progress.iter().filter(|quest| quest.complete).count();
+    let kills: i64 = archive.kills.iter().map(|kill| kill.count).sum();
+    let rows = [
+        ("版本", archive.version.clone()),
+        ("经验", archive.exp.to_string()),
+        ("生命值", archive.health.to_string()),
+        ("金钱", archive.economy_data.money.to_string()),
+        ("游戏天数", archive.game_lock.days.to_string()),
+        ("保存时间", archive.save_time.to_string()),
+        ("已完成任务", format!("{}/{}", quests_done, archive.quest_progress.len())),
+        ("击杀总数", kills.to_string()),
+        ("仓库物品", archive.player_storage.entries.len().to_string()),
+        ("死亡掉落", archive.death_list.len().to_string()),
+        ("警告", archive.warnings.len().to_string()),
+    ];
+
+    let width = rows.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+    for (name, value) in rows {
+        // 中文字符按两列宽对齐
+        let padding = (width - name.chars().count()) * 2;
+        println!("{}{}  {}", name, " ".repeat(padding), value);
+    }
+    if let Some(valuation) = &archive.valuation {
+        let containers = [
+            ("角色装备", &valuation.character),
+            ("仓库", &valuation.player_storage),
+            ("安全箱", &valuation.inventory_safe),
+        ];
+        for (name, totals) in containers {
+            let weight = match totals.weight {
+                Some(weight) => format!("{:.2} kg", weight),
+                None => "重量未知".to_string(),
+            };
+            println!("{}: {} 件, {}, 估值 {}", name, totals.count, weight, totals.value);
+        }
+        for (index, totals) in valuation.death_list.iter().enumerate() {
+            println!("死亡掉落 #{}: {} 件, 估值 {}", index + 1, totals.count, totals.value);
+        }
+        if !valuation.missing_type_ids.is_empty() {
+            println!("! 物品数据库中缺少的 typeID: {:?}", valuation.missing_type_ids);
+        }
+    }
+    for warning in &archive.warnings {
+        println!("! {}", warning);
+    }
+}
+
+fn print_keys(report: &KeyReport) {
+    for group in &report.groups {
+        let mark = if group.extracted { ' ' } else { '?' };
+        println!("{} {}* ({} 个, {} 字节) {}", mark, group.prefix, group.count, group.size, group.type_names.join(", "));
+    }
+    for info in report.keys.iter().filter(|info| info.group.is_none()) {
+        let mark = if info.extracted { ' ' } else { '?' };
+        println!("{} {} ({} 字节) {}", mark, info.key, info.size, info.type_name.as_deref().unwrap_or("-"));
+    }
+}
+
+fn run(cli: Cli) -> Result<u8, CliError> {
+    let catalog = match &cli.catalog {
+        Some(path) => {
+            let json = fs::read_to_string(path).map_err(|e| CliError::Io(path.clone(), e))?;
+            Some(ItemCatalog::from_json(&json)?)
+        }
+        None => None,
+    };
+    let options = ParseOptions { password: cli.password, catalog };
+
+    match cli.command {
+        Command::Inspect { file, format } => {
+            let archive = parse_archive_with(&read(&file)?, &options)?;
+            match format {
+                OutputFormat::Json => println!("{}", to_json(&archive)?),
+                OutputFormat::Table => print_table(&archive),
+            }
+        }
+        Command::Diff { before, after } => {
+            let before = parse_archive_with(&read(&before)?, &options)?;
+            let after = parse_archive_with(&read(&after)?, &options)?;
+            println!("{}", to_json(&SaveDiff::between(&before, &after))?);
+        }
+        Command::Extract { file, section, raw } => {
+            let save_data = SaveData::from_es3(decode_save_text(&read(&file)?, &options)?)?;
+            let mut value = save_data
+                .value(&section)?
+                .ok_or(SaveParseError::MissingKey { key: section })?;
+            if !raw && let Some(inner) = value.get_mut("value") {
+                value = inner.take();
+            }
+            println!("{}", to_json(&value)?);
+        }
+        Command::Build { file } => {
+            let archive = parse_archive_with(&read(&file)?, &options)?;
+            println!("{}", archive.loadout.to_build_code());
+        }
+        Command::Slots { dir } => {
+            let mut files = Vec::new();
+            read_dir(&dir, &dir, &mut files)?;
+            println!("{}", to_json(&ingest_saves(files, &options))?);
+        }
+        Command::Keys { file, format, unextracted } => {
+            let save_data = SaveData::from_es3(decode_save_text(&read(&file)?, &options)?)?;
+            let mut report = key_report(&save_data);
+            if unextracted {
+                report.keys.retain(|info| !info.extracted);
+                report.groups.retain(|group| !group.extracted);
+            }
+            match format {
+                OutputFormat::Json => println!("{}", to_json(&report)?),
+                OutputFormat::Table => print_keys(&report),
+            }
+        }
+        Command::Timeline { files, gap_days } => {
+            let saves = files.iter().map(|file| read(file)).collect::<Result<Vec<_>, _>>()?;
+            let timeline_options = TimelineOptions { gap_seconds: gap_days * 24 * 3600 };
+            println!("{}", to_json(&Timeline::from_saves(&saves, &options, &timeline_options))?);
+        }
+        Command::Validate { file } => {
+            let archive = parse_archive_with(&read(&file)?, &options)?;
+            for warning in &archive.warnings {
+                eprintln!("{}", warning);
+            }
+            let validate_options = ValidateOptions {
+                known_master_keys: options.catalog.as_ref().map(ItemCatalog::master_key_ids),
+            };
+            let findings = validate(&archive, &validate_options);
+            for finding in &findings {
+                eprintln!("[{:?}] {} {}: {}", finding.severity, finding.code, finding.path, finding.message);
+            }
+            if let Some(first) = archive.warnings.first() {
+                return Ok(exit_code(first));
+            }
+            if findings.iter().any(|finding| finding.severity == Severity::Error) {
+                return Ok(9);
+            }
+            println!("ok");
+        }
+        Command::Convert { file, output } => {
+            let save_data = SaveData::from_es3(decode_save_text(&read(&file)?, &options)?)?;
+            let json = save_data.to_json()?;
+            match output {
+                Some(path) => fs::write(&path, json).map_err(|e| CliError::Io(path, e))?,
+                None => println!("{}", json),
+            }
+        }
+    }
+
+    Ok(0)
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(code) => ExitCode::from(code),
+        Err(CliError::Io(path, err)) => {
+            eprintln!("{}: {}", path.display(), err);
+            ExitCode::from(1)
+        }
+        Err(CliError::Parse(err)) => {
+            eprintln!("{}", err);
+            ExitCode::from(exit_code(&err))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+
+        let cli = Cli::try_parse_from(["duckov-save", "extract", "a.sav", "EconomyData", "--raw"]).unwrap();
+        assert!(matches!(cli.command, Command::Extract { raw: true, .. }));
+        assert_eq!(cli.password, DEFAULT_PASSWORD);
+    }
+}
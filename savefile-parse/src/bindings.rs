@@ -0,0 +1,83 @@
+use std::any::TypeId;
+use std::collections::HashSet;
+use ts_rs::{TS, TypeVisitor};
+use crate::archived::Archive;
+use crate::diff::SaveDiff;
+use crate::keys::KeyReport;
+use crate::progress::ParseProgress;
+use crate::slots::SaveFolder;
+use crate::timeline::Timeline;
+use crate::validate::Finding;
+
+// 前端使用的类型定义由 Rust 类型生成，提交在 bindings/savefile-parse.d.ts，
+// 同时通过 wasm-bindgen 写入 wasm-pack 输出的 .d.ts。
+// 修改类型后执行 `UPDATE_BINDINGS=1 cargo test bindings` 重新生成。
+// i64 字段标注为 number：process_file 按 JSON 兼容方式输出，不会产生 BigInt。
+
+/// 生成文件相对于 crate 根目录的路径
+pub const BINDINGS_PATH: &str = "bindings/savefile-parse.d.ts";
+
+#[derive(Default)]
+struct Collector {
+    seen: HashSet<TypeId>,
+    decls: Vec<(String, String)>,
+}
+
+impl TypeVisitor for Collector {
+    fn visit<T: TS + 'static + ?Sized>(&mut self) {
+        // 包装类型会直接转发到内部类型的依赖，递归类型需要靠 seen 终止
+        if !self.seen.insert(TypeId::of::<T>()) {
+            return;
+        }
+        // 只有带名字的类型需要声明，Vec、Option 等包装类型只继续向下遍历
+        if T::output_path().is_some() {
+            self.decls.push((T::ident(), T::decl()));
+        }
+        T::visit_dependencies(self);
+    }
+}
+
+/// 所有 wasm 导出函数返回的类型及其依赖的 TypeScript 声明
+pub fn typescript_declarations() -> String {
+    let mut collector = Collector::default();
+    collector.visit::<Archive>();
+    collector.visit::<SaveDiff>();
+    collector.visit::<Finding>();
+    collector.visit::<SaveFolder>();
+    collector.visit::<KeyReport>();
+    collector.visit::<Timeline>();
+    collector.visit::<ParseProgress>();
+
+    // 按名称排序，避免字段顺序调整导致整个文件变动
+    collector.decls.sort();
+    let mut output = String::from("// 由 savefile-parse 的 Rust 类型生成，请勿手动修改\n");
+    for (_, decl) in collector.decls {
+        output.push_str("\nexport ");
+        output.push_str(&decl);
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_bindings_up_to_date() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(BINDINGS_PATH);
+        let generated = typescript_declarations();
+        if std::env::var_os("UPDATE_BINDINGS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &generated).unwrap();
+        }
+
+        let committed = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            committed == generated,
+            "{} 与 Rust 类型不一致，请执行 `UPDATE_BINDINGS=1 cargo test bindings` 重新生成",
+            BINDINGS_PATH
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::{BTreeSet, HashMap};
+use serde::{Deserialize, Deserializer, Serialize};
+use ts_rs::TS;
+use crate::archived::{Archive, ItemTreeData};
+use crate::error::SaveParseError;
+
+// items.json 中的价格是字符串，这里同时兼容数字
+fn price<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Price {
+        Number(i64),
+        Text(String),
+    }
+
+    match Price::deserialize(deserializer)? {
+        Price::Number(value) => Ok(value),
+        Price::Text(text) => text.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
+
+/// 物品数据库中的一条记录，格式与前端的 items.json 一致
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogItem {
+    pub id: i32,
+    pub name: String,
+    #[serde(default)]
+    pub display_name: String, // 本地化键
+    pub quality: i32,
+    #[serde(rename = "priceEach", deserialize_with = "price", default)]
+    pub price: i64,
+    #[serde(default)]
+    pub weight: Option<f32>, // 前端的 items.json 目前没有重量
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// 按 typeID 索引的物品数据库
+#[derive(Debug, Clone, Default)]
+pub struct ItemCatalog {
+    items: HashMap<i32, CatalogItem>,
+}
+
+impl ItemCatalog {
+    /// 从 items.json 格式的数组加载
+    pub fn from_json(json: &str) -> Result<Self, SaveParseError> {
+        let items: Vec<CatalogItem> = serde_json::from_str(json).map_err(|e| SaveParseError::section("catalog", e))?;
+        Ok(items.into_iter().collect())
+    }
+
+    pub fn get(&self, type_id: i32) -> Option<&CatalogItem> {
+        self.items.get(&type_id)
+    }
+
+    /// 带 Key 标签的普通钥匙，与存档页面的钥匙列表一致
+    pub fn master_key_ids(&self) -> BTreeSet<i32> {
+        self.items
+            .values()
+            .filter(|item| item.tags.iter().any(|tag| tag == "Key") && !item.tags.iter().any(|tag| tag == "SpecialKey"))
+            .map(|item| item.id)
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl FromIterator<CatalogItem> for ItemCatalog {
+    fn from_iter<I: IntoIterator<Item = CatalogItem>>(iter: I) -> Self {
+        ItemCatalog {
+            items: iter.into_iter().map(|item| (item.id, item)).collect(),
+        }
+    }
+}
+
+/// 附加到每个物品上的数据库信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemInfo {
+    pub name: String,
+    pub display_name: String,
+    pub quality: i32,
+    #[ts(type = "number")]
+    pub price: i64,
+    #[ts(optional)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f32>,
+}
+
+impl From<&CatalogItem> for ItemInfo {
+    fn from(item: &CatalogItem) -> Self {
+        ItemInfo {
+            name: item.name.clone(),
+            display_name: item.display_name.clone(),
+            quality: item.quality,
+            price: item.price,
+            weight: item.weight,
+        }
+    }
+}
+
+/// 一个容器内物品的合计
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS)]
+pub struct ContainerTotals {
+    #[ts(type = "number")]
+    pub count: i64,
+    // 只要有一件物品缺少重量数据，总重量就是未知
+    #[ts(optional)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+    #[ts(type = "number")]
+    pub value: i64, // 按基础价格估算
+}
+
+impl Default for ContainerTotals {
+    fn default() -> Self {
+        ContainerTotals {
+            count: 0,
+            weight: Some(0.0),
+            value: 0,
+        }
+    }
+}
+
+impl ContainerTotals {
+    fn add_tree(&mut self, tree: &ItemTreeData, skip_root: bool) {
+        for entry in &tree.entries {
+            if skip_root && entry.instance_id == tree.root_instance_id {
+                continue;
+            }
+            let count = entry.stack_count();
+            self.count += count;
+            let weight = entry.info.as_ref().and_then(|info| info.weight);
+            self.weight = self.weight.zip(weight).map(|(total, weight)| total + f64::from(weight) * count as f64);
+            if let Some(info) = &entry.info {
+                self.value += info.price * count;
+            }
+        }
+    }
+}
+
+/// 各容器的合计以及数据库中找不到的 typeID
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct Valuation {
+    pub character: ContainerTotals,
+    pub player_storage: ContainerTotals,
+    pub inventory_safe: ContainerTotals,
+    pub death_list: Vec<ContainerTotals>,
+    #[serde(rename = "missingTypeIDs")]
+    pub missing_type_ids: Vec<i32>,
+}
+
+impl Archive {
+    /// 为每个物品附加数据库信息并计算各容器的合计
+    pub fn apply_catalog(&mut self, catalog: &ItemCatalog) {
+        let mut missing = BTreeSet::new();
+        for tree in self.item_trees_mut() {
+            for entry in &mut tree.entries {
+                entry.info = catalog.get(entry.type_id).map(ItemInfo::from);
+                if entry.info.is_none() {
+                    missing.insert(entry.type_id);
+                }
+            }
+        }
+
+        let mut valuation = Valuation {
+            missing_type_ids: missing.into_iter().collect(),
+            ..Default::default()
+        };
+        // 角色装备树的根节点是角色本身，不计入
+        valuation.character.add_tree(&self.character_item_data, true);
+        for entry in &self.player_storage.entries {
+            valuation.player_storage.add_tree(&entry.item_tree_data, false);
+        }
+        for entry in &self.inventory_safe.entries {
+            valuation.inventory_safe.add_tree(&entry.item_tree_data, false);
+        }
+        valuation.death_list = self
+            .death_list
+            .iter()
+            .map(|stash| {
+                let mut totals = ContainerTotals::default();
+                totals.add_tree(&stash.item_tree_data, false);
+                totals
+            })
+            .collect();
+
+        self.valuation = Some(valuation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseOptions, parse_archive_with};
+
+    const SAMPLE: &str = include_str!("../fixtures/sample.sav");
+    const CATALOG: &str = r#"[
+        {"id": 254, "name": "Bullet_9mm", "displayName": "Item_Bullet_9mm", "quality": 1, "priceEach": "12", "weight": 0.01, "tags": ["Bullet"]},
+        {"id": 381, "name": "Medkit", "quality": 2, "priceEach": 300, "weight": 0.5},
+        {"id": 256, "name": "Gem", "quality": 4, "priceEach": "5000", "tags": []}
+    ]"#;
+
+    #[test]
+    fn test_catalog_prices() {
+        let catalog = ItemCatalog::from_json(CATALOG).unwrap();
+        assert_eq!(catalog.len(), 3);
+        assert_eq!(catalog.get(254).unwrap().price, 12);
+        assert_eq!(catalog.get(381).unwrap().price, 300);
+        assert_eq!(catalog.get(256).unwrap().weight, None);
+        assert!(ItemCatalog::from_json(r#"[{"id": 1, "name": "x", "quality": 0, "priceEach": "abc"}]"#).is_err());
+    }
+
+    #[test]
+    fn test_valuation() {
+        let options = ParseOptions {
+            catalog: Some(ItemCatalog::from_json(CATALOG).unwrap()),
+            ..Default::default()
+        };
+        let archive = parse_archive_with(SAMPLE.as_bytes(), &options).unwrap();
+        let valuation = archive.valuation.as_ref().unwrap();
+
+        // 仓库：30 发子弹 + 1 个医疗包
+        assert_eq!(valuation.player_storage.count, 31);
+        assert_eq!(valuation.player_storage.value, 30 * 12 + 300);
+        assert!((valuation.player_storage.weight.unwrap() - 0.8).abs() < 1e-6);
+        // 宝石没有重量数据，安全箱的总重量未知
+        assert_eq!(valuation.inventory_safe.value, 5000);
+        assert_eq!(valuation.inventory_safe.weight, None);
+        assert_eq!(valuation.death_list.len(), 1);
+
+        let storage = &archive.player_storage.entries[0].item_tree_data.entries[0];
+        assert_eq!(storage.info.as_ref().unwrap().name, "Bullet_9mm");
+        assert!(valuation.missing_type_ids.contains(&238));
+        assert!(!valuation.missing_type_ids.contains(&254));
+    }
+}
@@ -0,0 +1,188 @@
+use std::io::{Read, Write};
+use aes::Aes128;
+use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use aes::cipher::block_padding::Pkcs7;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use crate::error::SaveParseError;
+
+/// Easy Save 3 未配置密码时使用的默认密码
+pub const DEFAULT_PASSWORD: &str = "password";
+
+// ES3 使用 Rfc2898DeriveBytes（PBKDF2-HMAC-SHA1）派生 128 位密钥，盐即 IV
+const KEY_ITERATIONS: u32 = 100;
+const BLOCK_SIZE: usize = 16;
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const UTF8_BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
+// 加密与压缩最多嵌套的层数，防止异常数据导致死循环
+const MAX_LAYERS: usize = 4;
+
+/// 存档外层的容器格式，按从外到内的顺序记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ContainerLayer {
+    Gzip,
+    Encrypted { iv: [u8; BLOCK_SIZE] },
+}
+
+type Aes128CbcDec = cbc::Decryptor<Aes128>;
+type Aes128CbcEnc = cbc::Encryptor<Aes128>;
+
+fn derive_key(password: &str, salt: &[u8]) -> [u8; BLOCK_SIZE] {
+    let mut key = [0u8; BLOCK_SIZE];
+    pbkdf2::pbkdf2_hmac::<Sha1>(password.as_bytes(), salt, KEY_ITERATIONS, &mut key);
+    key
+}
+
+fn is_plain_json(data: &[u8]) -> bool {
+    let data = data.strip_prefix(&UTF8_BOM).unwrap_or(data);
+    data.iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .is_some_and(|b| *b == b'{')
+}
+
+fn decrypt(data: &[u8], password: &str) -> Result<(Vec<u8>, [u8; BLOCK_SIZE]), SaveParseError> {
+    if data.len() < BLOCK_SIZE * 2 || !data.len().is_multiple_of(BLOCK_SIZE) {
+        return Err(SaveParseError::Truncated {
+            message: format!("加密数据长度 {} 不是完整的 AES 块", data.len()),
+        });
+    }
+
+    let (iv, payload) = data.split_at(BLOCK_SIZE);
+    let iv: [u8; BLOCK_SIZE] = iv.try_into().expect("IV 长度固定为 16");
+    let key = derive_key(password, &iv);
+    let plain = Aes128CbcDec::new(&key.into(), &iv.into())
+        .decrypt_padded_vec_mut::<Pkcs7>(payload)
+        .map_err(|_| SaveParseError::WrongPassword)?;
+    Ok((plain, iv))
+}
+
+fn gunzip(data: &[u8]) -> Result<Vec<u8>, SaveParseError> {
+    let mut out = Vec::new();
+    GzDecoder::new(data).read_to_end(&mut out).map_err(|e| match e.kind() {
+        std::io::ErrorKind::UnexpectedEof => SaveParseError::Truncated {
+            message: "gzip 数据不完整".to_string(),
+        },
+        _ => SaveParseError::Decompress { message: e.to_string() },
+    })?;
+    Ok(out)
+}
+
+/// 根据文件头逐层解密、解压，直到得到 JSON 文本
+pub fn decode_container(data: &[u8], password: &str) -> Result<(Vec<u8>, Vec<ContainerLayer>), SaveParseError> {
+    let mut current = data.to_vec();
+    let mut layers = Vec::new();
+
+    while !is_plain_json(&current) {
+        if layers.len() >= MAX_LAYERS {
+            return Err(SaveParseError::Decompress {
+                message: "无法识别的存档容器格式".to_string(),
+            });
+        }
+
+        if current.starts_with(&GZIP_MAGIC) {
+            current = gunzip(&current)?;
+            layers.push(ContainerLayer::Gzip);
+        } else if matches!(layers.last(), Some(ContainerLayer::Encrypted { .. })) {
+            // 解密成功但内容既不是 JSON 也不是 gzip，说明密码错误
+            return Err(SaveParseError::WrongPassword);
+        } else {
+            let (plain, iv) = decrypt(&current, password)?;
+            current = plain;
+            layers.push(ContainerLayer::Encrypted { iv });
+        }
+    }
+
+    Ok((current, layers))
+}
+
+/// 按记录的容器格式重新封装，IV 沿用原文件中的值
+pub fn encode_container(plain: &[u8], layers: &[ContainerLayer], password: &str) -> Vec<u8> {
+    let mut current = plain.to_vec();
+    for layer in layers.iter().rev() {
+        current = match layer {
+            ContainerLayer::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(&current).expect("写入内存缓冲不会失败");
+                encoder.finish().expect("写入内存缓冲不会失败")
+            }
+            ContainerLayer::Encrypted { iv } => {
+                let key = derive_key(password, iv);
+                let encrypted = Aes128CbcEnc::new(&key.into(), iv.into()).encrypt_padded_vec_mut::<Pkcs7>(&current);
+                [iv.as_slice(), &encrypted].concat()
+            }
+        };
+    }
+    current
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseOptions, parse_archive, parse_archive_with};
+
+    const SAMPLE: &str = include_str!("../fixtures/sample.sav");
+    const IV: [u8; BLOCK_SIZE] = *b"duckov-test-iv!!";
+
+    fn encrypted(password: &str) -> Vec<u8> {
+        encode_container(SAMPLE.as_bytes(), &[ContainerLayer::Encrypted { iv: IV }], password)
+    }
+
+    #[test]
+    fn test_plain_passthrough() {
+        let (plain, layers) = decode_container(SAMPLE.as_bytes(), DEFAULT_PASSWORD).unwrap();
+        assert_eq!(plain, SAMPLE.as_bytes());
+        assert!(layers.is_empty());
+    }
+
+    #[test]
+    fn test_gzip() {
+        let data = encode_container(SAMPLE.as_bytes(), &[ContainerLayer::Gzip], DEFAULT_PASSWORD);
+        assert!(data.starts_with(&GZIP_MAGIC));
+
+        let archive = parse_archive(&data).unwrap();
+        assert_eq!(archive.economy_data.money, 12850);
+    }
+
+    #[test]
+    fn test_encrypted_with_default_password() {
+        let archive = parse_archive(&encrypted(DEFAULT_PASSWORD)).unwrap();
+        assert_eq!(archive.exp, 5320);
+    }
+
+    #[test]
+    fn test_encrypted_then_compressed() {
+        let layers = [ContainerLayer::Gzip, ContainerLayer::Encrypted { iv: IV }];
+        let data = encode_container(SAMPLE.as_bytes(), &layers, "duck");
+
+        let (plain, decoded_layers) = decode_container(&data, "duck").unwrap();
+        assert_eq!(plain, SAMPLE.as_bytes());
+        assert_eq!(decoded_layers, layers);
+    }
+
+    #[test]
+    fn test_custom_password() {
+        let data = encrypted("quack");
+        let options = ParseOptions { password: "quack".to_string(), ..Default::default() };
+        assert!(parse_archive_with(&data, &options).is_ok());
+        assert_eq!(parse_archive(&data).unwrap_err(), SaveParseError::WrongPassword);
+    }
+
+    #[test]
+    fn test_truncated() {
+        let data = encrypted(DEFAULT_PASSWORD);
+        assert!(matches!(
+            parse_archive(&data[..data.len() - 5]).unwrap_err(),
+            SaveParseError::Truncated { .. }
+        ));
+
+        let gz = encode_container(SAMPLE.as_bytes(), &[ContainerLayer::Gzip], DEFAULT_PASSWORD);
+        assert!(matches!(
+            parse_archive(&gz[..gz.len() / 2]).unwrap_err(),
+            SaveParseError::Truncated { .. }
+        ));
+    }
+}
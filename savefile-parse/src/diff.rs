@@ -0,0 +1,254 @@
+use std::collections::{BTreeMap, BTreeSet};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use crate::archived::{Archive, StashData};
+use crate::error::SaveParseError;
+use crate::{ParseOptions, parse_archive_with};
+
+/// 输出格式版本，字段变化时递增
+pub const DIFF_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct Delta {
+    #[ts(type = "number")]
+    pub before: i64,
+    #[ts(type = "number")]
+    pub after: i64,
+    #[ts(type = "number")]
+    pub delta: i64,
+}
+
+impl Delta {
+    pub fn new(before: i64, after: i64) -> Self {
+        Delta { before, after, delta: after - before }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemCountChange {
+    #[serde(rename = "typeID")]
+    pub type_id: i32,
+    #[ts(type = "number")]
+    pub before: i64,
+    #[ts(type = "number")]
+    pub after: i64,
+    #[ts(type = "number")]
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct KillChange {
+    pub monster_name: String,
+    #[ts(type = "number")]
+    pub before: i64,
+    #[ts(type = "number")]
+    pub after: i64,
+    #[ts(type = "number")]
+    pub delta: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct StashSummary {
+    #[serde(rename = "raidID")]
+    pub raid_id: i32,
+    #[serde(rename = "subSceneID")]
+    pub sub_scene_id: String,
+    pub item_count: usize,
+}
+
+/// 两个存档之间的差异
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveDiff {
+    pub schema_version: u32,
+    pub money: Delta,
+    pub exp: Delta,
+    pub items_gained: Vec<ItemCountChange>,
+    pub items_lost: Vec<ItemCountChange>,
+    pub quests_completed: Vec<u32>,
+    pub kills: Vec<KillChange>,
+    pub new_death_stashes: Vec<StashSummary>,
+    pub unlocked_items: Vec<i32>,
+}
+
+/// 按 typeID 统计所有物品树中的物品数量
+pub fn count_items(archive: &Archive) -> BTreeMap<i32, i64> {
+    let mut counts = BTreeMap::new();
+    for tree in archive.item_trees() {
+        // 根节点是角色本身，不计入
+        let is_character = std::ptr::eq(tree, &archive.character_item_data);
+        for entry in &tree.entries {
+            if is_character && entry.instance_id == tree.root_instance_id {
+                continue;
+            }
+            *counts.entry(entry.type_id).or_insert(0) += entry.stack_count();
+        }
+    }
+    counts
+}
+
+fn completed_quests(archive: &Archive) -> BTreeSet<u32> {
+    archive
+        .quests
+        .active_quests
+        .iter()
+        .chain(&archive.quests.history_quests)
+        .filter(|quest| quest.complete)
+        .map(|quest| quest.id)
+        .collect()
+}
+
+fn stash_key(stash: &StashData) -> (i32, &str) {
+    (stash.raid_id, stash.sub_scene_id.as_str())
+}
+
+impl SaveDiff {
+    pub fn between(before: &Archive, after: &Archive) -> Self {
+        let counts_before = count_items(before);
+        let counts_after = count_items(after);
+        let type_ids: BTreeSet<i32> = counts_before.keys().chain(counts_after.keys()).copied().collect();
+
+        let mut items_gained = Vec::new();
+        let mut items_lost = Vec::new();
+        for type_id in type_ids {
+            let a = counts_before.get(&type_id).copied().unwrap_or(0);
+            let b = counts_after.get(&type_id).copied().unwrap_or(0);
+            let change = ItemCountChange { type_id, before: a, after: b, count: (b - a).abs() };
+            match b.cmp(&a) {
+                std::cmp::Ordering::Greater => items_gained.push(change),
+                std::cmp::Ordering::Less => items_lost.push(change),
+                std::cmp::Ordering::Equal => {}
+            }
+        }
+
+        let done_before = completed_quests(before);
+        let quests_completed = completed_quests(after).difference(&done_before).copied().collect();
+
+        let kills_before: BTreeMap<&str, i64> =
+            before.kills.iter().map(|k| (k.monster_name.as_str(), k.count)).collect();
+        let mut kills: Vec<KillChange> = after
+            .kills
+            .iter()
+            .filter_map(|kill| {
+                let previous = kills_before.get(kill.monster_name.as_str()).copied().unwrap_or(0);
+                (kill.count > previous).then(|| KillChange {
+                    monster_name: kill.monster_name.clone(),
+                    before: previous,
+                    after: kill.count,
+                    delta: kill.count - previous,
+                })
+            })
+            .collect();
+        kills.sort_by(|a, b| a.monster_name.cmp(&b.monster_name));
+
+        let stashes_before: BTreeSet<(i32, &str)> = before.death_list.iter().map(stash_key).collect();
+        let new_death_stashes = after
+            .death_list
+            .iter()
+            .filter(|stash| !stashes_before.contains(&stash_key(stash)))
+            .map(|stash| StashSummary {
+                raid_id: stash.raid_id,
+                sub_scene_id: stash.sub_scene_id.clone(),
+                item_count: stash.item_tree_data.entries.len(),
+            })
+            .collect();
+
+        let unlocked_before: BTreeSet<i32> = before.economy_data.unlocked_items.iter().copied().collect();
+        let unlocked_items = after
+            .economy_data
+            .unlocked_items
+            .iter()
+            .copied()
+            .filter(|id| !unlocked_before.contains(id))
+            .collect::<BTreeSet<i32>>()
+            .into_iter()
+            .collect();
+
+        SaveDiff {
+            schema_version: DIFF_SCHEMA_VERSION,
+            money: Delta::new(before.economy_data.money.into(), after.economy_data.money.into()),
+            exp: Delta::new(before.exp.into(), after.exp.into()),
+            items_gained,
+            items_lost,
+            quests_completed,
+            kills,
+            new_death_stashes,
+            unlocked_items,
+        }
+    }
+}
+
+/// 解析两个存档并比较，`a` 为较早的存档
+pub fn diff_saves(a: &[u8], b: &[u8], options: &ParseOptions) -> Result<SaveDiff, SaveParseError> {
+    let before = parse_archive_with(a, options)?;
+    let after = parse_archive_with(b, options)?;
+    Ok(SaveDiff::between(&before, &after))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_archive;
+
+    const SAMPLE: &str = include_str!("../fixtures/sample.sav");
+
+    #[test]
+    fn test_identical_saves() {
+        let diff = diff_saves(SAMPLE.as_bytes(), SAMPLE.as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(diff.money.delta, 0);
+        assert!(diff.items_gained.is_empty());
+        assert!(diff.items_lost.is_empty());
+        assert!(diff.kills.is_empty());
+        assert!(diff.new_death_stashes.is_empty());
+    }
+
+    #[test]
+    fn test_encrypted_saves() {
+        use crate::container::{ContainerLayer, encode_container};
+
+        let layers = [ContainerLayer::Encrypted { iv: *b"0123456789abcdef" }];
+        let data = encode_container(SAMPLE.as_bytes(), &layers, "duck");
+        let options = ParseOptions { password: "duck".to_string(), ..Default::default() };
+
+        let diff = diff_saves(&data, SAMPLE.as_bytes(), &options).unwrap();
+        assert_eq!(diff.money.delta, 0);
+        assert!(diff_saves(&data, SAMPLE.as_bytes(), &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_after_raid() {
+        let before = parse_archive(SAMPLE.as_bytes()).unwrap();
+        let mut after = before.clone();
+        after.economy_data.money += 500;
+        after.economy_data.unlocked_items.push(570);
+        after.quests.active_quests[0].complete = true;
+        after.kills[0].count += 3;
+        // 弹药从 30 发用到 10 发，并捡到一个新物品
+        let storage = &mut after.player_storage.entries[0].item_tree_data.entries;
+        storage[0].decoded.insert("Count".to_string(), crate::variable::VariableValue::Int(10));
+        let mut pickup = storage[0].clone();
+        pickup.instance_id = 999;
+        pickup.type_id = 777;
+        pickup.decoded.clear();
+        storage.push(pickup);
+
+        let diff = SaveDiff::between(&before, &after);
+
+        assert_eq!(diff.money, Delta::new(12850, 13350));
+        assert_eq!(diff.unlocked_items, vec![570]);
+        assert_eq!(diff.quests_completed, vec![902]);
+        assert_eq!(diff.kills.len(), 1);
+        assert_eq!(diff.kills[0].delta, 3);
+        assert_eq!(
+            diff.items_lost,
+            vec![ItemCountChange { type_id: 254, before: 30, after: 10, count: 20 }]
+        );
+        assert_eq!(
+            diff.items_gained,
+            vec![ItemCountChange { type_id: 777, before: 0, after: 1, count: 1 }]
+        );
+    }
+}
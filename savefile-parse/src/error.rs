@@ -0,0 +1,163 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use wasm_bindgen::JsValue;
+use crate::tree::TreeIssue;
+
+/// 存档解析错误
+///
+/// 序列化后带有 `kind` 标签，前端可以据此区分错误类别。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SaveParseError {
+    /// 解密失败，密码错误
+    WrongPassword,
+    /// 文件被截断，加密块或压缩流不完整
+    Truncated {
+        message: String,
+    },
+    /// 解压失败或无法识别容器格式
+    Decompress {
+        message: String,
+    },
+    /// 文件不是合法的 UTF-8 文本
+    InvalidUtf8 {
+        valid_up_to: usize,
+    },
+    /// JSON 语法错误（位置基于修复格式后的文本）
+    JsonSyntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// 缺少必须的顶层键
+    MissingKey {
+        key: String,
+    },
+    /// 存档目录中缺少存档位的主文件
+    MissingFile {
+        name: String,
+    },
+    /// 某个存档段落的结构与预期类型不符
+    SectionType {
+        key: String,
+        message: String,
+    },
+    /// 存档版本不在已支持的范围内，按当前布局尝试解析
+    UnknownVersion {
+        version: String,
+    },
+    /// 物品变量无法按 dataType 解码，已按原始字节保留
+    ItemVariable {
+        instance_id: i32,
+        key: String,
+        data_type: i32,
+        message: String,
+    },
+    /// 物品树结构异常（循环、悬空引用、孤立条目等）
+    ItemTree {
+        root_instance_id: i32,
+        issue: TreeIssue,
+    },
+    /// 配装分享码无法解析
+    BuildCode {
+        message: String,
+    },
+    /// 解析被调用方取消
+    Cancelled,
+    /// 输出序列化失败
+    Serialize {
+        message: String,
+    },
+}
+
+impl SaveParseError {
+    pub fn section(key: &str, err: impl fmt::Display) -> Self {
+        SaveParseError::SectionType {
+            key: key.to_string(),
+            message: err.to_string(),
+        }
+    }
+
+    /// 附带 `displayMessage` 的序列化形式，供前端直接展示
+    pub fn with_display(&self) -> JsError<'_> {
+        JsError {
+            error: self,
+            display_message: self.to_string(),
+        }
+    }
+
+    /// 转换为 JS 对象，形如 `{ kind, message, ... }`
+    pub fn to_js(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.with_display()).unwrap_or_else(|_| JsValue::from_str(&self.to_string()))
+    }
+}
+
+/// 序列化为 `{ kind, ..., displayMessage }`
+#[derive(Serialize)]
+pub struct JsError<'a> {
+    #[serde(flatten)]
+    error: &'a SaveParseError,
+    #[serde(rename = "displayMessage")]
+    display_message: String,
+}
+
+impl fmt::Display for SaveParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveParseError::WrongPassword => write!(f, "存档解密失败，请检查密码"),
+            SaveParseError::Truncated { message } => write!(f, "存档文件不完整: {}", message),
+            SaveParseError::Decompress { message } => write!(f, "存档解压失败: {}", message),
+            SaveParseError::InvalidUtf8 { valid_up_to } => {
+                write!(f, "存档不是合法的 UTF-8 文本（第 {} 字节之后）", valid_up_to)
+            }
+            SaveParseError::JsonSyntax { line, column, message } => {
+                write!(f, "存档 JSON 语法错误（第 {} 行第 {} 列）: {}", line, column, message)
+            }
+            SaveParseError::MissingKey { key } => write!(f, "存档缺少必须的字段: {}", key),
+            SaveParseError::SectionType { key, message } => {
+                write!(f, "存档字段 {} 类型不匹配: {}", key, message)
+            }
+            SaveParseError::UnknownVersion { version } => {
+                write!(f, "未适配的存档版本 {}，部分数据可能解析不正确", version)
+            }
+            SaveParseError::ItemVariable { instance_id, key, data_type, message } => {
+                write!(f, "物品 {} 的变量 {}（dataType {}）无法解码: {}", instance_id, key, data_type, message)
+            }
+            SaveParseError::ItemTree { root_instance_id, issue } => {
+                write!(f, "物品树 {} 结构异常: {:?}", root_instance_id, issue)
+            }
+            SaveParseError::MissingFile { name } => write!(f, "存档目录中缺少 {}", name),
+            SaveParseError::BuildCode { message } => write!(f, "分享码无效: {}", message),
+            SaveParseError::Cancelled => write!(f, "解析已取消"),
+            SaveParseError::Serialize { message } => write!(f, "存档输出序列化失败: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SaveParseError {}
+
+impl From<std::str::Utf8Error> for SaveParseError {
+    fn from(err: std::str::Utf8Error) -> Self {
+        SaveParseError::InvalidUtf8 {
+            valid_up_to: err.valid_up_to(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for SaveParseError {
+    fn from(err: serde_json::Error) -> Self {
+        match err.classify() {
+            // 顶层能解析但不是对象，视为根节点类型不匹配
+            serde_json::error::Category::Data => SaveParseError::SectionType {
+                key: "$".to_string(),
+                message: err.to_string(),
+            },
+            _ => SaveParseError::JsonSyntax {
+                line: err.line(),
+                column: err.column(),
+                message: err.to_string(),
+            },
+        }
+    }
+}
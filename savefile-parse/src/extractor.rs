@@ -1,22 +1,18 @@
+use serde::de::DeserializeOwned;
+use ts_rs::TS;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
-use std::fs;
-use std::ptr::write;
-use log::info;
+use std::collections::{BTreeMap, HashMap};
 use crate::archived::{EconomyData, Inventory, MasterKey, GameClock, QuestData, StashData, ItemTreeData};
-use crate::format::fix_json_format;
+use crate::error::SaveParseError;
+use crate::format::RawSection;
 
-// 基础类型包装器
-#[derive(Debug, Deserialize, Serialize)]
+// 基础类型包装器，`__type` 不参与解析
+#[derive(Debug, Deserialize)]
 struct TypedValue<T> {
-    #[serde(rename = "__type")]
-    type_name: String,
     value: T,
 }
 
-pub type MasterKeyData = Vec<MasterKey>;
-
 // 版本信息
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -28,273 +24,334 @@ pub struct VersionData {
 }
 
 // 颜色数据
-#[derive(Debug, Deserialize, Serialize)]
-struct Color {
-    r: f32,
-    g: f32,
-    b: f32,
-    a: f32,
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, TS)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
 }
 
 // 头部设置
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
-struct HeadSetting {
-    main_color: Color,
-    head_scale_offset: f32,
-    forehead_height: f32,
-    forehead_round: f32,
+pub struct HeadSetting {
+    pub main_color: Color,
+    pub head_scale_offset: f32,
+    pub forehead_height: f32,
+    pub forehead_round: f32,
 }
 
 // 外观部件信息
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
-struct PartInfo {
-    radius: f32,
-    color: Color,
-    height: f32,
-    height_offset: f32,
-    scale: f32,
-    twist: f32,
-    distance_angle: f32,
-    left_right_angle: f32,
+pub struct PartInfo {
+    pub radius: f32,
+    pub color: Color,
+    pub height: f32,
+    pub height_offset: f32,
+    pub scale: f32,
+    pub twist: f32,
+    pub distance_angle: f32,
+    pub left_right_angle: f32,
 }
 
 // 自定义外观数据
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
-struct CustomFaceSettingData {
-    saved_setting: bool,
-    head_setting: HeadSetting,
-    hair_id: i32,
-    hair_info: PartInfo,
-    eye_id: i32,
-    eye_info: PartInfo,
-    eyebrow_id: i32,
-    eyebrow_info: PartInfo,
-    mouth_id: i32,
-    mouth_info: PartInfo,
-    tail_id: i32,
-    tail_info: PartInfo,
-    foot_id: i32,
-    foot_info: PartInfo,
-    wing_id: i32,
-    wing_info: PartInfo,
+pub struct CustomFaceSettingData {
+    pub saved_setting: bool,
+    pub head_setting: HeadSetting,
+    pub hair_id: i32,
+    pub hair_info: PartInfo,
+    pub eye_id: i32,
+    pub eye_info: PartInfo,
+    pub eyebrow_id: i32,
+    pub eyebrow_info: PartInfo,
+    pub mouth_id: i32,
+    pub mouth_info: PartInfo,
+    pub tail_id: i32,
+    pub tail_info: PartInfo,
+    pub foot_id: i32,
+    pub foot_info: PartInfo,
+    pub wing_id: i32,
+    pub wing_info: PartInfo,
 }
 
-// 物品变量
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct ItemVariable {
-    key: String,
-    data_type: i32,
-    data: String, // Base64编码的数据
-    display: bool,
-}
-
-// 槽位内容
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct SlotContent {
-    slot: String,
-    instance_id: i32,
-}
-
-// 背包位置
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct InventoryItem {
-    position: i32,
-    instance_id: i32,
-}
-
-// 物品条目
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct ItemEntry {
-    instance_id: i32,
-    type_id: i32,
-    variables: Vec<ItemVariable>,
-    slot_contents: Vec<SlotContent>,
-    inventory: Vec<InventoryItem>,
-    inventory_sort_locks: Vec<Value>,
-}
-
-// 物品树数据
-// #[derive(Debug, Deserialize, Serialize)]
-// #[serde(rename_all = "camelCase")]
-// struct ItemTreeData {
-//     root_instance_id: i32,
-//     entries: Vec<ItemEntry>,
-// }
-
 // 商店库存
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
-struct StockItem {
-    item_type_id: i32,
-    stock: i32,
+pub struct StockItem {
+    #[serde(rename = "itemTypeID")]
+    pub item_type_id: i32,
+    pub stock: i32,
 }
 
 // 商店数据
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
-struct StockShopData {
-    last_time_refreshed_stock: i64,
-    stock_counts: Vec<StockItem>,
+pub struct StockShopData {
+    #[ts(type = "number")]
+    pub last_time_refreshed_stock: i64,
+    pub stock_counts: Vec<StockItem>,
 }
 
 // 主存档结构
-#[derive(Debug, Deserialize, Serialize)]
+//
+// 只在加载时扫描一遍顶层对象并记录每个段落在原文中的位置，
+// 各个 getter 需要时才从对应的原文片段直接反序列化。
+#[derive(Debug, Clone)]
 pub struct SaveData {
-    #[serde(flatten)]
-    pub data: HashMap<String, Value>,
+    source: String,
+    sections: Vec<RawSection>,
+    index: HashMap<String, usize>,
+    // 被版本适配或编辑改写过的段落
+    overrides: HashMap<String, Value>,
 }
 
 impl SaveData {
-    // 解析存档文件
-    pub fn from_json(json_str: &str) -> Result<Self, serde_json::Error> {
-        serde_json::from_str(json_str)
+    // 存档中必须存在的顶层键
+    pub const REQUIRED_KEYS: &'static [&'static str] = &["CreatedWithVersion"];
+
+    /// 已有 getter 读取的顶层键，新增 getter 时需同步更新
+    pub const EXTRACTED_KEYS: &'static [&'static str] = &[
+        "CreatedWithVersion",
+        "SaveTime",
+        "EXP_Value",
+        "MainCharacterHealth",
+        "GameClock",
+        "EconomyData",
+        "MasterKeys",
+        "QuestData",
+        "DeathList",
+        "Inventory/PlayerStorage",
+        "Inventory/Inventory_Safe",
+        "Item/MainCharacterItemData",
+        "CustomFace_MainCharacter",
+    ];
+
+    /// 已有 getter 按前缀读取的键
+    pub const EXTRACTED_PREFIXES: &'static [&'static str] = &[
+        "Count/Kills/",
+        "MultiSceneCore_Visited_",
+        "ConstructionSite_",
+        "StockShop_",
+    ];
+
+    // 解析标准 JSON 格式的存档（ES3 方言是标准 JSON 的超集）
+    pub fn from_json(json_str: impl Into<String>) -> Result<Self, SaveParseError> {
+        Self::from_es3(json_str)
     }
 
-    // 获取游戏版本
-    pub fn get_version(&self) -> Option<VersionData> {
-        self.data
-            .get("CreatedWithVersion")
-            .and_then(|v| serde_json::from_value::<TypedValue<VersionData>>(v.clone()).ok())
-            .map(|t| t.value)
-    }
-    
-    pub fn get_master_keys(&self) -> Option<Vec<MasterKey>> {
-        self.data
-            .get("MasterKeys")
-            .and_then(|v| serde_json::from_value::<TypedValue<Vec<MasterKey>>>(v.clone()).ok())
-            .map(|t| t.value)
-    }
-    pub fn get_death_list(&self) -> Option<Vec<StashData>> {
-        self.data
-            .get("DeathList")
-            .and_then(|v| serde_json::from_value::<TypedValue<Vec<StashData>>>(v.clone()).ok())
-            .map(|t| t.value)
-    }
-    pub fn get_save_time(&self) -> Option<i32> {
-        self.data
-            .get("SaveTime")
-            .and_then(|v| serde_json::from_value::<TypedValue<i32>>(v.clone()).ok())
-            .map(|t| t.value)
-    }
-    
-    pub fn get_player_storage(&self) -> Option<Inventory> {
-        self.data
-            .get("Inventory/PlayerStorage")
-            .and_then(|v| serde_json::from_value::<TypedValue<Inventory>>(v.clone()).ok())
-            .map(|t| t.value)
-    }
-    pub fn get_inventory_safe(&self) -> Option<Inventory> {
-        self.data
-            .get("Inventory/Inventory_Safe")
-            .and_then(|v| serde_json::from_value::<TypedValue<Inventory>>(v.clone()).ok())
-            .map(|t| t.value)
-    }
-    
-    pub fn get_game_lock(&self) -> Option<GameClock> {
-        self.data
-            .get("GameClock")
-            .and_then(|v| serde_json::from_value::<TypedValue<GameClock>>(v.clone()).ok())
-            .map(|t| t.value)
-    }
-    pub fn get_health(&self) -> Option<f32> {
-        self.data
-            .get("MainCharacterHealth")
-            .and_then(|v| serde_json::from_value::<TypedValue<f32>>(v.clone()).ok())
-            .map(|t| t.value)
-    }
-    
-    pub fn get_character_items(&self) -> Option<ItemTreeData> {
-        self.data
-            .get("Item/MainCharacterItemData")
-            .and_then(|v| serde_json::from_value::<TypedValue<ItemTreeData>>(v.clone()).ok())
-            .map(|t| t.value)
-    }
-    
-    // 获取自定义外观
-    pub fn get_custom_face(&self) -> Option<CustomFaceSettingData> {
-        self.data
-            .get("CustomFace_MainCharacter")
-            .and_then(|v| serde_json::from_value::<TypedValue<CustomFaceSettingData>>(v.clone()).ok())
-            .map(|t| t.value)
+    // 直接解析游戏写出的 ES3 格式存档
+    pub fn from_es3(text: impl Into<String>) -> Result<Self, SaveParseError> {
+        let source = text.into();
+        let sections = crate::format::index_sections(&source)?;
+        let index = sections
+            .iter()
+            .enumerate()
+            .map(|(i, section)| (section.key.clone(), i))
+            .collect();
+        let save_data = SaveData {
+            source,
+            sections,
+            index,
+            overrides: HashMap::new(),
+        };
+        save_data.check_required()
     }
 
-    // 获取击杀统计
-    pub fn get_kill_stats(&self) -> HashMap<String, i32> {
-        let mut stats = HashMap::new();
-        for (key, value) in &self.data {
-            if key.starts_with("Count/Kills/") {
-                if let Ok(typed_value) = serde_json::from_value::<TypedValue<i32>>(value.clone()) {
-                    let enemy_name = key.strip_prefix("Count/Kills/").unwrap_or(key);
-                    stats.insert(enemy_name.to_string(), typed_value.value);
-                }
-            }
+    fn check_required(self) -> Result<Self, SaveParseError> {
+        let save_data = self;
+        if let Some(key) = Self::REQUIRED_KEYS.iter().find(|key| !save_data.contains_key(key)) {
+            return Err(SaveParseError::MissingKey { key: key.to_string() });
         }
-        stats
+        Ok(save_data)
     }
 
-    // 获取已访问的场景
-    pub fn get_visited_scenes(&self) -> Vec<String> {
-        let mut scenes = Vec::new();
-        for (key, value) in &self.data {
-            if key.starts_with("MultiSceneCore_Visited_") {
-                if let Ok(typed_value) = serde_json::from_value::<TypedValue<bool>>(value.clone()) {
-                    if typed_value.value {
-                        let scene_name = key.strip_prefix("MultiSceneCore_Visited_").unwrap_or(key);
-                        scenes.push(scene_name.to_string());
-                    }
+    /// 存档原文
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// 按原文顺序排列的顶层段落索引
+    pub fn sections(&self) -> &[RawSection] {
+        &self.sections
+    }
+
+    /// 按原文顺序列出所有顶层键
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.sections.iter().map(|section| section.key.as_str())
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// 段落在原文中的文本，不受改写影响
+    pub fn raw(&self, key: &str) -> Option<&str> {
+        self.raw_range(key).map(|range| &self.source[range])
+    }
+
+    pub(crate) fn raw_range(&self, key: &str) -> Option<std::ops::Range<usize>> {
+        self.index.get(key).map(|&i| self.sections[i].value.clone())
+    }
+
+    /// 整个段落（含 `__type` 包装）转换为 Value
+    pub fn value(&self, key: &str) -> Result<Option<Value>, SaveParseError> {
+        if let Some(value) = self.overrides.get(key) {
+            return Ok(Some(value.clone()));
+        }
+        self.parse_raw(key)
+    }
+
+    /// 可修改的段落，第一次访问时从原文构造
+    pub fn value_mut(&mut self, key: &str) -> Result<Option<&mut Value>, SaveParseError> {
+        if !self.overrides.contains_key(key) {
+            let Some(value) = self.parse_raw(key)? else {
+                return Ok(None);
+            };
+            self.overrides.insert(key.to_string(), value);
+        }
+        Ok(self.overrides.get_mut(key))
+    }
+
+    /// 替换整个段落（含 `__type` 包装）
+    pub fn set_value(&mut self, key: &str, value: Value) {
+        self.overrides.insert(key.to_string(), value);
+    }
+
+    fn parse_raw(&self, key: &str) -> Result<Option<Value>, SaveParseError> {
+        match self.raw_range(key) {
+            Some(range) => crate::format::from_slice_at(&self.source, range)
+                .map(Some)
+                .map_err(|e| SaveParseError::section(key, e)),
+            None => Ok(None),
+        }
+    }
+
+    // 读取 `{"__type": ..., "value": ...}` 包装的段落，键不存在时返回 None
+    fn get_section<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, SaveParseError> {
+        let wrapper: Option<TypedValue<T>> = if let Some(value) = self.overrides.get(key) {
+            Some(TypedValue::deserialize(value).map_err(|e| SaveParseError::section(key, e))?)
+        } else if let Some(range) = self.raw_range(key) {
+            Some(crate::format::from_slice_at(&self.source, range).map_err(|e| SaveParseError::section(key, e))?)
+        } else {
+            None
+        };
+        Ok(wrapper.map(|t| t.value))
+    }
+
+    // 以某个前缀开头的段落，键为去掉前缀后的名称；类型不符的段落不计入结果，错误单独返回
+    fn sections_with_prefix<T: DeserializeOwned>(&self, prefix: &str) -> (BTreeMap<String, T>, Vec<SaveParseError>) {
+        let mut values = BTreeMap::new();
+        let mut errors = Vec::new();
+        for key in self.keys() {
+            let Some(name) = key.strip_prefix(prefix) else {
+                continue;
+            };
+            match self.get_section::<T>(key) {
+                Ok(Some(value)) => {
+                    values.insert(name.to_string(), value);
                 }
+                Ok(None) => {}
+                Err(err) => errors.push(err),
             }
         }
-        scenes
+        (values, errors)
+    }
+
+    // 获取游戏版本
+    pub fn get_version(&self) -> Result<Option<VersionData>, SaveParseError> {
+        self.get_section("CreatedWithVersion")
+    }
+
+    pub fn get_master_keys(&self) -> Result<Option<Vec<MasterKey>>, SaveParseError> {
+        self.get_section("MasterKeys")
+    }
+
+    pub fn get_death_list(&self) -> Result<Option<Vec<StashData>>, SaveParseError> {
+        self.get_section("DeathList")
+    }
+
+    pub fn get_save_time(&self) -> Result<Option<i32>, SaveParseError> {
+        self.get_section("SaveTime")
+    }
+
+    pub fn get_player_storage(&self) -> Result<Option<Inventory>, SaveParseError> {
+        self.get_section("Inventory/PlayerStorage")
+    }
+
+    pub fn get_inventory_safe(&self) -> Result<Option<Inventory>, SaveParseError> {
+        self.get_section("Inventory/Inventory_Safe")
+    }
+
+    pub fn get_game_lock(&self) -> Result<Option<GameClock>, SaveParseError> {
+        self.get_section("GameClock")
+    }
+
+    pub fn get_health(&self) -> Result<Option<f32>, SaveParseError> {
+        self.get_section("MainCharacterHealth")
+    }
+
+    pub fn get_character_items(&self) -> Result<Option<ItemTreeData>, SaveParseError> {
+        self.get_section("Item/MainCharacterItemData")
+    }
+
+    // 获取自定义外观
+    pub fn get_custom_face(&self) -> Result<Option<CustomFaceSettingData>, SaveParseError> {
+        self.get_section("CustomFace_MainCharacter")
+    }
+
+    // 获取击杀统计
+    pub fn get_kill_stats(&self) -> (BTreeMap<String, i32>, Vec<SaveParseError>) {
+        self.sections_with_prefix("Count/Kills/")
+    }
+
+    // 获取已访问的场景
+    pub fn get_visited_scenes(&self) -> (Vec<String>, Vec<SaveParseError>) {
+        let (scenes, errors) = self.sections_with_prefix::<bool>("MultiSceneCore_Visited_");
+        let scenes = scenes
+            .into_iter()
+            .filter(|(_, visited)| *visited)
+            .map(|(scene_name, _)| scene_name)
+            .collect();
+        (scenes, errors)
     }
 
     // 获取货币
-    pub fn get_currency(&self) -> Option<EconomyData> {
-        self.data
-            .get("EconomyData")
-            .and_then(|v| serde_json::from_value::<TypedValue<EconomyData>>(v.clone()).ok())
-            .map(|t| t.value)
+    pub fn get_currency(&self) -> Result<Option<EconomyData>, SaveParseError> {
+        self.get_section("EconomyData")
     }
-    
+
     // 获取任务
-    pub fn get_quests(&self) -> Option<QuestData> {
-        self.data
-            .get("QuestData")
-            .and_then(|v| serde_json::from_value::<TypedValue<QuestData>>(v.clone()).ok())
-            .map(|t| t.value)
+    pub fn get_quests(&self) -> Result<Option<QuestData>, SaveParseError> {
+        self.get_section("QuestData")
     }
-    
+
     // 获取经验
-    pub fn get_exp(&self) -> Option<i32> {
-        self.data
-            .get("EXP_Value")
-            .and_then(|v| serde_json::from_value::<TypedValue<i32>>(v.clone()).ok())
-            .map(|t| t.value)
+    pub fn get_exp(&self) -> Result<Option<i32>, SaveParseError> {
+        self.get_section("EXP_Value")
     }
 
     // 获取建造站点状态
-    pub fn get_construction_sites(&self) -> HashMap<String, bool> {
-        let mut sites = HashMap::new();
-        for (key, value) in &self.data {
-            if key.starts_with("ConstructionSite_") {
-                if let Ok(typed_value) = serde_json::from_value::<TypedValue<bool>>(value.clone()) {
-                    let site_name = key.strip_prefix("ConstructionSite_").unwrap_or(key);
-                    sites.insert(site_name.to_string(), typed_value.value);
-                }
-            }
-        }
-        sites
+    pub fn get_construction_sites(&self) -> (BTreeMap<String, bool>, Vec<SaveParseError>) {
+        self.sections_with_prefix("ConstructionSite_")
+    }
+
+    // 获取商人库存，键为去掉 StockShop_ 前缀的商人名
+    pub fn get_merchant_stocks(&self) -> (BTreeMap<String, StockShopData>, Vec<SaveParseError>) {
+        self.sections_with_prefix("StockShop_")
     }
 
     // 导出为JSON
-    pub fn to_json(&self) -> Result<String, serde_json::Error> {
-        serde_json::to_string_pretty(&self.data)
+    pub fn to_json(&self) -> Result<String, SaveParseError> {
+        let mut map = serde_json::Map::new();
+        for key in self.keys() {
+            if let Some(value) = self.value(key)? {
+                map.insert(key.to_string(), value);
+            }
+        }
+        serde_json::to_string_pretty(&map).map_err(|e| SaveParseError::Serialize { message: e.to_string() })
     }
-}
\ No newline at end of file
+}
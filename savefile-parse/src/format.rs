@@ -1,20 +1,671 @@
-use regex::Regex;
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Range;
+use serde::de::value::{BorrowedStrDeserializer, StringDeserializer};
+use serde::de::{self, DeserializeSeed, IgnoredAny, IntoDeserializer, Visitor};
+use serde::Deserialize;
+use crate::error::SaveParseError;
 
-/// 修复Unity存档中的JSON格式错误
+// Unity ES3 存档的 JSON 方言：
+// 基础类型写作 `"__type":"<t>"<literal>`，缺少 `,"value":`，其余部分与 JSON 一致。
+// 这里直接在原始文本上做词法分析并驱动 serde，不生成修复后的中间字符串。
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ErrorKind {
+    /// 文本本身不合法
+    Syntax,
+    /// 文本合法但与目标类型不符
+    Data,
+}
+
+/// 带位置信息的解析错误，行列均从 1 开始
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    kind: ErrorKind,
+    message: String,
+    offset: Option<usize>,
+    line: usize,
+    column: usize,
+}
+
+impl Error {
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    fn at(mut self, input: &str, offset: usize) -> Self {
+        if self.offset.is_none() {
+            let (line, column) = line_column(input, offset);
+            self.offset = Some(offset);
+            self.line = line;
+            self.column = column;
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {} column {}", self.message, self.line, self.column)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error {
+            kind: ErrorKind::Data,
+            message: msg.to_string(),
+            offset: None,
+            line: 0,
+            column: 0,
+        }
+    }
+}
+
+impl From<Error> for SaveParseError {
+    fn from(err: Error) -> Self {
+        match err.kind {
+            ErrorKind::Syntax => SaveParseError::JsonSyntax {
+                line: err.line,
+                column: err.column,
+                message: err.message,
+            },
+            ErrorKind::Data => SaveParseError::SectionType {
+                key: "$".to_string(),
+                message: err.to_string(),
+            },
+        }
+    }
+}
+
+fn line_column(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset.min(input.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+// 对象与数组的最大嵌套层数，与 serde_json 一致，避免恶意存档耗尽栈空间
+const MAX_DEPTH: usize = 128;
+
+/// ES3 存档的 serde 反序列化器
+pub struct Deserializer<'de> {
+    input: &'de str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(input: &'de str) -> Self {
+        Deserializer { input, pos: 0, depth: 0 }
+    }
+
+    fn syntax(&self, message: impl Into<String>) -> Error {
+        self.syntax_at(self.pos, message)
+    }
+
+    fn syntax_at(&self, offset: usize, message: impl Into<String>) -> Error {
+        Error {
+            kind: ErrorKind::Syntax,
+            message: message.into(),
+            offset: None,
+            line: 0,
+            column: 0,
+        }
+        .at(self.input, offset)
+    }
+
+    fn bytes(&self) -> &'de [u8] {
+        self.input.as_bytes()
+    }
+
+    fn skip_ws(&mut self) {
+        while self.pos < self.input.len() && self.bytes()[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_ws();
+        self.bytes().get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), Error> {
+        match self.peek() {
+            Some(b) if b == byte => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(b) => Err(self.syntax(format!("expected '{}', found '{}'", byte as char, b as char))),
+            None => Err(self.syntax(format!("expected '{}', found end of input", byte as char))),
+        }
+    }
+
+    fn eat_ident(&mut self, ident: &str) -> bool {
+        if self.input[self.pos..].starts_with(ident) {
+            self.pos += ident.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    // 进入一层对象或数组
+    fn enter(&mut self) -> Result<(), Error> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.syntax("recursion limit exceeded"));
+        }
+        self.depth += 1;
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result<(), Error> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.syntax("trailing characters")),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<Cow<'de, str>, Error> {
+        self.expect(b'"')?;
+        let start = self.pos;
+        let bytes = self.bytes();
+
+        // 没有转义时直接借用原文
+        while self.pos < bytes.len() {
+            match bytes[self.pos] {
+                b'"' => {
+                    let s = &self.input[start..self.pos];
+                    self.pos += 1;
+                    return Ok(Cow::Borrowed(s));
+                }
+                b'\\' => break,
+                _ => self.pos += 1,
+            }
+        }
+
+        let mut out = String::from(&self.input[start..self.pos]);
+        loop {
+            let Some(&b) = bytes.get(self.pos) else {
+                return Err(self.syntax("unterminated string"));
+            };
+            match b {
+                b'"' => {
+                    self.pos += 1;
+                    return Ok(Cow::Owned(out));
+                }
+                b'\\' => {
+                    self.pos += 1;
+                    let Some(&escape) = bytes.get(self.pos) else {
+                        return Err(self.syntax("unterminated string"));
+                    };
+                    self.pos += 1;
+                    match escape {
+                        b'"' => out.push('"'),
+                        b'\\' => out.push('\\'),
+                        b'/' => out.push('/'),
+                        b'b' => out.push('\u{8}'),
+                        b'f' => out.push('\u{c}'),
+                        b'n' => out.push('\n'),
+                        b'r' => out.push('\r'),
+                        b't' => out.push('\t'),
+                        b'u' => out.push(self.parse_unicode_escape()?),
+                        other => {
+                            self.pos -= 1;
+                            return Err(self.syntax(format!("invalid escape '\\{}'", other as char)));
+                        }
+                    }
+                }
+                _ => {
+                    let ch = self.input[self.pos..].chars().next().expect("pos 位于字符边界");
+                    out.push(ch);
+                    self.pos += ch.len_utf8();
+                }
+            }
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u16, Error> {
+        let digits = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| self.syntax("unterminated unicode escape"))?;
+        let value = u16::from_str_radix(digits, 16).map_err(|_| self.syntax("invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(value)
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, Error> {
+        let high = self.parse_hex4()?;
+        let code = if (0xd800..0xdc00).contains(&high) {
+            if !self.eat_ident("\\u") {
+                return Err(self.syntax("unpaired surrogate in unicode escape"));
+            }
+            let low = self.parse_hex4()?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(self.syntax("unpaired surrogate in unicode escape"));
+            }
+            0x10000 + (((high as u32) - 0xd800) << 10) + ((low as u32) - 0xdc00)
+        } else {
+            high as u32
+        };
+        char::from_u32(code).ok_or_else(|| self.syntax("invalid unicode escape"))
+    }
+
+    fn parse_number<V: Visitor<'de>>(&mut self, visitor: V) -> Result<V::Value, Error> {
+        let start = self.pos;
+        // C# 会把非有限浮点数写成 NaN / Infinity
+        for (ident, value) in [("NaN", f64::NAN), ("Infinity", f64::INFINITY), ("-Infinity", f64::NEG_INFINITY)] {
+            if self.eat_ident(ident) {
+                return visitor.visit_f64(value);
+            }
+        }
+
+        let bytes = self.bytes();
+        while self.pos < bytes.len() && matches!(bytes[self.pos], b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E') {
+            self.pos += 1;
+        }
+        let literal = &self.input[start..self.pos];
+        let invalid = |de: &Self| de.syntax_at(start, format!("invalid number '{}'", literal));
+
+        if literal.contains(['.', 'e', 'E']) {
+            let value: f64 = literal.parse().map_err(|_| invalid(self))?;
+            visitor.visit_f64(value)
+        } else if literal.starts_with('-') {
+            match literal.parse::<i64>() {
+                Ok(value) => visitor.visit_i64(value),
+                Err(_) => visitor.visit_f64(literal.parse().map_err(|_| invalid(self))?),
+            }
+        } else {
+            match literal.parse::<u64>() {
+                Ok(value) => visitor.visit_u64(value),
+                Err(_) => visitor.visit_f64(literal.parse().map_err(|_| invalid(self))?),
+            }
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let start = match self.peek() {
+            Some(_) => self.pos,
+            None => return Err(self.syntax("unexpected end of input")),
+        };
+        let result = match self.bytes()[self.pos] {
+            b'{' => {
+                self.enter()?;
+                let value = visitor.visit_map(MapAccess::new(self))?;
+                self.expect(b'}')?;
+                self.depth -= 1;
+                Ok(value)
+            }
+            b'[' => {
+                self.enter()?;
+                let value = visitor.visit_seq(SeqAccess { de: self, first: true })?;
+                self.expect(b']')?;
+                self.depth -= 1;
+                Ok(value)
+            }
+            b'"' => match self.parse_string()? {
+                Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+                Cow::Owned(s) => visitor.visit_string(s),
+            },
+            b't' if self.eat_ident("true") => visitor.visit_bool(true),
+            b'f' if self.eat_ident("false") => visitor.visit_bool(false),
+            b'n' if self.eat_ident("null") => visitor.visit_unit(),
+            b'-' | b'0'..=b'9' | b'N' | b'I' => self.parse_number(visitor),
+            other => Err(self.syntax(format!("unexpected character '{}'", other as char))),
+        };
+        result.map_err(|err| err.at(self.input, start))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.skip_ws();
+        if self.eat_ident("null") {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.peek() {
+            Some(b'"') => {
+                let variant = self.parse_string()?.into_owned();
+                visitor.visit_enum(variant.into_deserializer())
+            }
+            _ => Err(self.syntax("expected enum variant name")),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+// `"__type":"int"123` 中缺失的 value 键按如下状态补齐
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quirk {
+    None,
+    /// 刚读到 `__type` 键
+    TypeKey,
+    /// 下一个键应当返回虚拟的 `value`
+    ValueKey,
+    /// 下一个值是紧跟在类型名后的字面量
+    Literal,
+}
+
+struct MapAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    first: bool,
+    quirk: Quirk,
+}
+
+impl<'a, 'de> MapAccess<'a, 'de> {
+    fn new(de: &'a mut Deserializer<'de>) -> Self {
+        MapAccess { de, first: true, quirk: Quirk::None }
+    }
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        if self.quirk == Quirk::ValueKey {
+            self.quirk = Quirk::Literal;
+            return seed.deserialize(BorrowedStrDeserializer::new("value")).map(Some);
+        }
+
+        match self.de.peek() {
+            Some(b'}') => return Ok(None),
+            Some(b',') if !self.first => {
+                self.de.pos += 1;
+                // 容忍结尾多余的逗号
+                if self.de.peek() == Some(b'}') {
+                    return Ok(None);
+                }
+            }
+            Some(_) if self.first => {}
+            Some(_) => return Err(self.de.syntax("expected ',' or '}'")),
+            None => return Err(self.de.syntax("unterminated object")),
+        }
+        self.first = false;
+
+        if self.de.peek() != Some(b'"') {
+            return Err(self.de.syntax("expected object key"));
+        }
+        let key = self.de.parse_string()?;
+        self.de.expect(b':')?;
+        if key == "__type" {
+            self.quirk = Quirk::TypeKey;
+        }
+        match key {
+            Cow::Borrowed(s) => seed.deserialize(BorrowedStrDeserializer::new(s)),
+            Cow::Owned(s) => seed.deserialize(StringDeserializer::new(s)),
+        }
+        .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        match self.quirk {
+            Quirk::TypeKey if self.de.peek() == Some(b'"') => {
+                let value = seed.deserialize(&mut *self.de)?;
+                // 类型名之后不是分隔符，说明后面紧跟着字面量
+                self.quirk = match self.de.peek() {
+                    Some(b',') | Some(b'}') | None => Quirk::None,
+                    Some(_) => Quirk::ValueKey,
+                };
+                Ok(value)
+            }
+            _ => {
+                self.quirk = Quirk::None;
+                seed.deserialize(&mut *self.de)
+            }
+        }
+    }
+}
+
+struct SeqAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    first: bool,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        match self.de.peek() {
+            Some(b']') => return Ok(None),
+            Some(b',') if !self.first => {
+                self.de.pos += 1;
+                if self.de.peek() == Some(b']') {
+                    return Ok(None);
+                }
+            }
+            Some(_) if self.first => {}
+            Some(_) => return Err(self.de.syntax("expected ',' or ']'")),
+            None => return Err(self.de.syntax("unterminated array")),
+        }
+        self.first = false;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+/// 直接从 ES3 存档文本反序列化
+pub fn from_str<'de, T: Deserialize<'de>>(input: &'de str) -> Result<T, Error> {
+    let mut de = Deserializer::new(input);
+    let value = T::deserialize(&mut de).map_err(|err| err.at(input, de.pos))?;
+    de.end()?;
+    Ok(value)
+}
+
+/// 从完整文本中的一段反序列化，错误位置相对于完整文本
+pub fn from_slice_at<'de, T: Deserialize<'de>>(input: &'de str, range: Range<usize>) -> Result<T, Error> {
+    // 段落位于顶层对象之内，嵌套层数从 1 开始计
+    let mut de = Deserializer { input: &input[..range.end], pos: range.start, depth: 1 };
+    let value = T::deserialize(&mut de).map_err(|err| err.at(input, de.pos))?;
+    de.end()?;
+    Ok(value)
+}
+
+/// 顶层对象中的一个键，`value` 为值在原文中的字节范围
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawSection {
+    pub key: String,
+    pub value: Range<usize>,
+}
+
+/// 扫描一遍顶层对象，只校验语法并记录每个值的位置，不构造任何值
+pub fn index_sections(input: &str) -> Result<Vec<RawSection>, Error> {
+    let mut de = Deserializer::new(input);
+    de.expect(b'{')?;
+    de.depth = 1;
+
+    let mut sections = Vec::new();
+    let mut first = true;
+    loop {
+        match de.peek() {
+            Some(b'}') => break,
+            Some(b',') if !first => {
+                de.pos += 1;
+                if de.peek() == Some(b'}') {
+                    break;
+                }
+            }
+            Some(_) if first => {}
+            Some(_) => return Err(de.syntax("expected ',' or '}'")),
+            None => return Err(de.syntax("unterminated object")),
+        }
+        first = false;
+
+        if de.peek() != Some(b'"') {
+            return Err(de.syntax("expected object key"));
+        }
+        let key = de.parse_string()?.into_owned();
+        de.expect(b':')?;
+        de.skip_ws();
+        let start = de.pos;
+        IgnoredAny::deserialize(&mut de).map_err(|err| err.at(input, de.pos))?;
+        sections.push(RawSection { key, value: start..de.pos });
+    }
+    de.pos += 1;
+    de.end()?;
+
+    Ok(sections)
+}
+
+/// 将 ES3 存档文本转换为标准 JSON：在 `"__type":"<t>"` 与字面量之间补上 `, "value": `，其余字符原样保留
 pub fn fix_json_format(content: &str) -> String {
-    let mut result = content.to_string();
+    #[derive(PartialEq)]
+    enum State {
+        None,
+        TypeKey,
+        Colon,
+        TypeName,
+    }
+
+    let bytes = content.as_bytes();
+    let mut out = String::with_capacity(content.len() + content.len() / 16);
+    let mut state = State::None;
+    let mut copied = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if state == State::TypeName && !matches!(b, b',' | b'}' | b']') {
+            out.push_str(&content[copied..i]);
+            out.push_str(", \"value\": ");
+            copied = i;
+        }
+
+        if b == b'"' {
+            let start = i;
+            i += 1;
+            while i < bytes.len() && bytes[i] != b'"' {
+                i += if bytes[i] == b'\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(bytes.len());
+            state = match state {
+                State::Colon => State::TypeName,
+                _ if &content[start..i] == "\"__type\"" => State::TypeKey,
+                _ => State::None,
+            };
+            continue;
+        }
+
+        state = match (state, b) {
+            (State::TypeKey, b':') => State::Colon,
+            _ => State::None,
+        };
+        i += 1;
+    }
+
+    out.push_str(&content[copied..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn parse(input: &str) -> Value {
+        from_str(input).unwrap()
+    }
+
+    #[test]
+    fn test_primitive_quirks() {
+        assert_eq!(parse(r#"{"__type":"int"-42}"#), json!({"__type": "int", "value": -42}));
+        assert_eq!(parse(r#"{"__type" : "bool"true}"#), json!({"__type": "bool", "value": true}));
+        assert_eq!(parse(r#"{"__type":"long"9007199254740993}"#)["value"], json!(9007199254740993u64));
+        assert_eq!(parse(r#"{"__type":"float"1e-5}"#)["value"], json!(1e-5));
+        assert_eq!(parse(r#"{"__type":"double"-3.25E+2}"#)["value"], json!(-325.0));
+        assert_eq!(parse(r#"{"__type":"string""小鸭\"quack\""}"#)["value"], json!("小鸭\"quack\""));
+    }
+
+    #[test]
+    fn test_nested_quirks() {
+        let value = parse(r#"{"a":{"__type":"Dict","value":{"hp":{"__type":"float"-0.5},"ok":{"__type":"bool"false}}}}"#);
+        assert_eq!(value["a"]["value"]["hp"]["value"], json!(-0.5));
+        assert_eq!(value["a"]["value"]["ok"]["value"], json!(false));
+    }
+
+    #[test]
+    fn test_standard_type_wrapper_untouched() {
+        let value = parse(r#"{"__type":"int","value":3}"#);
+        assert_eq!(value, json!({"__type": "int", "value": 3}));
+    }
+
+    #[test]
+    fn test_error_position() {
+        let err = from_str::<Value>("{\n\t\"a\" : {\n\t\t\"__type\" : \"int\"12x\n\t}\n}").unwrap_err();
+        assert_eq!((err.line(), err.column()), (3, 21));
+
+        let err = from_str::<Value>("{\"a\":1 \"b\":2}").unwrap_err();
+        assert_eq!((err.line(), err.column()), (1, 8));
+    }
+
+    #[test]
+    fn test_recursion_limit() {
+        let nested = |depth: usize| format!("{{\"a\":{}{}}}", "[".repeat(depth), "]".repeat(depth));
+        assert!(from_str::<Value>(&nested(MAX_DEPTH - 1)).is_ok());
+
+        // 超出层数时返回带位置的错误，而不是栈溢出
+        let input = format!("{{\n\"a\":{}", "[".repeat(200_000));
+        let err = from_str::<Value>(&input).unwrap_err();
+        assert_eq!((err.line(), err.column()), (2, MAX_DEPTH + 4));
+        assert!(err.to_string().contains("recursion limit exceeded"));
+        let err = index_sections(&input).unwrap_err();
+        assert_eq!((err.line(), err.column()), (2, MAX_DEPTH + 4));
+    }
 
-    // 修复 "__type" : "bool"false 格式
-    let bool_re = Regex::new(r#""__type"\s*:\s*"bool"(true|false)"#).unwrap();
-    result = bool_re.replace_all(&result, r#""__type": "bool", "value": $1"#).to_string();
+    #[test]
+    fn test_index_sections() {
+        let input = "{\n\t\"a\" : {\"__type\":\"int\"5},\n\t\"b\" : [1, 2],\n}";
+        let sections = index_sections(input).unwrap();
+        let keys: Vec<&str> = sections.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, ["a", "b"]);
+        assert_eq!(&input[sections[1].value.clone()], "[1, 2]");
 
-    // 修复 "__type" : "int"数字 格式（包括负数）
-    let int_re = Regex::new(r#""__type"\s*:\s*"int"(-?\d+)"#).unwrap();
-    result = int_re.replace_all(&result, r#""__type": "int", "value": $1"#).to_string();
+        let a: Value = from_slice_at(input, sections[0].value.clone()).unwrap();
+        assert_eq!(a["value"], json!(5));
 
-    // 修复 "__type" : "float"数字 格式
-    let float_re = Regex::new(r#""__type"\s*:\s*"float"([\d.]+)"#).unwrap();
-    result = float_re.replace_all(&result, r#""__type": "float", "value": $1"#).to_string();
+        let err = from_slice_at::<Vec<String>>(input, sections[1].value.clone()).unwrap_err();
+        assert_eq!((err.line(), err.column()), (3, 9));
+    }
 
-    result
-}
\ No newline at end of file
+    #[test]
+    fn test_fix_json_format() {
+        let fixed = fix_json_format(r#"{"n":{"__type" : "int"-1},"f":{"__type":"float"2.5e-3},"s":{"__type":"string""x"},"o":{"__type":"T","value":1}}"#);
+        assert_eq!(
+            fixed,
+            r#"{"n":{"__type" : "int", "value": -1},"f":{"__type":"float", "value": 2.5e-3},"s":{"__type":"string", "value": "x"},"o":{"__type":"T","value":1}}"#
+        );
+        let standard: Value = serde_json::from_str(&fixed).unwrap();
+        assert_eq!(standard, parse(r#"{"n":{"__type" : "int"-1},"f":{"__type":"float"2.5e-3},"s":{"__type":"string""x"},"o":{"__type":"T","value":1}}"#));
+    }
+}
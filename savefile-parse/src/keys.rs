@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use crate::extractor::SaveData;
+
+// 存档顶层键清单：列出每个键的 `__type`、大小以及是否已有 getter 读取，
+// 游戏更新后用来发现需要建模的新段落。
+
+/// 一个顶层键
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyInfo {
+    pub key: String,
+    /// `__type` 的值，段落不是对象或没有该字段时为 None
+    pub type_name: Option<String>,
+    /// 值在原文中的字节数
+    pub size: usize,
+    /// 是否已有 getter 读取
+    pub extracted: bool,
+    /// 所属分组的前缀
+    pub group: Option<String>,
+}
+
+/// 前缀相同的一组键，如 `Count/Kills/*`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyGroup {
+    pub prefix: String,
+    pub count: usize,
+    pub size: usize,
+    /// 组内出现过的 `__type`，已去重排序
+    pub type_names: Vec<String>,
+    /// 组内的键是否全部已有 getter 读取
+    pub extracted: bool,
+}
+
+/// 存档顶层键清单
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyReport {
+    /// 按原文顺序排列
+    pub keys: Vec<KeyInfo>,
+    /// 按前缀排序
+    pub groups: Vec<KeyGroup>,
+}
+
+impl KeyReport {
+    /// 还没有 getter 读取的键
+    pub fn unextracted(&self) -> impl Iterator<Item = &KeyInfo> {
+        self.keys.iter().filter(|info| !info.extracted)
+    }
+}
+
+#[derive(Deserialize)]
+struct TypeTag {
+    #[serde(rename = "__type")]
+    type_name: Option<String>,
+}
+
+fn extractor_prefix(key: &str) -> Option<&'static str> {
+    SaveData::EXTRACTED_PREFIXES.iter().copied().find(|prefix| key.starts_with(prefix))
+}
+
+// 未知的键按最后一个 `/` 或 `_` 之前的部分分组
+fn guess_prefix(key: &str) -> Option<&str> {
+    let end = key.rfind('/').or_else(|| key.rfind('_'))?;
+    Some(&key[..=end])
+}
+
+/// 生成存档顶层键清单，`__type` 与大小均取自原文，不受版本适配影响
+pub fn key_report(save_data: &SaveData) -> KeyReport {
+    let mut keys: Vec<KeyInfo> = save_data
+        .keys()
+        .map(|key| {
+            let raw = save_data.raw(key).unwrap_or_default();
+            let prefix = extractor_prefix(key);
+            KeyInfo {
+                key: key.to_string(),
+                type_name: crate::format::from_slice_at::<TypeTag>(raw, 0..raw.len())
+                    .ok()
+                    .and_then(|tag| tag.type_name),
+                size: raw.len(),
+                extracted: prefix.is_some() || SaveData::EXTRACTED_KEYS.contains(&key),
+                group: prefix.map(str::to_string),
+            }
+        })
+        .collect();
+
+    // 只有一个成员的猜测前缀不算分组
+    let mut guessed: BTreeMap<String, usize> = BTreeMap::new();
+    for info in keys.iter().filter(|info| info.group.is_none()) {
+        if let Some(prefix) = guess_prefix(&info.key) {
+            *guessed.entry(prefix.to_string()).or_default() += 1;
+        }
+    }
+    for info in keys.iter_mut().filter(|info| info.group.is_none()) {
+        info.group = guess_prefix(&info.key)
+            .filter(|prefix| guessed[*prefix] > 1)
+            .map(str::to_string);
+    }
+
+    let mut groups: BTreeMap<&str, KeyGroup> = BTreeMap::new();
+    for info in &keys {
+        let Some(prefix) = &info.group else {
+            continue;
+        };
+        let group = groups.entry(prefix).or_insert_with(|| KeyGroup {
+            prefix: prefix.clone(),
+            count: 0,
+            size: 0,
+            type_names: Vec::new(),
+            extracted: true,
+        });
+        group.count += 1;
+        group.size += info.size;
+        group.extracted &= info.extracted;
+        if let Some(type_name) = &info.type_name
+            && !group.type_names.contains(type_name)
+        {
+            group.type_names.push(type_name.clone());
+        }
+    }
+    let groups = groups
+        .into_values()
+        .map(|mut group| {
+            group.type_names.sort();
+            group
+        })
+        .collect();
+
+    KeyReport { keys, groups }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = include_str!("../fixtures/sample.sav");
+
+    fn with_new_keys() -> String {
+        let extra = r#",
+	"Perk_Skill_Sprint" : { "__type" : "bool", "value" : true },
+	"Perk_Skill_Reload" : { "__type" : "bool", "value" : false },
+	"WeatherSystem" : { "__type" : "Duckov.Weather.WeatherData,TeamSoda.Duckov.Core", "value" : { "seed" : 7 } }
+}"#;
+        let end = SAMPLE.trim_end().strip_suffix('}').unwrap().trim_end();
+        format!("{}{}", end, extra)
+    }
+
+    #[test]
+    fn test_sample_fully_extracted() {
+        let report = key_report(&SaveData::from_es3(SAMPLE).unwrap());
+        assert_eq!(report.keys.len(), 20);
+        assert_eq!(report.unextracted().count(), 0);
+
+        let kills = report.groups.iter().find(|group| group.prefix == "Count/Kills/").unwrap();
+        assert_eq!(kills.count, 2);
+        assert_eq!(kills.type_names, ["int"]);
+        // Inventory/PlayerStorage 与 Inventory/Inventory_Safe 按猜测的前缀分组
+        assert!(report.groups.iter().any(|group| group.prefix == "Inventory/" && group.extracted));
+    }
+
+    #[test]
+    fn test_new_keys_reported() {
+        let report = key_report(&SaveData::from_es3(with_new_keys()).unwrap());
+        let unextracted: Vec<&str> = report.unextracted().map(|info| info.key.as_str()).collect();
+        assert_eq!(unextracted, ["Perk_Skill_Sprint", "Perk_Skill_Reload", "WeatherSystem"]);
+
+        let perks = report.groups.iter().find(|group| group.prefix == "Perk_Skill_").unwrap();
+        assert_eq!((perks.count, perks.extracted), (2, false));
+
+        let weather = report.keys.iter().find(|info| info.key == "WeatherSystem").unwrap();
+        assert_eq!(weather.group, None);
+        assert_eq!(weather.type_name.as_deref(), Some("Duckov.Weather.WeatherData,TeamSoda.Duckov.Core"));
+        assert!(weather.size > 50);
+    }
+}
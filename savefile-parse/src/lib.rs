@@ -1,26 +1,45 @@
-mod extractor;
-mod format;
-mod archived;
+pub mod extractor;
+pub mod format;
+pub mod archived;
+pub mod error;
+pub mod variable;
+pub mod tree;
+pub mod writer;
+pub mod diff;
+pub mod container;
+pub mod catalog;
+pub mod schema;
+pub mod validate;
+pub mod bindings;
+pub mod loadout;
+pub mod slots;
+pub mod keys;
+pub mod timeline;
+pub mod progress;
+#[cfg(feature = "bench")]
+mod bench;
 
-use log::{info, debug, error};
+use log::info;
 use wasm_bindgen::prelude::*;
 use crate::archived::Archive;
-use crate::format::fix_json_format;
-
-macro_rules! load_optional_field {
-    ($source:expr, $target:expr, $field:ident, $getter:ident) => {
-        if let Some(value) = $source.$getter() {
-            $target.$field = value.into();
-        }
-    };
-}
-
+use crate::catalog::ItemCatalog;
+use crate::container::{DEFAULT_PASSWORD, decode_container};
+use crate::error::SaveParseError;
+use crate::extractor::SaveData;
+use crate::loadout::Loadout;
+use crate::progress::{ParseProgress, ProgressSink, Sections};
+use crate::schema::GameVersion;
+use crate::writer::SaveDocument;
 
+// 段落缺失时保留默认值，解析失败时记录到 warnings；每个段落开始前报告进度
 macro_rules! load_optional_fields {
-    ($source:expr, $target:expr, { $($field:ident => $getter:ident),* $(,)? }) => {
+    ($source:expr, $target:expr, $sections:expr, { $($field:ident => $getter:ident),* $(,)? }) => {
         $(
-            if let Some(value) = $source.$getter() {
-                $target.$field = value.into();
+            $sections.next(stringify!($field))?;
+            match $source.$getter() {
+                Ok(Some(value)) => $target.$field = value.into(),
+                Ok(None) => {}
+                Err(err) => $target.warnings.push(err),
             }
         )*
     };
@@ -45,63 +64,90 @@ pub fn init() {
     }
 }
 
-#[wasm_bindgen]
-pub fn process_file(data: &[u8]) -> Result<String, JsValue> {
-    // info!("开始处理文件, 数据长度: {} 字节", data.len());
+/// 解析选项
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// ES3 加密存档的密码
+    pub password: String,
+    /// 物品数据库，提供时为物品附加名称与价格并计算各容器合计
+    pub catalog: Option<ItemCatalog>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            password: DEFAULT_PASSWORD.to_string(),
+            catalog: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    fn from_js(password: Option<String>) -> Self {
+        ParseOptions {
+            password: password.unwrap_or_else(|| DEFAULT_PASSWORD.to_string()),
+            catalog: None,
+        }
+    }
+}
+
+/// 解密、解压后得到存档的 JSON 文本（去掉 BOM）
+pub fn decode_save_text(data: &[u8], options: &ParseOptions) -> Result<String, SaveParseError> {
+    let (plain, _) = decode_container(data, &options.password)?;
+    let text = std::str::from_utf8(&plain)?;
+    Ok(text.strip_prefix('\u{feff}').unwrap_or(text).to_string())
+}
+
+/// 解析存档字节为 Archive
+pub fn parse_archive(data: &[u8]) -> Result<Archive, SaveParseError> {
+    parse_archive_with(data, &ParseOptions::default())
+}
+
+pub fn parse_archive_with(data: &[u8], options: &ParseOptions) -> Result<Archive, SaveParseError> {
+    parse_archive_with_progress(data, options, &mut ())
+}
 
-    let json_content = std::str::from_utf8(data).map_err(|e| JsValue::from_str(&e.to_string()))?;
+// load_optional_fields 的 12 个段落加上按前缀读取的 4 组
+const SECTION_COUNT: usize = 16;
 
-    // info!("解析{}", json_content);
+/// 解析存档并在每个阶段开始前报告进度，`progress` 返回错误时停止解析
+pub fn parse_archive_with_progress<P: ProgressSink + ?Sized>(
+    data: &[u8],
+    options: &ParseOptions,
+    progress: &mut P,
+) -> Result<Archive, SaveParseError> {
+    progress.report(ParseProgress::Decode)?;
+    let json_content = decode_save_text(data, options)?;
 
-    let save_data = crate::extractor::SaveData::from_json(&fix_json_format(&json_content))
-        .expect("无法解析存档文件");
+    progress.report(ParseProgress::FixFormat)?;
+    let mut save_data = SaveData::from_es3(json_content)?;
 
     let mut archive = Archive::default();
 
-    if let Some(version) = save_data.get_version() {
-        archive.version = format!("游戏版本: {}.{}.{}{}",
-                 version.main_version,
-                 version.sub_version,
-                 version.build_version,
-                 version.suffix
-        );
-    }
-
-    // if let Some(economy_data) = save_data.get_currency() {
-    //     archive.economy_data = economy_data;
-    // }
-
-   
-
-    // if let Some(exp) = save_data.get_exp() {
-    //     archive.exp = exp;
-    // }
-    // if let Some(keys) = save_data.get_master_keys() {
-    //     archive.master_key = keys;
-    // }
-
-    
-    // if let Some(death) = save_data.get_death_list() {
-    //     archive.death_list = death;
-    // }
-
-    // if let Some(save_time) = save_data.get_save_time() {
-    //     archive.save_time = save_time;
-    // }
-    // if let Some(inventory) = save_data.get_player_storage() {
-    //     archive.player_storage = inventory;
-    // }
-    // if let Some(inventory) = save_data.get_inventory_safe() {
-    //     archive.inventory_safe = inventory;
-    // }
-    // if let Some(health) = save_data.get_health() {
-    //     archive.health = health;
-    // }
-    // if let Some(items) = save_data.get_character_items() {
-    //     archive.character_item_data = items;
-    // }
-
-    load_optional_fields!(save_data, archive, {
+    let game_version = match save_data.get_version() {
+        Ok(Some(version)) => {
+            archive.version = format!("游戏版本: {}.{}.{}{}",
+                     version.main_version,
+                     version.sub_version,
+                     version.build_version,
+                     version.suffix
+            );
+            Some(GameVersion::from(&version))
+        }
+        Ok(None) => None,
+        Err(err) => {
+            archive.warnings.push(err);
+            None
+        }
+    };
+
+    // 按版本选择适配器，把存档改写为当前布局
+    let (adapter, warning) = schema::select(game_version);
+    archive.warnings.extend(warning);
+    adapter.normalize(&mut save_data);
+
+    let mut sections = Sections::new(progress, SECTION_COUNT);
+    load_optional_fields!(save_data, archive, sections, {
         economy_data => get_currency,
         exp => get_exp,
         master_key => get_master_keys,
@@ -113,18 +159,328 @@ pub fn process_file(data: &[u8]) -> Result<String, JsValue> {
         character_item_data => get_character_items,
         game_lock => get_game_lock,
         quests => get_quests,
+        custom_face => get_custom_face,
     });
-    
-    // if let Some(game_lock) = save_data.get_game_lock() {
-    //     archive.game_lock = game_lock;
-    // }
-    archive.extract_kill_counts(&save_data.data);
-    
-    // if let Some(quests) = save_data.get_quests() {
-    //     archive.quests = quests;
-    // }
-
-    let archive_json = serde_json::to_string(&archive).unwrap();
-
-    Ok(archive_json)
-}
\ No newline at end of file
+
+    archive.quest_progress = archive.quests.progress();
+    sections.next("visited_scenes")?;
+    let (visited_scenes, errors) = save_data.get_visited_scenes();
+    archive.visited_scenes = visited_scenes;
+    archive.warnings.extend(errors);
+    sections.next("construction_sites")?;
+    let (construction_sites, errors) = save_data.get_construction_sites();
+    archive.construction_sites = construction_sites;
+    archive.warnings.extend(errors);
+    sections.next("merchant_stock")?;
+    let (merchant_stock, errors) = save_data.get_merchant_stocks();
+    archive.merchant_stock = merchant_stock;
+    archive.warnings.extend(errors);
+    sections.next("kills")?;
+    archive.extract_kill_counts(&save_data);
+
+    progress.report(ParseProgress::Items)?;
+    archive.decode_item_variables();
+    if let Some(catalog) = &options.catalog {
+        archive.apply_catalog(catalog);
+    }
+    archive.link_item_trees();
+    archive.extract_loadout();
+
+    if !archive.warnings.is_empty() {
+        info!("存档解析完成，{} 个段落解析失败", archive.warnings.len());
+    }
+
+    Ok(archive)
+}
+
+// 由 bindings.rs 生成的类型定义，写入 wasm-pack 输出的 .d.ts
+#[wasm_bindgen(typescript_custom_section)]
+const TS_BINDINGS: &str = include_str!("../bindings/savefile-parse.d.ts");
+
+/// 以 JSON 兼容的方式转换为 JS 对象：map 输出为普通对象，None 输出为 null
+fn to_js_value<T: serde::Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| SaveParseError::Serialize { message: e.to_string() }.to_js())
+}
+
+/// 解析存档，`password` 为空时使用 ES3 默认密码；`catalog` 为 items.json 的内容
+#[wasm_bindgen(unchecked_return_type = "Archive")]
+pub fn process_file(data: &[u8], password: Option<String>, catalog: Option<String>) -> Result<JsValue, JsValue> {
+    let mut options = ParseOptions::from_js(password);
+    if let Some(catalog) = catalog {
+        options.catalog = Some(ItemCatalog::from_json(&catalog).map_err(|e| e.to_js())?);
+    }
+    let archive = parse_archive_with(data, &options).map_err(|e| e.to_js())?;
+
+    to_js_value(&archive)
+}
+
+/// 在专用 Worker 中解析存档：按 `id` 向主线程发送进度与结果（见 wasm-worker 的消息协议），
+/// `cancel` 为主线程共享的取消标志，写入非 0 后在下一个阶段开始前停止
+#[wasm_bindgen]
+pub fn worker_process_file(
+    id: u32,
+    data: &[u8],
+    password: Option<String>,
+    catalog: Option<String>,
+    cancel: Option<js_sys::Int32Array>,
+) {
+    let task = wasm_worker::WorkerTask::new(id, cancel);
+    let mut options = ParseOptions::from_js(password);
+    let result = catalog
+        .map(|catalog| ItemCatalog::from_json(&catalog))
+        .transpose()
+        .and_then(|catalog| {
+            options.catalog = catalog;
+            parse_archive_with_progress(data, &options, &mut |progress: ParseProgress| {
+                if task.progress(&progress) {
+                    Ok(())
+                } else {
+                    Err(SaveParseError::Cancelled)
+                }
+            })
+        });
+    match result {
+        Ok(archive) => task.finish(Ok::<_, ()>(archive)),
+        Err(SaveParseError::Cancelled) => task.cancelled(),
+        Err(err) => task.finish(Err::<(), _>(err.with_display())),
+    }
+}
+
+/// 将编辑后的 Archive（process_file 的输出）写回原存档，返回新的存档字节
+#[wasm_bindgen]
+pub fn write_file(data: &[u8], archive_json: &str, password: Option<String>) -> Result<Vec<u8>, JsValue> {
+    let archive: Archive = serde_json::from_str(archive_json)
+        .map_err(|e| SaveParseError::section("archive", e).to_js())?;
+
+    let options = ParseOptions::from_js(password);
+    let mut document = SaveDocument::parse_with(data, &options).map_err(|e| e.to_js())?;
+    document.apply_archive(&archive).map_err(|e| e.to_js())?;
+
+    Ok(document.write_bytes())
+}
+
+/// 比较两个存档（a 为较早的存档）；`password` 为空时使用 ES3 默认密码
+#[wasm_bindgen(js_name = diff_saves, unchecked_return_type = "SaveDiff")]
+pub fn diff_files(a: &[u8], b: &[u8], password: Option<String>) -> Result<JsValue, JsValue> {
+    let diff = crate::diff::diff_saves(a, b, &ParseOptions::from_js(password)).map_err(|e| e.to_js())?;
+
+    to_js_value(&diff)
+}
+
+/// 检查存档内部一致性；`known_master_keys` 为已知的钥匙 ID
+#[wasm_bindgen(unchecked_return_type = "Finding[]")]
+pub fn validate_file(data: &[u8], password: Option<String>, known_master_keys: Option<Vec<i32>>) -> Result<JsValue, JsValue> {
+    let archive = parse_archive_with(data, &ParseOptions::from_js(password)).map_err(|e| e.to_js())?;
+    let options = crate::validate::ValidateOptions {
+        known_master_keys: known_master_keys.map(|ids| ids.into_iter().collect()),
+    };
+    let findings = crate::validate::validate(&archive, &options);
+
+    to_js_value(&findings)
+}
+
+/// 解析整个存档目录，`names` 与 `files` 一一对应（文件名可带相对路径）
+#[wasm_bindgen(unchecked_return_type = "SaveFolder")]
+pub fn process_folder(names: Vec<String>, files: Vec<js_sys::Uint8Array>, password: Option<String>) -> Result<JsValue, JsValue> {
+    if names.len() != files.len() {
+        return Err(JsValue::from_str("names 与 files 数量不一致"));
+    }
+    let files = names.into_iter().zip(files.iter().map(js_sys::Uint8Array::to_vec));
+    let folder = crate::slots::ingest_saves(files, &ParseOptions::from_js(password));
+
+    to_js_value(&folder)
+}
+
+/// 同一角色的多个存档（从早到晚）生成进度时间线
+#[wasm_bindgen(unchecked_return_type = "Timeline")]
+pub fn progress_timeline(files: Vec<js_sys::Uint8Array>, password: Option<String>) -> Result<JsValue, JsValue> {
+    let saves: Vec<Vec<u8>> = files.iter().map(js_sys::Uint8Array::to_vec).collect();
+    let timeline = crate::timeline::Timeline::from_saves(&saves, &ParseOptions::from_js(password), &Default::default());
+
+    to_js_value(&timeline)
+}
+
+/// 列出存档的所有顶层键及其 `__type`、大小和是否已被解析，用于发现新版本增加的数据
+#[wasm_bindgen(unchecked_return_type = "KeyReport")]
+pub fn report_keys(data: &[u8], password: Option<String>) -> Result<JsValue, JsValue> {
+    let text = decode_save_text(data, &ParseOptions::from_js(password)).map_err(|e| e.to_js())?;
+    let save_data = SaveData::from_es3(text).map_err(|e| e.to_js())?;
+
+    to_js_value(&crate::keys::key_report(&save_data))
+}
+
+/// 将配装（process_file 输出中的 loadout）编码为可放进 URL 的分享码
+#[wasm_bindgen]
+pub fn encode_build(#[wasm_bindgen(unchecked_param_type = "Loadout")] loadout: JsValue) -> Result<String, JsValue> {
+    let loadout: Loadout = serde_wasm_bindgen::from_value(loadout)
+        .map_err(|e| SaveParseError::section("loadout", e).to_js())?;
+    Ok(loadout.to_build_code())
+}
+
+/// 解析分享码，不需要原存档
+#[wasm_bindgen(unchecked_return_type = "Loadout")]
+pub fn decode_build(code: &str) -> Result<JsValue, JsValue> {
+    let loadout = Loadout::from_build_code(code).map_err(|e| e.to_js())?;
+    to_js_value(&loadout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = include_str!("../fixtures/sample.sav");
+
+    #[test]
+    fn test_parse_sample() {
+        let archive = parse_archive(SAMPLE.as_bytes()).unwrap();
+
+        assert!(archive.warnings.is_empty(), "{:?}", archive.warnings);
+        assert_eq!(archive.economy_data.money, 12850);
+        assert_eq!(archive.exp, 5320);
+        assert!(!archive.kills.is_empty());
+
+        let weapon = &archive.character_item_data.entries[1];
+        assert_eq!(weapon.decoded["Durability"].as_f32(), Some(87.5));
+        assert_eq!(archive.player_storage.entries[0].item_tree_data.entries[0].decoded["Count"].as_i32(), Some(30));
+
+        let character = archive.item_trees.character_item_data.root.as_ref().unwrap();
+        assert_eq!(character.slots["PrimaryWeapon"].slots["Scope"].type_id, 570);
+        assert_eq!(character.slots["Backpack"].inventory[&2].type_id, 15);
+        assert_eq!(archive.item_trees.player_storage[&3].root.as_ref().unwrap().type_id, 381);
+        assert_eq!(archive.item_trees.death_list.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_sample_extras() {
+        let archive = parse_archive(SAMPLE.as_bytes()).unwrap();
+
+        assert_eq!(archive.visited_scenes, ["Level_GroundZero_Main"]);
+        assert_eq!(archive.construction_sites.get("Workbench"), Some(&true));
+        assert_eq!(archive.construction_sites.get("MedicalStation"), Some(&false));
+
+        let face = archive.custom_face.as_ref().unwrap();
+        assert!(face.saved_setting);
+        assert_eq!(face.hair_id, 3);
+
+        let stock = &archive.merchant_stock["Merchant_Normal"];
+        assert_eq!(stock.stock_counts.len(), 2);
+        assert_eq!(stock.stock_counts[0].item_type_id, 254);
+        assert_eq!(stock.stock_counts[0].stock, 40);
+    }
+
+    #[test]
+    fn test_quest_progress() {
+        let archive = parse_archive(SAMPLE.as_bytes()).unwrap();
+
+        let active = &archive.quests.active_quests[0];
+        assert_eq!(active.task_status[1].count, Some(3));
+        assert!(!active.reward_status[0].claimed);
+
+        let ids: Vec<u32> = archive.quest_progress.iter().map(|p| p.id).collect();
+        assert_eq!(ids, [900, 901, 902]);
+        let progress = &archive.quest_progress[2];
+        assert!(progress.active && !progress.complete);
+        assert_eq!((progress.tasks_finished, progress.tasks_total), (1, 2));
+        assert_eq!((progress.rewards_claimed, progress.rewards_total), (0, 1));
+        assert_eq!(archive.quest_progress[0].rewards_claimed, 1);
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let err = parse_archive(&[b'{', 0xff, 0xfe, b'}']).unwrap_err();
+        assert_eq!(err, SaveParseError::InvalidUtf8 { valid_up_to: 1 });
+    }
+
+    #[test]
+    fn test_json_syntax_position() {
+        let err = parse_archive(b"{\n  \"SaveTime\": {\n    \"__type\": \"int\",,\n  }\n}").unwrap_err();
+        match err {
+            SaveParseError::JsonSyntax { line, .. } => assert_eq!(line, 3),
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_deeply_nested_unknown_key() {
+        let nested = format!("{{\n\t\"Unknown\" : {}", "[".repeat(200_000));
+        let broken = SAMPLE.replacen('{', &nested, 1);
+        match parse_archive(broken.as_bytes()).unwrap_err() {
+            SaveParseError::JsonSyntax { line, message, .. } => {
+                assert_eq!(line, 2);
+                assert!(message.contains("recursion limit"), "{}", message);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_missing_required_key() {
+        let err = parse_archive(br#"{"SaveTime":{"__type":"int"100}}"#).unwrap_err();
+        assert_eq!(err, SaveParseError::MissingKey { key: "CreatedWithVersion".to_string() });
+    }
+
+    #[test]
+    fn test_progress_stages() {
+        let mut stages = Vec::new();
+        parse_archive_with_progress(SAMPLE.as_bytes(), &ParseOptions::default(), &mut |progress| {
+            stages.push(progress);
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(stages[..2], [ParseProgress::Decode, ParseProgress::FixFormat]);
+        assert_eq!(stages.last(), Some(&ParseProgress::Items));
+        let sections: Vec<(usize, usize)> = stages
+            .iter()
+            .filter_map(|stage| match stage {
+                ParseProgress::Section { done, total, .. } => Some((*done, *total)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(sections.len(), SECTION_COUNT);
+        assert_eq!(sections.last(), Some(&(SECTION_COUNT, SECTION_COUNT)));
+    }
+
+    #[test]
+    fn test_progress_cancel() {
+        let mut reported = 0;
+        let err = parse_archive_with_progress(SAMPLE.as_bytes(), &ParseOptions::default(), &mut |progress| {
+            reported += 1;
+            match progress {
+                ParseProgress::Section { done: 3, .. } => Err(SaveParseError::Cancelled),
+                _ => Ok(()),
+            }
+        })
+        .unwrap_err();
+
+        assert_eq!(err, SaveParseError::Cancelled);
+        assert_eq!(reported, 5);
+    }
+
+    #[test]
+    fn test_section_type_mismatch_becomes_warning() {
+        let broken = SAMPLE.replacen(r#""money" : 12850"#, r#""money" : "lots""#, 1);
+        let archive = parse_archive(broken.as_bytes()).unwrap();
+
+        assert_eq!(archive.economy_data.money, 0);
+        assert!(matches!(
+            archive.warnings.as_slice(),
+            [SaveParseError::SectionType { key, .. }] if key == "EconomyData"
+        ));
+    }
+
+    #[test]
+    fn test_prefixed_section_mismatch_becomes_warning() {
+        let broken = SAMPLE.replacen(r#""__type" : "int"4"#, r#""__type" : "string""four""#, 1);
+        let archive = parse_archive(broken.as_bytes()).unwrap();
+
+        let names: Vec<&str> = archive.kills.iter().map(|k| k.monster_name.as_str()).collect();
+        assert_eq!(names, ["Cname_Scav"]);
+        assert!(matches!(
+            archive.warnings.as_slice(),
+            [SaveParseError::SectionType { key, .. }] if key == "Count/Kills/Cname_Prison"
+        ));
+    }
+}
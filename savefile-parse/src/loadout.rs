@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use crate::archived::Archive;
+use crate::error::SaveParseError;
+use crate::tree::ResolvedItem;
+
+/// 分享码格式版本，编码方式变化时递增
+pub const BUILD_CODE_VERSION: u8 = 1;
+
+// 解压后的分享码内容上限，完整配装的 JSON 远小于此；防止小段输入解压出巨大数据
+const MAX_BUILD_CODE_BYTES: u64 = 64 * 1024;
+
+// 角色身上的槽位名称，与游戏一致（头盔槽位的拼写为 Helmat）
+const PRIMARY_WEAPON: &str = "PrimaryWeapon";
+const SECONDARY_WEAPON: &str = "SecondaryWeapon";
+const MELEE_WEAPON: &str = "MeleeWeapon";
+const HELMET: &str = "Helmat";
+const ARMOR: &str = "Armor";
+const FACE_MASK: &str = "FaceMask";
+const BACKPACK: &str = "Backpack";
+const HEADSET: &str = "Headset";
+const TOTEM_PREFIX: &str = "Totem";
+
+/// 配装中的一件物品，`slots` 为按槽位名称挂载的配件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadoutItem {
+    #[serde(rename = "typeID")]
+    pub type_id: i32,
+    pub slots: BTreeMap<String, LoadoutItem>,
+}
+
+impl From<&ResolvedItem> for LoadoutItem {
+    fn from(item: &ResolvedItem) -> Self {
+        LoadoutItem {
+            type_id: item.type_id,
+            slots: item.slots.iter().map(|(slot, child)| (slot.clone(), child.into())).collect(),
+        }
+    }
+}
+
+/// 角色装备：武器及其配件、护甲、头盔、背包与图腾，不包含背包里的物品
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct Loadout {
+    pub primary_weapon: Option<LoadoutItem>,
+    pub secondary_weapon: Option<LoadoutItem>,
+    pub melee_weapon: Option<LoadoutItem>,
+    pub helmet: Option<LoadoutItem>,
+    pub armor: Option<LoadoutItem>,
+    pub face_mask: Option<LoadoutItem>,
+    pub backpack: Option<LoadoutItem>,
+    pub headset: Option<LoadoutItem>,
+    /// 按槽位顺序排列（Totem1、Totem2 …）
+    pub totems: Vec<LoadoutItem>,
+    /// 未识别的槽位，原样保留
+    pub other: BTreeMap<String, LoadoutItem>,
+}
+
+// 分享码中的物品：[typeID, {槽位: 物品}]
+#[derive(Serialize, Deserialize)]
+struct CodeItem(i32, #[serde(default, skip_serializing_if = "BTreeMap::is_empty")] BTreeMap<String, CodeItem>);
+
+impl From<&LoadoutItem> for CodeItem {
+    fn from(item: &LoadoutItem) -> Self {
+        CodeItem(item.type_id, item.slots.iter().map(|(slot, child)| (slot.clone(), child.into())).collect())
+    }
+}
+
+impl From<CodeItem> for LoadoutItem {
+    fn from(CodeItem(type_id, slots): CodeItem) -> Self {
+        LoadoutItem {
+            type_id,
+            slots: slots.into_iter().map(|(slot, child)| (slot, child.into())).collect(),
+        }
+    }
+}
+
+fn build_code_error(err: impl std::fmt::Display) -> SaveParseError {
+    SaveParseError::BuildCode { message: err.to_string() }
+}
+
+impl Loadout {
+    /// 由角色根物品各槽位上的物品组成配装
+    pub fn from_slots<I: IntoIterator<Item = (String, LoadoutItem)>>(slots: I) -> Self {
+        let mut loadout = Loadout::default();
+        let mut totems = BTreeMap::new();
+        for (slot, item) in slots {
+            let target = match slot.as_str() {
+                PRIMARY_WEAPON => &mut loadout.primary_weapon,
+                SECONDARY_WEAPON => &mut loadout.secondary_weapon,
+                MELEE_WEAPON => &mut loadout.melee_weapon,
+                HELMET => &mut loadout.helmet,
+                ARMOR => &mut loadout.armor,
+                FACE_MASK => &mut loadout.face_mask,
+                BACKPACK => &mut loadout.backpack,
+                HEADSET => &mut loadout.headset,
+                _ => {
+                    match slot.strip_prefix(TOTEM_PREFIX).and_then(|index| index.parse::<u32>().ok()) {
+                        Some(index) => totems.insert(index, item),
+                        None => loadout.other.insert(slot, item),
+                    };
+                    continue;
+                }
+            };
+            *target = Some(item);
+        }
+        loadout.totems = totems.into_values().collect();
+        loadout
+    }
+
+    /// 按槽位名称列出所有物品，与 from_slots 互逆
+    pub fn slots(&self) -> Vec<(String, &LoadoutItem)> {
+        let named = [
+            (PRIMARY_WEAPON, &self.primary_weapon),
+            (SECONDARY_WEAPON, &self.secondary_weapon),
+            (MELEE_WEAPON, &self.melee_weapon),
+            (HELMET, &self.helmet),
+            (ARMOR, &self.armor),
+            (FACE_MASK, &self.face_mask),
+            (BACKPACK, &self.backpack),
+            (HEADSET, &self.headset),
+        ];
+        let mut slots: Vec<(String, &LoadoutItem)> = named
+            .into_iter()
+            .filter_map(|(slot, item)| Some((slot.to_string(), item.as_ref()?)))
+            .collect();
+        slots.extend(self.totems.iter().enumerate().map(|(index, item)| (format!("{}{}", TOTEM_PREFIX, index + 1), item)));
+        slots.extend(self.other.iter().map(|(slot, item)| (slot.clone(), item)));
+        slots
+    }
+
+    /// 编码为可放进 URL 的分享码：版本号 + deflate 压缩的紧凑 JSON，再做 URL 安全的 base64
+    pub fn to_build_code(&self) -> String {
+        let items: BTreeMap<String, CodeItem> = self.slots().into_iter().map(|(slot, item)| (slot, item.into())).collect();
+        let json = serde_json::to_vec(&items).expect("分享码内容总能序列化");
+
+        let mut encoder = DeflateEncoder::new(vec![BUILD_CODE_VERSION], Compression::best());
+        encoder.write_all(&json).expect("写入内存不会失败");
+        URL_SAFE_NO_PAD.encode(encoder.finish().expect("写入内存不会失败"))
+    }
+
+    /// 解析 to_build_code 生成的分享码
+    pub fn from_build_code(code: &str) -> Result<Self, SaveParseError> {
+        let bytes = URL_SAFE_NO_PAD.decode(code.trim()).map_err(build_code_error)?;
+        let (&version, compressed) = bytes.split_first().ok_or_else(|| build_code_error("分享码为空"))?;
+        if version != BUILD_CODE_VERSION {
+            return Err(build_code_error(format!("不支持的分享码版本 {}", version)));
+        }
+
+        let mut json = Vec::new();
+        DeflateDecoder::new(compressed)
+            .take(MAX_BUILD_CODE_BYTES + 1)
+            .read_to_end(&mut json)
+            .map_err(build_code_error)?;
+        if json.len() as u64 > MAX_BUILD_CODE_BYTES {
+            return Err(build_code_error("分享码内容过大"));
+        }
+        let items: BTreeMap<String, CodeItem> = serde_json::from_slice(&json).map_err(build_code_error)?;
+        Ok(Loadout::from_slots(items.into_iter().map(|(slot, item)| (slot, item.into()))))
+    }
+}
+
+impl Archive {
+    /// 从已还原的角色物品树中提取配装，需在 link_item_trees 之后调用
+    pub fn extract_loadout(&mut self) {
+        self.loadout = match &self.item_trees.character_item_data.root {
+            Some(root) => Loadout::from_slots(root.slots.iter().map(|(slot, item)| (slot.clone(), item.into()))),
+            None => Loadout::default(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_archive;
+
+    const SAMPLE: &str = include_str!("../fixtures/sample.sav");
+
+    #[test]
+    fn test_sample_loadout() {
+        let archive = parse_archive(SAMPLE.as_bytes()).unwrap();
+        let loadout = &archive.loadout;
+
+        let primary = loadout.primary_weapon.as_ref().unwrap();
+        assert_eq!(primary.type_id, 238);
+        assert_eq!(primary.slots["Scope"].type_id, 570);
+        assert_eq!(loadout.armor.as_ref().unwrap().type_id, 46);
+        assert_eq!(loadout.backpack.as_ref().unwrap().type_id, 39);
+        // 背包里的物品不属于配装
+        assert!(loadout.backpack.as_ref().unwrap().slots.is_empty());
+        assert!(loadout.helmet.is_none() && loadout.totems.is_empty());
+    }
+
+    #[test]
+    fn test_build_code_round_trip() {
+        let mut loadout = parse_archive(SAMPLE.as_bytes()).unwrap().loadout;
+        loadout.totems = vec![
+            LoadoutItem { type_id: 1001, slots: BTreeMap::new() },
+            LoadoutItem { type_id: 1002, slots: BTreeMap::new() },
+        ];
+        loadout.other.insert("Pet".to_string(), LoadoutItem { type_id: 7, slots: BTreeMap::new() });
+
+        let code = loadout.to_build_code();
+        assert!(code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(Loadout::from_build_code(&code).unwrap(), loadout);
+    }
+
+    #[test]
+    fn test_invalid_build_code() {
+        assert!(matches!(Loadout::from_build_code("not a code!"), Err(SaveParseError::BuildCode { .. })));
+        assert!(matches!(Loadout::from_build_code(""), Err(SaveParseError::BuildCode { .. })));
+
+        let future = URL_SAFE_NO_PAD.encode([BUILD_CODE_VERSION + 1, 0]);
+        assert!(matches!(Loadout::from_build_code(&future), Err(SaveParseError::BuildCode { .. })));
+    }
+
+    #[test]
+    fn test_oversized_build_code() {
+        // 全是空格的 JSON 压缩后只有几百字节，解压后超过上限
+        let mut encoder = DeflateEncoder::new(vec![BUILD_CODE_VERSION], Compression::best());
+        encoder.write_all(&vec![b' '; MAX_BUILD_CODE_BYTES as usize]).unwrap();
+        encoder.write_all(b"{}").unwrap();
+        let code = URL_SAFE_NO_PAD.encode(encoder.finish().unwrap());
+
+        assert!(code.len() < 1024);
+        match Loadout::from_build_code(&code) {
+            Err(SaveParseError::BuildCode { message }) => assert!(message.contains("过大"), "{}", message),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use crate::error::SaveParseError;
+
+// 解析进度：parse_archive_with_progress 在每个阶段开始前调用 ProgressSink，
+// sink 返回错误（通常是 Cancelled）时解析立即停止。
+
+/// 解析阶段，带有 `stage` 标签
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum ParseProgress {
+    /// 解密、解压
+    Decode,
+    /// 修复 ES3 格式并索引顶层段落
+    FixFormat,
+    /// 提取第 `done` 个段落（从 1 开始），共 `total` 个
+    Section { name: String, done: usize, total: usize },
+    /// 解码物品变量、构建物品树
+    Items,
+}
+
+/// 接收解析进度
+pub trait ProgressSink {
+    fn report(&mut self, progress: ParseProgress) -> Result<(), SaveParseError>;
+}
+
+/// 不关心进度
+impl ProgressSink for () {
+    fn report(&mut self, _: ParseProgress) -> Result<(), SaveParseError> {
+        Ok(())
+    }
+}
+
+impl<F: FnMut(ParseProgress) -> Result<(), SaveParseError>> ProgressSink for F {
+    fn report(&mut self, progress: ParseProgress) -> Result<(), SaveParseError> {
+        self(progress)
+    }
+}
+
+/// 按顺序报告段落提取进度
+pub(crate) struct Sections<'a, P: ProgressSink + ?Sized> {
+    sink: &'a mut P,
+    done: usize,
+    total: usize,
+}
+
+impl<'a, P: ProgressSink + ?Sized> Sections<'a, P> {
+    pub(crate) fn new(sink: &'a mut P, total: usize) -> Self {
+        Sections { sink, done: 0, total }
+    }
+
+    pub(crate) fn next(&mut self, name: &str) -> Result<(), SaveParseError> {
+        self.done += 1;
+        self.sink.report(ParseProgress::Section {
+            name: name.to_string(),
+            done: self.done,
+            total: self.total,
+        })
+    }
+}
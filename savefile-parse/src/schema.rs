@@ -0,0 +1,115 @@
+use std::fmt;
+use crate::error::SaveParseError;
+use crate::extractor::{SaveData, VersionData};
+
+// 不同游戏版本的存档布局不同，解析前先按 CreatedWithVersion 选择适配器，
+// 把存档统一改写成当前 Archive 所对应的布局。
+
+/// 不含后缀的游戏版本号
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GameVersion(pub i32, pub i32, pub i32);
+
+impl From<&VersionData> for GameVersion {
+    fn from(version: &VersionData) -> Self {
+        GameVersion(version.main_version, version.sub_version, version.build_version)
+    }
+}
+
+impl fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+type Step = fn(&mut SaveData);
+
+/// 某一版本区间的存档适配器
+pub struct SchemaAdapter {
+    pub name: &'static str,
+    /// 适用的版本区间，左闭右开
+    pub since: GameVersion,
+    pub until: GameVersion,
+    steps: &'static [Step],
+}
+
+impl SchemaAdapter {
+    pub fn supports(&self, version: GameVersion) -> bool {
+        self.since <= version && version < self.until
+    }
+
+    pub fn normalize(&self, save_data: &mut SaveData) {
+        for step in self.steps {
+            step(save_data);
+        }
+    }
+}
+
+/// 已支持的版本，按版本从旧到新排列，最后一项为当前布局。
+/// 旧版本的适配器需要有对应版本的真实存档作为测试样本后再加入。
+pub const ADAPTERS: &[SchemaAdapter] = &[SchemaAdapter {
+    name: "1.0",
+    since: GameVersion(1, 0, 0),
+    until: GameVersion(1, 1, 0),
+    steps: &[],
+}];
+
+/// 当前布局对应的适配器
+pub fn latest() -> &'static SchemaAdapter {
+    ADAPTERS.last().expect("至少有一个适配器")
+}
+
+/// 按版本选择适配器，未知版本按当前布局解析并返回警告
+pub fn select(version: Option<GameVersion>) -> (&'static SchemaAdapter, Option<SaveParseError>) {
+    let Some(version) = version else {
+        return (latest(), None);
+    };
+    match ADAPTERS.iter().find(|adapter| adapter.supports(version)) {
+        Some(adapter) => (adapter, None),
+        None => (latest(), Some(SaveParseError::UnknownVersion { version: version.to_string() })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_archive;
+
+    const SAMPLE: &str = include_str!("../fixtures/sample.sav");
+
+    #[test]
+    fn test_select() {
+        assert_eq!(select(Some(GameVersion(1, 0, 30))).0.name, "1.0");
+        assert_eq!(select(None).0.name, "1.0");
+
+        let (adapter, warning) = select(Some(GameVersion(1, 2, 0)));
+        assert_eq!(adapter.name, "1.0");
+        assert_eq!(warning, Some(SaveParseError::UnknownVersion { version: "1.2.0".to_string() }));
+    }
+
+    #[test]
+    fn test_current_fixture() {
+        let archive = parse_archive(SAMPLE.as_bytes()).unwrap();
+        assert!(archive.warnings.is_empty(), "{:?}", archive.warnings);
+        assert_eq!(archive.economy_data.money, 12850);
+    }
+
+    #[test]
+    fn test_unknown_version_warns() {
+        let future = SAMPLE.replacen(r#""subVersion" : 0"#, r#""subVersion" : 3"#, 1);
+        let archive = parse_archive(future.as_bytes()).unwrap();
+
+        assert_eq!(archive.economy_data.money, 12850);
+        assert!(matches!(
+            archive.warnings.as_slice(),
+            [SaveParseError::UnknownVersion { version }] if version == "1.3.30"
+        ));
+
+        // 旧版本同样没有适配器，按当前布局解析并给出警告
+        let legacy = SAMPLE.replacen(r#""mainVersion" : 1"#, r#""mainVersion" : 0"#, 1);
+        let archive = parse_archive(legacy.as_bytes()).unwrap();
+        assert!(matches!(
+            archive.warnings.as_slice(),
+            [SaveParseError::UnknownVersion { version }] if version == "0.0.30"
+        ));
+    }
+}
@@ -0,0 +1,255 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use crate::archived::Archive;
+use crate::error::SaveParseError;
+use crate::{ParseOptions, parse_archive_with};
+
+// 存档目录中每个存档位有一个主文件，以及若干备份。按文件名分组：
+//   Save_1.sav                 主文件，存档位 Save_1
+//   Save_1.sav.bak、Save_1.sav.bak2、Save_1_bak3.sav、Save_1_backup.sav   备份
+//   Backups/Save_1.sav         位于 backup 目录下的同名文件也视为备份
+// 扩展名不是 .sav、.sav.bak 或 .sav.bakN 的文件会被忽略。
+
+const SAVE_EXTENSION: &str = ".sav";
+const BACKUP_EXTENSION: &str = ".bak";
+const BACKUP_MARKERS: &[&str] = &["_backup", "_bak"];
+
+// 文件名、是否为备份、文件内容
+type SlotFile<'a> = (String, bool, &'a [u8]);
+
+/// 从文件路径得到存档位名称以及是否为备份，不是存档文件时返回 None
+fn classify(path: &str) -> Option<(String, bool)> {
+    let mut components: Vec<&str> = path.split(['/', '\\']).filter(|c| !c.is_empty()).collect();
+    let file_name = components.pop()?;
+    let in_backup_dir = components.iter().any(|dir| dir.to_ascii_lowercase().starts_with("backup"));
+
+    let lower = file_name.to_ascii_lowercase();
+    // .sav 之后带 .bak 或 .bakN 后缀的是备份
+    let mut name = lower.as_str();
+    let mut backup = in_backup_dir;
+    if let Some(index) = name.rfind(BACKUP_EXTENSION)
+        && name[index + BACKUP_EXTENSION.len()..].bytes().all(|b| b.is_ascii_digit())
+    {
+        name = &name[..index];
+        backup = true;
+    }
+    let extension = name.strip_suffix(SAVE_EXTENSION)?.len();
+    let mut stem = &file_name[..extension];
+
+    let stem_lower = stem.to_ascii_lowercase();
+    for marker in BACKUP_MARKERS {
+        if let Some(index) = stem_lower.rfind(marker)
+            && stem_lower[index + marker.len()..].bytes().all(|b| b.is_ascii_digit())
+        {
+            stem = &stem[..index];
+            backup = true;
+            break;
+        }
+    }
+
+    (!stem.is_empty()).then(|| (stem.to_string(), backup))
+}
+
+/// 存档位中一个文件的解析结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveCandidate {
+    /// 传入时的文件名（可能带有相对路径）
+    pub name: String,
+    pub backup: bool,
+    #[ts(optional)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub save_time: Option<i32>,
+    /// 解析失败的原因
+    #[ts(optional)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<SaveParseError>,
+}
+
+/// 选中版本的概要
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotSummary {
+    pub version: String,
+    pub exp: i32,
+    pub money: i32,
+    pub days: i32,
+    pub save_time: i32,
+    /// 解析时产生的警告数量
+    pub warnings: usize,
+}
+
+impl From<&Archive> for SlotSummary {
+    fn from(archive: &Archive) -> Self {
+        SlotSummary {
+            version: archive.version.clone(),
+            exp: archive.exp,
+            money: archive.economy_data.money,
+            days: archive.game_lock.days,
+            save_time: archive.save_time,
+            warnings: archive.warnings.len(),
+        }
+    }
+}
+
+/// 一个存档位及其所有备份
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveSlot {
+    pub slot: String,
+    /// 可用的文件按 SaveTime 从新到旧排列，主文件在时间相同时优先，解析失败的排在最后
+    pub files: Vec<SaveCandidate>,
+    /// 选中的文件名，所有文件都无法解析时为 None
+    pub selected: Option<String>,
+    pub summary: Option<SlotSummary>,
+    /// 主文件缺失或解析失败而改用备份时的原因
+    #[ts(optional)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_error: Option<SaveParseError>,
+    /// 选中版本的解析结果，仅在 Rust 中使用
+    #[serde(skip)]
+    #[ts(skip)]
+    pub archive: Option<Archive>,
+}
+
+/// 整个存档目录的解析结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveFolder {
+    /// 按存档位名称排序
+    pub slots: Vec<SaveSlot>,
+    /// 不是存档文件而被忽略的文件名
+    pub ignored: Vec<String>,
+}
+
+impl SaveSlot {
+    fn from_files(slot: String, files: Vec<SlotFile>, options: &ParseOptions) -> Self {
+        let mut parsed: Vec<(SaveCandidate, Option<Archive>)> = files
+            .into_iter()
+            .map(|(name, backup, data)| {
+                let result = parse_archive_with(data, options);
+                let candidate = SaveCandidate {
+                    name,
+                    backup,
+                    save_time: result.as_ref().ok().map(|archive| archive.save_time),
+                    error: result.as_ref().err().cloned(),
+                };
+                (candidate, result.ok())
+            })
+            .collect();
+        parsed.sort_by_key(|(candidate, _)| {
+            (candidate.error.is_some(), Reverse(candidate.save_time), candidate.backup, candidate.name.clone())
+        });
+
+        // 主文件可用但备份更新时不算回退
+        let primary_error = match parsed.iter().find(|(candidate, _)| !candidate.backup) {
+            Some((primary, _)) => primary.error.clone(),
+            None => Some(SaveParseError::MissingFile { name: format!("{}{}", slot, SAVE_EXTENSION) }),
+        };
+        let (selected, archive) = match parsed.first_mut() {
+            Some((candidate, archive)) if candidate.error.is_none() => (Some(candidate.name.clone()), archive.take()),
+            _ => (None, None),
+        };
+
+        SaveSlot {
+            slot,
+            files: parsed.into_iter().map(|(candidate, _)| candidate).collect(),
+            selected,
+            summary: archive.as_ref().map(SlotSummary::from),
+            primary_error,
+            archive,
+        }
+    }
+}
+
+/// 解析一组文件（整个存档目录），按存档位分组并为每个存档位选出最新的可用版本
+pub fn ingest_saves<N, D>(files: impl IntoIterator<Item = (N, D)>, options: &ParseOptions) -> SaveFolder
+where
+    N: Into<String>,
+    D: AsRef<[u8]>,
+{
+    let files: Vec<(String, D)> = files.into_iter().map(|(name, data)| (name.into(), data)).collect();
+
+    let mut folder = SaveFolder::default();
+    let mut groups: BTreeMap<String, Vec<SlotFile>> = BTreeMap::new();
+    for (name, data) in &files {
+        match classify(name) {
+            Some((slot, backup)) => groups.entry(slot).or_default().push((name.clone(), backup, data.as_ref())),
+            None => folder.ignored.push(name.clone()),
+        }
+    }
+
+    folder.slots = groups
+        .into_iter()
+        .map(|(slot, files)| SaveSlot::from_files(slot, files, options))
+        .collect();
+    folder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = include_str!("../fixtures/sample.sav");
+
+    fn with_save_time(save_time: i32) -> String {
+        SAMPLE.replacen("1760512345", &save_time.to_string(), 1)
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("Save_1.sav"), Some(("Save_1".to_string(), false)));
+        assert_eq!(classify("Saves/Save_1.sav.bak2"), Some(("Save_1".to_string(), true)));
+        assert_eq!(classify("Save_1_bak3.sav"), Some(("Save_1".to_string(), true)));
+        assert_eq!(classify("Saves\\Backups\\Save_2.sav"), Some(("Save_2".to_string(), true)));
+        assert_eq!(classify("Saves/Options.json"), None);
+
+        assert_eq!(classify("Save_1.SAV.BAK"), Some(("Save_1".to_string(), true)));
+        assert_eq!(classify("Save_1.save"), None);
+        assert_eq!(classify("Save_1.savedata"), None);
+        assert_eq!(classify("Save_1.sav.tmp"), None);
+        assert_eq!(classify("Save_1.sav.bakx"), None);
+        assert_eq!(classify("Save_1.bak"), None);
+    }
+
+    #[test]
+    fn test_corrupt_primary_falls_back() {
+        let older = with_save_time(1760000000);
+        let newer = with_save_time(1760500000);
+        let files: Vec<(&str, &[u8])> = vec![
+            ("Saves/Save_1.sav", b"{ truncated"),
+            ("Saves/Save_1.sav.bak1", older.as_bytes()),
+            ("Saves/Save_1.sav.bak2", newer.as_bytes()),
+            ("Saves/Save_2.sav", SAMPLE.as_bytes()),
+            ("Saves/Save_2.sav.bak", older.as_bytes()),
+            ("Saves/desktop.ini", b""),
+        ];
+        let folder = ingest_saves(files, &ParseOptions::default());
+
+        assert_eq!(folder.ignored, ["Saves/desktop.ini"]);
+        let [first, second] = folder.slots.as_slice() else {
+            panic!("应有两个存档位: {:?}", folder.slots);
+        };
+
+        assert_eq!(first.slot, "Save_1");
+        assert_eq!(first.selected.as_deref(), Some("Saves/Save_1.sav.bak2"));
+        assert!(matches!(first.primary_error, Some(SaveParseError::JsonSyntax { .. })));
+        let names: Vec<&str> = first.files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(names, ["Saves/Save_1.sav.bak2", "Saves/Save_1.sav.bak1", "Saves/Save_1.sav"]);
+        assert_eq!(first.summary.as_ref().unwrap().save_time, 1760500000);
+
+        assert_eq!(second.selected.as_deref(), Some("Saves/Save_2.sav"));
+        assert_eq!(second.primary_error, None);
+        assert_eq!(second.archive.as_ref().unwrap().economy_data.money, 12850);
+    }
+
+    #[test]
+    fn test_only_backups() {
+        let folder = ingest_saves([("Save_3_bak.sav", SAMPLE.as_bytes())], &ParseOptions::default());
+        let slot = &folder.slots[0];
+        assert_eq!(slot.selected.as_deref(), Some("Save_3_bak.sav"));
+        assert!(matches!(slot.primary_error, Some(SaveParseError::MissingFile { .. })));
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::{BTreeMap, BTreeSet};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use crate::archived::Archive;
+use crate::error::SaveParseError;
+use crate::{ParseOptions, parse_archive_with};
+
+/// 同一角色多个存档按时间排列的进度，每个存档一个数据点，可直接用于绘制折线图
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelinePoint {
+    /// 在输入中的位置
+    pub index: usize,
+    pub save_time: i32,
+    /// GameClock 中的游戏天数
+    pub days: i32,
+    pub money: i32,
+    pub exp: i32,
+    /// 按敌人类型统计的累计击杀
+    #[ts(type = "Record<string, number>")]
+    pub kills: BTreeMap<String, i64>,
+    #[ts(type = "number")]
+    pub total_kills: i64,
+    pub quests_completed: usize,
+    pub unlocked_items: usize,
+}
+
+impl From<(usize, &Archive)> for TimelinePoint {
+    fn from((index, archive): (usize, &Archive)) -> Self {
+        let kills: BTreeMap<String, i64> = archive
+            .kills
+            .iter()
+            .map(|kill| (kill.monster_name.clone(), kill.count))
+            .collect();
+        TimelinePoint {
+            index,
+            save_time: archive.save_time,
+            days: archive.game_lock.days,
+            money: archive.economy_data.money,
+            exp: archive.exp,
+            total_kills: kills.values().sum(),
+            kills,
+            quests_completed: archive.quest_progress.iter().filter(|quest| quest.complete).count(),
+            unlocked_items: archive.economy_data.unlocked_items.len(),
+        }
+    }
+}
+
+/// 时间线中发现的问题，`index` 为输入中的位置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimelineIssue {
+    /// 存档无法解析，已跳过
+    Unreadable { index: usize, error: SaveParseError },
+    /// SaveTime 不晚于上一个存档，输入顺序可能有误
+    OutOfOrder { index: usize, previous: i32, save_time: i32 },
+    /// 只增不减的进度出现倒退，通常是回档到了更早的存档
+    Rollback { index: usize, metrics: Vec<String> },
+    /// 与上一个存档的间隔超过 gap_seconds
+    Gap {
+        index: usize,
+        #[ts(type = "number")]
+        seconds: i64,
+    },
+}
+
+/// 时间线选项
+#[derive(Debug, Clone)]
+pub struct TimelineOptions {
+    /// 相邻存档间隔超过该秒数时记为 Gap
+    pub gap_seconds: i64,
+}
+
+impl Default for TimelineOptions {
+    fn default() -> Self {
+        TimelineOptions { gap_seconds: 7 * 24 * 3600 }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct Timeline {
+    pub points: Vec<TimelinePoint>,
+    /// 出现过的所有敌人类型，用作击杀折线的图例
+    pub monsters: Vec<String>,
+    pub issues: Vec<TimelineIssue>,
+}
+
+// 正常游玩时不会减少的指标
+fn regressed_metrics(before: &TimelinePoint, after: &TimelinePoint) -> Vec<String> {
+    let mut metrics: Vec<String> = [
+        ("exp", before.exp > after.exp),
+        ("days", before.days > after.days),
+        ("questsCompleted", before.quests_completed > after.quests_completed),
+        ("unlockedItems", before.unlocked_items > after.unlocked_items),
+    ]
+    .into_iter()
+    .filter(|(_, regressed)| *regressed)
+    .map(|(metric, _)| metric.to_string())
+    .collect();
+    metrics.extend(
+        before
+            .kills
+            .iter()
+            .filter(|(monster, count)| after.kills.get(*monster).copied().unwrap_or(0) < **count)
+            .map(|(monster, _)| format!("kills.{}", monster)),
+    );
+    metrics
+}
+
+impl Timeline {
+    /// 按输入顺序生成时间线，输入应为同一角色从早到晚的存档
+    pub fn from_archives<'a>(archives: impl IntoIterator<Item = &'a Archive>, options: &TimelineOptions) -> Self {
+        let mut timeline = Timeline::default();
+        for point in archives.into_iter().enumerate().map(TimelinePoint::from) {
+            timeline.push(point, options);
+        }
+        timeline.finish()
+    }
+
+    /// 解析并生成时间线，无法解析的存档记为 Unreadable 并跳过
+    pub fn from_saves<D: AsRef<[u8]>>(saves: &[D], parse_options: &ParseOptions, options: &TimelineOptions) -> Self {
+        let mut timeline = Timeline::default();
+        for (index, data) in saves.iter().enumerate() {
+            match parse_archive_with(data.as_ref(), parse_options) {
+                Ok(archive) => timeline.push((index, &archive).into(), options),
+                Err(error) => timeline.issues.push(TimelineIssue::Unreadable { index, error }),
+            }
+        }
+        timeline.finish()
+    }
+
+    fn push(&mut self, point: TimelinePoint, options: &TimelineOptions) {
+        if let Some(previous) = self.points.last() {
+            let index = point.index;
+            if point.save_time <= previous.save_time {
+                self.issues.push(TimelineIssue::OutOfOrder {
+                    index,
+                    previous: previous.save_time,
+                    save_time: point.save_time,
+                });
+            } else {
+                let seconds = i64::from(point.save_time) - i64::from(previous.save_time);
+                if seconds > options.gap_seconds {
+                    self.issues.push(TimelineIssue::Gap { index, seconds });
+                }
+            }
+
+            let metrics = regressed_metrics(previous, &point);
+            if !metrics.is_empty() {
+                self.issues.push(TimelineIssue::Rollback { index, metrics });
+            }
+        }
+        self.points.push(point);
+    }
+
+    fn finish(mut self) -> Self {
+        let monsters: BTreeSet<&String> = self.points.iter().flat_map(|point| point.kills.keys()).collect();
+        self.monsters = monsters.into_iter().cloned().collect();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archived::Kill;
+    use crate::parse_archive;
+
+    const SAMPLE: &str = include_str!("../fixtures/sample.sav");
+
+    fn snapshot(save_time: i32, exp: i32, scav_kills: i64) -> Archive {
+        let mut archive = parse_archive(SAMPLE.as_bytes()).unwrap();
+        archive.save_time = save_time;
+        archive.exp = exp;
+        archive.kills.retain(|kill| kill.monster_name != "Cname_Scav");
+        archive.kills.push(Kill { monster_name: "Cname_Scav".to_string(), count: scav_kills });
+        archive
+    }
+
+    #[test]
+    fn test_progression() {
+        let archives = [snapshot(1000, 100, 1), snapshot(2000, 300, 5), snapshot(3000, 900, 9)];
+        let timeline = Timeline::from_archives(&archives, &TimelineOptions::default());
+
+        assert!(timeline.issues.is_empty(), "{:?}", timeline.issues);
+        let exp: Vec<i32> = timeline.points.iter().map(|point| point.exp).collect();
+        assert_eq!(exp, [100, 300, 900]);
+        assert_eq!(timeline.points[2].kills["Cname_Scav"], 9);
+        assert_eq!(timeline.points[2].total_kills, 13);
+        assert_eq!(timeline.monsters, ["Cname_Prison", "Cname_Scav"]);
+    }
+
+    #[test]
+    fn test_rollback_and_gap() {
+        let options = TimelineOptions { gap_seconds: 5000 };
+        let archives = [
+            snapshot(1000, 300, 5),
+            // 回档：经验与击杀都少于上一个存档
+            snapshot(2000, 100, 1),
+            snapshot(9000, 400, 6),
+            snapshot(9000, 400, 6),
+        ];
+        let timeline = Timeline::from_archives(&archives, &options);
+
+        assert_eq!(timeline.issues, [
+            TimelineIssue::Rollback { index: 1, metrics: vec!["exp".to_string(), "kills.Cname_Scav".to_string()] },
+            TimelineIssue::Gap { index: 2, seconds: 7000 },
+            TimelineIssue::OutOfOrder { index: 3, previous: 9000, save_time: 9000 },
+        ]);
+    }
+
+    #[test]
+    fn test_unreadable_save_skipped() {
+        let saves: [&[u8]; 2] = [b"not a save", SAMPLE.as_bytes()];
+        let timeline = Timeline::from_saves(&saves, &ParseOptions::default(), &TimelineOptions::default());
+
+        assert_eq!(timeline.points.len(), 1);
+        assert_eq!(timeline.points[0].index, 1);
+        assert!(matches!(timeline.issues.as_slice(), [TimelineIssue::Unreadable { index: 0, .. }]));
+    }
+}
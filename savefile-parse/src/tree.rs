@@ -0,0 +1,347 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use crate::archived::{Archive, InventoryItemData, ItemEntry, ItemTreeData, StashData};
+use crate::error::SaveParseError;
+use crate::variable::VariableValue;
+
+// 物品树的最大嵌套层数，游戏里实际只有几层；超出的部分不挂载，避免深链把栈撑爆
+const MAX_TREE_DEPTH: usize = 64;
+
+/// 解析后的物品节点：槽位按名字、容器按位置挂载子物品
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedItem {
+    #[serde(rename = "instanceID")]
+    pub instance_id: i32,
+    #[serde(rename = "typeID")]
+    pub type_id: i32,
+    pub variables: BTreeMap<String, VariableValue>,
+    pub slots: BTreeMap<String, ResolvedItem>,
+    pub inventory: BTreeMap<i32, ResolvedItem>,
+}
+
+impl ResolvedItem {
+    /// 深度优先遍历自身及所有子物品
+    pub fn walk(&self) -> Vec<&ResolvedItem> {
+        let mut items = Vec::new();
+        let mut stack = vec![self];
+        while let Some(item) = stack.pop() {
+            items.push(item);
+            // 逆序入栈，保证先访问槽位、再按位置访问容器
+            stack.extend(item.slots.values().chain(item.inventory.values()).rev());
+        }
+        items
+    }
+}
+
+/// 物品树中发现的结构问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TreeIssue {
+    /// rootInstanceID 在 entries 中不存在
+    MissingRoot { instance_id: i32 },
+    /// 同一棵树中 instanceID 重复，仅保留第一个
+    DuplicateInstance { instance_id: i32 },
+    /// 槽位或容器引用了不存在的条目
+    Dangling { parent: i32, instance_id: i32 },
+    /// 子物品引用了自身的祖先
+    Cycle { parent: i32, instance_id: i32 },
+    /// 同一个条目被多个父物品引用，仅挂载到第一个
+    SharedChild { parent: i32, instance_id: i32 },
+    /// 条目无法从根节点到达
+    Orphan { instance_id: i32 },
+    /// 嵌套层数超过上限，该子物品及其下的物品未挂载
+    TooDeep { parent: i32, instance_id: i32 },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedTree {
+    #[serde(rename = "rootInstanceID")]
+    pub root_instance_id: i32,
+    pub root: Option<ResolvedItem>,
+    pub issues: Vec<TreeIssue>,
+}
+
+struct Resolver<'a> {
+    entries: HashMap<i32, &'a ItemEntry>,
+    path: HashSet<i32>,
+    placed: HashSet<i32>,
+    issues: Vec<TreeIssue>,
+}
+
+impl<'a> Resolver<'a> {
+    fn build(&mut self, entry: &'a ItemEntry) -> ResolvedItem {
+        self.path.insert(entry.instance_id);
+        self.placed.insert(entry.instance_id);
+
+        let mut slots = BTreeMap::new();
+        for slot in &entry.slot_contents {
+            if let Some(child) = self.child(entry.instance_id, slot.instance_id) {
+                slots.insert(slot.slot.clone(), child);
+            }
+        }
+
+        let mut inventory = BTreeMap::new();
+        for item in &entry.inventory {
+            if let Some(child) = self.child(entry.instance_id, item.instance_id) {
+                inventory.insert(item.position, child);
+            }
+        }
+
+        self.path.remove(&entry.instance_id);
+
+        ResolvedItem {
+            instance_id: entry.instance_id,
+            type_id: entry.type_id,
+            variables: entry.decoded.clone(),
+            slots,
+            inventory,
+        }
+    }
+
+    fn child(&mut self, parent: i32, instance_id: i32) -> Option<ResolvedItem> {
+        let Some(entry) = self.entries.get(&instance_id).copied() else {
+            self.issues.push(TreeIssue::Dangling { parent, instance_id });
+            return None;
+        };
+        if self.path.contains(&instance_id) {
+            self.issues.push(TreeIssue::Cycle { parent, instance_id });
+            return None;
+        }
+        if self.placed.contains(&instance_id) {
+            self.issues.push(TreeIssue::SharedChild { parent, instance_id });
+            return None;
+        }
+        if self.path.len() >= MAX_TREE_DEPTH {
+            self.issues.push(TreeIssue::TooDeep { parent, instance_id });
+            self.mark_reachable(instance_id);
+            return None;
+        }
+        Some(self.build(entry))
+    }
+
+    // 未挂载的子树仍然可以从根到达，标记为已放置，不再重复报告为孤立条目
+    fn mark_reachable(&mut self, instance_id: i32) {
+        let mut stack = vec![instance_id];
+        while let Some(id) = stack.pop() {
+            let Some(entry) = self.entries.get(&id).copied() else {
+                continue;
+            };
+            if self.placed.insert(id) {
+                stack.extend(entry.slot_contents.iter().map(|slot| slot.instance_id));
+                stack.extend(entry.inventory.iter().map(|item| item.instance_id));
+            }
+        }
+    }
+}
+
+impl ItemTreeData {
+    /// 将扁平的 entries 按引用关系还原为嵌套树
+    pub fn resolve(&self) -> ResolvedTree {
+        let mut resolver = Resolver {
+            entries: HashMap::new(),
+            path: HashSet::new(),
+            placed: HashSet::new(),
+            issues: Vec::new(),
+        };
+
+        for entry in &self.entries {
+            if let Entry::Vacant(slot) = resolver.entries.entry(entry.instance_id) {
+                slot.insert(entry);
+            } else {
+                resolver.issues.push(TreeIssue::DuplicateInstance { instance_id: entry.instance_id });
+            }
+        }
+
+        let root = match resolver.entries.get(&self.root_instance_id).copied() {
+            Some(entry) => Some(resolver.build(entry)),
+            None => {
+                resolver.issues.push(TreeIssue::MissingRoot { instance_id: self.root_instance_id });
+                None
+            }
+        };
+
+        let mut seen = HashSet::new();
+        for entry in &self.entries {
+            if !resolver.placed.contains(&entry.instance_id) && seen.insert(entry.instance_id) {
+                resolver.issues.push(TreeIssue::Orphan { instance_id: entry.instance_id });
+            }
+        }
+
+        ResolvedTree {
+            root_instance_id: self.root_instance_id,
+            root,
+            issues: resolver.issues,
+        }
+    }
+}
+
+impl InventoryItemData {
+    pub fn resolve(&self) -> ResolvedTree {
+        self.item_tree_data.resolve()
+    }
+}
+
+impl StashData {
+    pub fn resolve(&self) -> ResolvedTree {
+        self.item_tree_data.resolve()
+    }
+}
+
+/// 存档中所有物品树的解析结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedItemTrees {
+    pub character_item_data: ResolvedTree,
+    /// 键为 inventoryPosition
+    pub player_storage: BTreeMap<i32, ResolvedTree>,
+    pub inventory_safe: BTreeMap<i32, ResolvedTree>,
+    /// 与 deathList 一一对应
+    pub death_list: Vec<ResolvedTree>,
+}
+
+impl ResolvedItemTrees {
+    pub fn iter(&self) -> impl Iterator<Item = &ResolvedTree> {
+        std::iter::once(&self.character_item_data)
+            .chain(self.player_storage.values())
+            .chain(self.inventory_safe.values())
+            .chain(self.death_list.iter())
+    }
+}
+
+impl Archive {
+    pub fn resolve_item_trees(&self) -> ResolvedItemTrees {
+        let by_position = |entries: &[InventoryItemData]| {
+            entries
+                .iter()
+                .map(|entry| (entry.inventory_position, entry.resolve()))
+                .collect()
+        };
+
+        ResolvedItemTrees {
+            character_item_data: self.character_item_data.resolve(),
+            player_storage: by_position(&self.player_storage.entries),
+            inventory_safe: by_position(&self.inventory_safe.entries),
+            death_list: self.death_list.iter().map(StashData::resolve).collect(),
+        }
+    }
+
+    /// 解析所有物品树写入 `item_trees`，结构问题记录到 warnings
+    pub fn link_item_trees(&mut self) {
+        let trees = self.resolve_item_trees();
+        let warnings = trees.iter().flat_map(|resolved| {
+            resolved.issues.iter().map(|issue| SaveParseError::ItemTree {
+                root_instance_id: resolved.root_instance_id,
+                issue: issue.clone(),
+            })
+        });
+
+        self.warnings.extend(warnings);
+        self.item_trees = trees;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archived::{InventoryItem, SlotContent};
+
+    fn entry(instance_id: i32, slots: &[(&str, i32)], inventory: &[(i32, i32)]) -> ItemEntry {
+        ItemEntry {
+            instance_id,
+            type_id: instance_id * 10,
+            variables: Vec::new(),
+            slot_contents: slots
+                .iter()
+                .map(|(slot, id)| SlotContent { slot: slot.to_string(), instance_id: *id })
+                .collect(),
+            inventory: inventory
+                .iter()
+                .map(|(position, id)| InventoryItem { position: *position, instance_id: *id })
+                .collect(),
+            inventory_sort_locks: Vec::new(),
+            decoded: BTreeMap::new(),
+            info: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_nested() {
+        let tree = ItemTreeData {
+            root_instance_id: 1,
+            entries: vec![
+                entry(1, &[("PrimaryWeapon", 2), ("Backpack", 3)], &[]),
+                entry(2, &[("Scope", 4)], &[]),
+                entry(3, &[], &[(0, 5), (4, 6)]),
+                entry(4, &[], &[]),
+                entry(5, &[], &[]),
+                entry(6, &[], &[]),
+            ],
+        };
+
+        let resolved = tree.resolve();
+        assert!(resolved.issues.is_empty());
+
+        let root = resolved.root.unwrap();
+        assert_eq!(root.slots["PrimaryWeapon"].slots["Scope"].instance_id, 4);
+        assert_eq!(root.slots["Backpack"].inventory[&4].instance_id, 6);
+        assert_eq!(root.walk().len(), 6);
+    }
+
+    #[test]
+    fn test_detect_cycle_dangling_orphan() {
+        let tree = ItemTreeData {
+            root_instance_id: 1,
+            entries: vec![
+                entry(1, &[("Slot", 2)], &[(0, 99)]),
+                entry(2, &[("Loop", 1)], &[]),
+                entry(3, &[], &[]),
+            ],
+        };
+
+        let resolved = tree.resolve();
+        assert_eq!(
+            resolved.issues,
+            vec![
+                TreeIssue::Cycle { parent: 2, instance_id: 1 },
+                TreeIssue::Dangling { parent: 1, instance_id: 99 },
+                TreeIssue::Orphan { instance_id: 3 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_root() {
+        let tree = ItemTreeData {
+            root_instance_id: 7,
+            entries: vec![entry(1, &[], &[])],
+        };
+
+        let resolved = tree.resolve();
+        assert!(resolved.root.is_none());
+        assert_eq!(
+            resolved.issues,
+            vec![TreeIssue::MissingRoot { instance_id: 7 }, TreeIssue::Orphan { instance_id: 1 }]
+        );
+    }
+
+    #[test]
+    fn test_long_chain() {
+        // 10 万层的单链，不能在解析或遍历时栈溢出
+        let count = 100_000;
+        let tree = ItemTreeData {
+            root_instance_id: 1,
+            entries: (1..=count)
+                .map(|id| if id < count { entry(id, &[("Slot", id + 1)], &[]) } else { entry(id, &[], &[]) })
+                .collect(),
+        };
+
+        let resolved = tree.resolve();
+        let depth = MAX_TREE_DEPTH as i32;
+        assert_eq!(resolved.issues, vec![TreeIssue::TooDeep { parent: depth, instance_id: depth + 1 }]);
+        assert_eq!(resolved.root.unwrap().walk().len(), MAX_TREE_DEPTH);
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use crate::archived::{Archive, Inventory, ItemTreeData};
+use crate::error::SaveParseError;
+
+/// 检查结果的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// 不影响游戏，但通常不会出现
+    Warning,
+    /// 数据矛盾，存档多半已损坏或被手动修改
+    Error,
+}
+
+/// 一条检查结果，`path` 为该值在 process_file 输出 JSON 中的路径
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct Finding {
+    pub severity: Severity,
+    pub code: String,
+    pub path: String,
+    pub message: String,
+}
+
+/// 检查选项
+#[derive(Debug, Clone, Default)]
+pub struct ValidateOptions {
+    /// 已知的钥匙 ID，提供时检查 masterKeys 中的未知 ID
+    pub known_master_keys: Option<BTreeSet<i32>>,
+}
+
+struct Validator {
+    findings: Vec<Finding>,
+    // instanceID 首次出现的位置
+    instances: HashMap<i32, String>,
+}
+
+impl Validator {
+    fn push(&mut self, severity: Severity, code: &str, path: String, message: String) {
+        self.findings.push(Finding {
+            severity,
+            code: code.to_string(),
+            path,
+            message,
+        });
+    }
+
+    fn check_tree(&mut self, tree: &ItemTreeData, path: &str) {
+        let ids: HashSet<i32> = tree.entries.iter().map(|entry| entry.instance_id).collect();
+
+        for (index, entry) in tree.entries.iter().enumerate() {
+            let entry_path = format!("{}.entries[{}]", path, index);
+
+            match self.instances.get(&entry.instance_id) {
+                Some(first) => {
+                    let message = format!("instanceID {} 与 {} 重复", entry.instance_id, first);
+                    self.push(Severity::Error, "duplicate_instance", format!("{}.instanceID", entry_path), message);
+                }
+                None => {
+                    self.instances.insert(entry.instance_id, entry_path.clone());
+                }
+            }
+
+            for (slot_index, slot) in entry.slot_contents.iter().enumerate() {
+                if !ids.contains(&slot.instance_id) {
+                    self.push(
+                        Severity::Error,
+                        "missing_slot_content",
+                        format!("{}.slotContents[{}].instanceID", entry_path, slot_index),
+                        format!("槽位 {} 指向不存在的物品 {}", slot.slot, slot.instance_id),
+                    );
+                }
+            }
+            for (item_index, item) in entry.inventory.iter().enumerate() {
+                if !ids.contains(&item.instance_id) {
+                    self.push(
+                        Severity::Error,
+                        "missing_inventory_item",
+                        format!("{}.inventory[{}].instanceID", entry_path, item_index),
+                        format!("背包位置 {} 指向不存在的物品 {}", item.position, item.instance_id),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_inventory(&mut self, inventory: &Inventory, path: &str) {
+        for (index, entry) in inventory.entries.iter().enumerate() {
+            let entry_path = format!("{}.entries[{}]", path, index);
+            let position = entry.inventory_position;
+            if position < 0 || position >= inventory.capacity {
+                self.push(
+                    Severity::Error,
+                    "position_out_of_range",
+                    format!("{}.inventoryPosition", entry_path),
+                    format!("位置 {} 超出容量 {}", position, inventory.capacity),
+                );
+            }
+            self.check_tree(&entry.item_tree_data, &format!("{}.itemTreeData", entry_path));
+        }
+    }
+
+    // 解析阶段的警告说明有段落或物品没能完整读出，检查结果不可信，一律按错误报告
+    fn check_warnings(&mut self, warnings: &[SaveParseError]) {
+        for warning in warnings {
+            let (code, path) = match warning {
+                SaveParseError::SectionType { key, .. } => ("section_type", key.clone()),
+                SaveParseError::UnknownVersion { .. } => ("unknown_version", "CreatedWithVersion".to_string()),
+                SaveParseError::ItemVariable { instance_id, .. } => ("item_variable", self.entry_path(*instance_id, ".variables")),
+                SaveParseError::ItemTree { root_instance_id, .. } => ("item_tree", self.entry_path(*root_instance_id, "")),
+                _ => ("parse_warning", "$".to_string()),
+            };
+            self.push(Severity::Error, code, path, warning.to_string());
+        }
+    }
+
+    // 物品条目在输出 JSON 中的路径，找不到时指向根
+    fn entry_path(&self, instance_id: i32, suffix: &str) -> String {
+        match self.instances.get(&instance_id) {
+            Some(path) => format!("{}{}", path, suffix),
+            None => "$".to_string(),
+        }
+    }
+}
+
+/// 检查存档内部数据是否一致，用于发现损坏或被手动修改的存档
+pub fn validate(archive: &Archive, options: &ValidateOptions) -> Vec<Finding> {
+    let mut validator = Validator {
+        findings: Vec::new(),
+        instances: HashMap::new(),
+    };
+
+    if archive.economy_data.money < 0 {
+        validator.push(
+            Severity::Error,
+            "negative_money",
+            "$.economyData.money".to_string(),
+            format!("金钱为负数: {}", archive.economy_data.money),
+        );
+    }
+    if archive.health < 0.0 {
+        validator.push(
+            Severity::Error,
+            "negative_health",
+            "$.health".to_string(),
+            format!("生命值为负数: {}", archive.health),
+        );
+    }
+
+    validator.check_tree(&archive.character_item_data, "$.characterItemData");
+    validator.check_inventory(&archive.player_storage, "$.playerStorage");
+    validator.check_inventory(&archive.inventory_safe, "$.inventorySafe");
+    for (index, stash) in archive.death_list.iter().enumerate() {
+        validator.check_tree(&stash.item_tree_data, &format!("$.deathList[{}].itemTreeData", index));
+    }
+    validator.check_warnings(&archive.warnings);
+
+    let history: HashSet<u32> = archive.quests.history_quests.iter().map(|quest| quest.id).collect();
+    for (index, quest) in archive.quests.active_quests.iter().enumerate() {
+        if history.contains(&quest.id) {
+            validator.push(
+                Severity::Warning,
+                "quest_active_and_history",
+                format!("$.quests.activeQuestsData[{}].id", index),
+                format!("任务 {} 同时出现在进行中和历史记录里", quest.id),
+            );
+        }
+    }
+
+    if let Some(known) = &options.known_master_keys {
+        for (index, key) in archive.master_key.iter().enumerate() {
+            if !known.contains(&key.id) {
+                validator.push(
+                    Severity::Warning,
+                    "unknown_master_key",
+                    format!("$.masterKeys[{}].id", index),
+                    format!("未知的钥匙 ID {}", key.id),
+                );
+            }
+        }
+    }
+
+    validator.findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_archive;
+
+    const SAMPLE: &str = include_str!("../fixtures/sample.sav");
+
+    #[test]
+    fn test_clean_sample() {
+        let archive = parse_archive(SAMPLE.as_bytes()).unwrap();
+        let options = ValidateOptions {
+            known_master_keys: Some([827, 828].into()),
+        };
+        assert!(validate(&archive, &options).is_empty());
+    }
+
+    #[test]
+    fn test_hand_edited() {
+        let mut archive = parse_archive(SAMPLE.as_bytes()).unwrap();
+        archive.economy_data.money = -1;
+        archive.player_storage.entries[1].inventory_position = 64;
+        archive.inventory_safe.entries[0].item_tree_data.entries[0].instance_id = 100;
+        archive.character_item_data.entries[0].slot_contents[0].instance_id = 555;
+        let mut quest = archive.quests.active_quests[0].clone();
+        quest.id = 900;
+        archive.quests.active_quests.push(quest);
+
+        let options = ValidateOptions {
+            known_master_keys: Some([827].into()),
+        };
+        let findings = validate(&archive, &options);
+        let codes: Vec<(&str, &str)> = findings.iter().map(|f| (f.code.as_str(), f.path.as_str())).collect();
+
+        assert_eq!(codes, [
+            ("negative_money", "$.economyData.money"),
+            ("missing_slot_content", "$.characterItemData.entries[0].slotContents[0].instanceID"),
+            ("position_out_of_range", "$.playerStorage.entries[1].inventoryPosition"),
+            ("duplicate_instance", "$.inventorySafe.entries[0].itemTreeData.entries[0].instanceID"),
+            ("quest_active_and_history", "$.quests.activeQuestsData[1].id"),
+            ("unknown_master_key", "$.masterKeys[1].id"),
+        ]);
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert_eq!(findings[4].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_parse_warnings() {
+        let broken = SAMPLE.replacen(r#""money" : 12850"#, r#""money" : "lots""#, 1);
+        let mut archive = parse_archive(broken.as_bytes()).unwrap();
+        let instance_id = archive.player_storage.entries[0].item_tree_data.entries[0].instance_id;
+        archive.warnings.push(SaveParseError::ItemVariable {
+            instance_id,
+            key: "Count".to_string(),
+            data_type: 9,
+            message: "未知的 dataType".to_string(),
+        });
+
+        let findings = validate(&archive, &ValidateOptions::default());
+        let codes: Vec<(&str, &str)> = findings.iter().map(|f| (f.code.as_str(), f.path.as_str())).collect();
+
+        assert_eq!(codes, [
+            ("section_type", "EconomyData"),
+            ("item_variable", "$.playerStorage.entries[0].itemTreeData.entries[0].variables"),
+        ]);
+        assert!(findings.iter().all(|f| f.severity == Severity::Error));
+        assert!(findings[0].message.contains("EconomyData"), "{}", findings[0].message);
+    }
+}
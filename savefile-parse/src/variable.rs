@@ -0,0 +1,250 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use crate::archived::{ItemTreeData, ItemVariable};
+use crate::error::SaveParseError;
+
+/// 物品变量的数据类型（对应游戏中 CustomData 的 dataType）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomDataType {
+    Raw = 0,
+    Float = 1,
+    Int = 2,
+    Bool = 3,
+    String = 4,
+}
+
+impl CustomDataType {
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(CustomDataType::Raw),
+            1 => Some(CustomDataType::Float),
+            2 => Some(CustomDataType::Int),
+            3 => Some(CustomDataType::Bool),
+            4 => Some(CustomDataType::String),
+            _ => None,
+        }
+    }
+}
+
+/// 解码后的物品变量值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum VariableValue {
+    Float(f32),
+    Int(i32),
+    Bool(bool),
+    String(String),
+    /// 未知类型或无法解码时保留原始字节
+    Raw(Vec<u8>),
+}
+
+impl VariableValue {
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            VariableValue::Float(v) => Some(*v),
+            VariableValue::Int(v) => Some(*v as f32),
+            _ => None,
+        }
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            VariableValue::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// 对应的 dataType，原始字节没有固定类型
+    pub fn data_type(&self) -> Option<CustomDataType> {
+        match self {
+            VariableValue::Float(_) => Some(CustomDataType::Float),
+            VariableValue::Int(_) => Some(CustomDataType::Int),
+            VariableValue::Bool(_) => Some(CustomDataType::Bool),
+            VariableValue::String(_) => Some(CustomDataType::String),
+            VariableValue::Raw(_) => None,
+        }
+    }
+
+    /// 编码回存档中的字节表示
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            VariableValue::Float(v) => v.to_le_bytes().to_vec(),
+            VariableValue::Int(v) => v.to_le_bytes().to_vec(),
+            VariableValue::Bool(v) => vec![*v as u8],
+            VariableValue::String(v) => v.as_bytes().to_vec(),
+            VariableValue::Raw(bytes) => bytes.clone(),
+        }
+    }
+
+    /// 编码为存档使用的 Base64 字符串
+    pub fn encode(&self) -> String {
+        STANDARD.encode(self.to_bytes())
+    }
+}
+
+// 存档里的数据一般是 Base64，少数旧存档为十六进制字符串（与前端 parseVariable 的判断一致）
+fn decode_payload(data: &str) -> Result<Vec<u8>, String> {
+    if !data.is_empty() && data.len().is_multiple_of(2) && data.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return hex::decode(data).map_err(|e| e.to_string());
+    }
+    STANDARD.decode(data).map_err(|e| e.to_string())
+}
+
+fn fixed<const N: usize>(bytes: &[u8]) -> Result<[u8; N], String> {
+    bytes
+        .try_into()
+        .map_err(|_| format!("期望 {} 字节，实际 {} 字节", N, bytes.len()))
+}
+
+/// 按 dataType 解码单个变量
+///
+/// 解码失败时返回原始字节（若 Base64 本身损坏则为空）以及失败原因。
+pub fn decode_variable(variable: &ItemVariable) -> (VariableValue, Option<String>) {
+    let bytes = match decode_payload(&variable.data) {
+        Ok(bytes) => bytes,
+        Err(err) => return (VariableValue::Raw(Vec::new()), Some(format!("数据无法解码: {}", err))),
+    };
+
+    let decoded = match CustomDataType::from_i32(variable.data_type) {
+        Some(CustomDataType::Float) => fixed::<4>(&bytes).map(|b| VariableValue::Float(f32::from_le_bytes(b))),
+        Some(CustomDataType::Int) => fixed::<4>(&bytes).map(|b| VariableValue::Int(i32::from_le_bytes(b))),
+        Some(CustomDataType::Bool) => fixed::<1>(&bytes).map(|b| VariableValue::Bool(b[0] != 0)),
+        Some(CustomDataType::String) => String::from_utf8(bytes.clone())
+            .map(VariableValue::String)
+            .map_err(|e| e.to_string()),
+        Some(CustomDataType::Raw) => Ok(VariableValue::Raw(bytes.clone())),
+        None => Err(format!("未知的 dataType {}", variable.data_type)),
+    };
+
+    match decoded {
+        Ok(value) => (value, None),
+        Err(err) => (VariableValue::Raw(bytes), Some(err)),
+    }
+}
+
+impl ItemTreeData {
+    /// 解码树中所有物品的变量，写入每个条目的 `decoded`，返回解码失败的警告
+    pub fn decode_variables(&mut self) -> Vec<SaveParseError> {
+        let mut warnings = Vec::new();
+        for entry in &mut self.entries {
+            entry.decoded.clear();
+            for variable in &entry.variables {
+                let (value, warning) = decode_variable(variable);
+                if let Some(message) = warning {
+                    warnings.push(SaveParseError::ItemVariable {
+                        instance_id: entry.instance_id,
+                        key: variable.key.clone(),
+                        data_type: variable.data_type,
+                        message,
+                    });
+                }
+                entry.decoded.insert(variable.key.clone(), value);
+            }
+        }
+        warnings
+    }
+
+    /// 将 `decoded` 中被修改过的值编码回 `variables`
+    pub fn encode_variables(&mut self) {
+        for entry in &mut self.entries {
+            for variable in &mut entry.variables {
+                let Some(value) = entry.decoded.get(&variable.key) else {
+                    continue;
+                };
+                if decode_variable(variable).0 == *value {
+                    continue;
+                }
+                variable.data = value.encode();
+                if let Some(data_type) = value.data_type() {
+                    variable.data_type = data_type as i32;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archived::ItemEntry;
+
+    // 取自存档中的物品条目
+    const WEAPON_ENTRY: &str = r#"{
+        "instanceID" : 101,
+        "typeID" : 238,
+        "variables" : [
+            {"key" : "Durability", "dataType" : 1, "data" : "AACvQg==", "display" : true},
+            {"key" : "BulletCount", "dataType" : 2, "data" : "HgAAAA==", "display" : true},
+            {"key" : "Inspected", "dataType" : 3, "data" : "AQ==", "display" : false},
+            {"key" : "Note", "dataType" : 4, "data" : "5bCP6bit", "display" : false}
+        ],
+        "slotContents" : [],
+        "inventory" : [],
+        "inventorySortLocks" : []
+    }"#;
+
+    fn variable(data_type: i32, data: &str) -> ItemVariable {
+        ItemVariable {
+            key: "Test".to_string(),
+            data_type,
+            data: data.to_string(),
+            display: true,
+        }
+    }
+
+    #[test]
+    fn test_decode_entry_fixture() {
+        let entry: ItemEntry = serde_json::from_str(WEAPON_ENTRY).unwrap();
+        let mut tree = ItemTreeData {
+            root_instance_id: 101,
+            entries: vec![entry],
+        };
+
+        let warnings = tree.decode_variables();
+        assert!(warnings.is_empty());
+
+        let decoded = &tree.entries[0].decoded;
+        assert_eq!(decoded["Durability"], VariableValue::Float(87.5));
+        assert_eq!(decoded["BulletCount"], VariableValue::Int(30));
+        assert_eq!(decoded["Inspected"], VariableValue::Bool(true));
+        assert_eq!(decoded["Note"], VariableValue::String("小鸭".to_string()));
+    }
+
+    #[test]
+    fn test_decode_negative_int_and_hex() {
+        let (value, warning) = decode_variable(&variable(2, "ffffffff"));
+        assert_eq!(value, VariableValue::Int(-1));
+        assert!(warning.is_none());
+    }
+
+    #[test]
+    fn test_unknown_type_keeps_raw_bytes() {
+        let original = variable(9, "AQIDBA==");
+        let (value, warning) = decode_variable(&original);
+
+        assert_eq!(value, VariableValue::Raw(vec![1, 2, 3, 4]));
+        assert!(warning.is_some());
+        assert_eq!(value.encode(), original.data);
+    }
+
+    #[test]
+    fn test_wrong_length_falls_back_to_raw() {
+        let (value, warning) = decode_variable(&variable(1, "AQ=="));
+        assert_eq!(value, VariableValue::Raw(vec![1]));
+        assert!(warning.is_some());
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        for data in ["AACvQg==", "HgAAAA==", "AQ=="] {
+            for data_type in 1..=3 {
+                let (value, warning) = decode_variable(&variable(data_type, data));
+                if warning.is_none() {
+                    assert_eq!(value.encode(), data);
+                }
+            }
+        }
+    }
+}
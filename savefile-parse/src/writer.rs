@@ -0,0 +1,456 @@
+use std::collections::BTreeMap;
+use serde::Serialize;
+use serde_json::Value;
+use crate::archived::{Archive, ItemTreeData};
+use crate::container::{ContainerLayer, decode_container, encode_container};
+use crate::error::SaveParseError;
+use crate::extractor::SaveData;
+use crate::ParseOptions;
+
+// 游戏存档中以 `"__type":"<t>"<literal>` 形式写出的基础类型
+const QUIRK_TYPES: &[&str] = &["bool", "int", "long", "float", "double", "string"];
+
+// 数组元素的标识字段，按顺序取第一个存在的；长度变化时据此找回原元素
+const ELEMENT_KEYS: &[&str] = &["instanceID", "inventoryPosition", "position", "slot", "key", "id"];
+
+/// 可编辑的存档文档
+///
+/// 保留原始文本，只有被修改的顶层键会重新序列化，其余键原样输出。
+pub struct SaveDocument {
+    bom: bool,
+    layers: Vec<ContainerLayer>,
+    password: String,
+    save_data: SaveData,
+    edits: BTreeMap<String, String>,
+}
+
+// 段落所在行的缩进，重新序列化时沿用
+fn line_indent(text: &str, offset: usize) -> &str {
+    let line_start = text[..offset].rfind('\n').map_or(0, |p| p + 1);
+    let line = &text[line_start..offset];
+    &line[..line.len() - line.trim_start().len()]
+}
+
+fn is_quirk(map: &serde_json::Map<String, Value>) -> Option<(&str, &Value)> {
+    if map.len() != 2 {
+        return None;
+    }
+    let type_name = map.get("__type")?.as_str()?;
+    let value = map.get("value")?;
+    (QUIRK_TYPES.contains(&type_name) && !value.is_object() && !value.is_array()).then_some((type_name, value))
+}
+
+/// 按游戏存档的格式输出 JSON（基础类型使用 `"__type":"int"123` 写法）
+struct Es3Writer {
+    out: String,
+    pretty: bool,
+}
+
+impl Es3Writer {
+    fn newline(&mut self, indent: &str, depth: usize) {
+        if self.pretty {
+            self.out.push('\n');
+            self.out.push_str(indent);
+            for _ in 0..depth {
+                self.out.push('\t');
+            }
+        }
+    }
+
+    fn separator(&self) -> &'static str {
+        if self.pretty { " : " } else { ":" }
+    }
+
+    fn write(&mut self, value: &Value, indent: &str, depth: usize) {
+        match value {
+            Value::Object(map) if map.is_empty() => self.out.push_str("{}"),
+            Value::Object(map) => {
+                self.out.push('{');
+                if let Some((type_name, literal)) = is_quirk(map) {
+                    self.newline(indent, depth + 1);
+                    let sep = self.separator();
+                    self.out.push_str(&format!("\"__type\"{}\"{}\"{}", sep, type_name, literal));
+                } else {
+                    for (index, (key, child)) in map.iter().enumerate() {
+                        if index > 0 {
+                            self.out.push(',');
+                        }
+                        self.newline(indent, depth + 1);
+                        self.out.push_str(&Value::String(key.clone()).to_string());
+                        self.out.push_str(self.separator());
+                        self.write(child, indent, depth + 1);
+                    }
+                }
+                self.newline(indent, depth);
+                self.out.push('}');
+            }
+            Value::Array(items) => {
+                self.out.push('[');
+                let scalars = items.iter().all(|v| !v.is_object() && !v.is_array());
+                if scalars && !items.is_empty() {
+                    self.newline(indent, depth + 1);
+                    let line: Vec<String> = items.iter().map(Value::to_string).collect();
+                    self.out.push_str(&line.join(","));
+                } else {
+                    for (index, child) in items.iter().enumerate() {
+                        if index > 0 {
+                            self.out.push(',');
+                        }
+                        self.newline(indent, depth + 1);
+                        self.write(child, indent, depth + 1);
+                    }
+                }
+                self.newline(indent, depth);
+                self.out.push(']');
+            }
+            scalar => self.out.push_str(&scalar.to_string()),
+        }
+    }
+}
+
+// 将修改后的值覆盖到原值上，保留类型中未建模的字段；数值相等时保留原始写法
+fn merge(original: &mut Value, edited: Value) {
+    match (original, edited) {
+        (Value::Object(target), Value::Object(source)) => {
+            for (key, value) in source {
+                match target.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Array(target), Value::Array(source)) if target.len() == source.len() => {
+            for (existing, value) in target.iter_mut().zip(source) {
+                merge(existing, value);
+            }
+        }
+        (Value::Array(target), Value::Array(source)) => {
+            // 增删过元素，按标识字段匹配原元素合并；没有标识的元素直接使用新值
+            let mut originals: Vec<Option<Value>> = std::mem::take(target).into_iter().map(Some).collect();
+            for value in source {
+                let matched = element_key(&value).and_then(|key| {
+                    originals
+                        .iter()
+                        .position(|original| original.as_ref().and_then(element_key) == Some(key))
+                });
+                match matched.and_then(|index| originals[index].take()) {
+                    Some(mut existing) => {
+                        merge(&mut existing, value);
+                        target.push(existing);
+                    }
+                    None => target.push(value),
+                }
+            }
+        }
+        (Value::Number(a), Value::Number(b)) if same_number(a, &b) => {}
+        (target, value) => *target = value,
+    }
+}
+
+fn element_key(value: &Value) -> Option<(&'static str, &Value)> {
+    let map = value.as_object()?;
+    ELEMENT_KEYS.iter().find_map(|&field| Some((field, map.get(field)?)))
+}
+
+// 整数严格比较；修改后的值是浮点数时来自 f32 字段，按 f32 精度比较
+fn same_number(original: &serde_json::Number, edited: &serde_json::Number) -> bool {
+    if edited.is_f64() {
+        original.as_f64().zip(edited.as_f64()).is_some_and(|(x, y)| x as f32 == y as f32)
+    } else {
+        original == edited
+    }
+}
+
+// 导出前把 decoded 写回 variables，并清空 decoded 以免写入存档
+fn prepare_tree(tree: &ItemTreeData) -> ItemTreeData {
+    let mut tree = tree.clone();
+    tree.encode_variables();
+    for entry in &mut tree.entries {
+        entry.decoded.clear();
+        entry.info = None;
+    }
+    tree
+}
+
+impl SaveDocument {
+    pub fn parse(data: &[u8]) -> Result<Self, SaveParseError> {
+        Self::parse_with(data, &ParseOptions::default())
+    }
+
+    pub fn parse_with(data: &[u8], options: &ParseOptions) -> Result<Self, SaveParseError> {
+        let (plain, layers) = decode_container(data, &options.password)?;
+        let text = std::str::from_utf8(&plain)?;
+        let (bom, source) = match text.strip_prefix('\u{feff}') {
+            Some(rest) => (true, rest.to_string()),
+            None => (false, text.to_string()),
+        };
+        let save_data = SaveData::from_es3(source)?;
+        Ok(SaveDocument {
+            bom,
+            layers,
+            password: options.password.clone(),
+            save_data,
+            edits: BTreeMap::new(),
+        })
+    }
+
+    pub fn save_data(&self) -> &SaveData {
+        &self.save_data
+    }
+
+    /// 用类型化的值替换某个顶层段落的 `value`，保留原有的 `__type`
+    pub fn set_section<T: Serialize>(&mut self, key: &str, value: &T) -> Result<(), SaveParseError> {
+        let range = self
+            .save_data
+            .raw_range(key)
+            .ok_or_else(|| SaveParseError::MissingKey { key: key.to_string() })?;
+        let mut wrapper = self
+            .save_data
+            .value(key)?
+            .ok_or_else(|| SaveParseError::MissingKey { key: key.to_string() })?;
+
+        let edited = serde_json::to_value(value).map_err(|e| SaveParseError::section(key, e))?;
+        match wrapper.get_mut("value") {
+            Some(original) => merge(original, edited),
+            None => return Err(SaveParseError::MissingKey { key: format!("{}.value", key) }),
+        }
+
+        let source = self.save_data.source();
+        let mut writer = Es3Writer {
+            out: String::new(),
+            pretty: source[range.clone()].contains('\n'),
+        };
+        writer.write(&wrapper, line_indent(source, range.start), 0);
+        self.edits.insert(key.to_string(), writer.out);
+        self.save_data.set_value(key, wrapper);
+        Ok(())
+    }
+
+    pub fn set_money(&mut self, money: i32) -> Result<(), SaveParseError> {
+        let mut economy = self.save_data.get_currency()?.unwrap_or_default();
+        economy.money = money;
+        self.set_section("EconomyData", &economy)
+    }
+
+    pub fn set_master_key_active(&mut self, id: i32, active: bool) -> Result<(), SaveParseError> {
+        let mut keys = self.save_data.get_master_keys()?.unwrap_or_default();
+        match keys.iter_mut().find(|key| key.id == id) {
+            Some(key) => key.active = active,
+            None => return Err(SaveParseError::MissingKey { key: format!("MasterKeys[{}]", id) }),
+        }
+        self.set_section("MasterKeys", &keys)
+    }
+
+    /// 把编辑后的 Archive 中与原存档不同的段落写回
+    pub fn apply_archive(&mut self, archive: &Archive) -> Result<(), SaveParseError> {
+        macro_rules! apply_sections {
+            ($($key:literal => $getter:ident => $value:expr),* $(,)?) => {
+                $(
+                    let original = self.save_data.$getter()?;
+                    let edited = $value;
+                    let changed = match &original {
+                        Some(original) => serde_json::to_value(original).ok() != serde_json::to_value(&edited).ok(),
+                        None => false,
+                    };
+                    if changed {
+                        self.set_section($key, &edited)?;
+                    }
+                )*
+            };
+        }
+
+        let mut player_storage = archive.player_storage.clone();
+        let mut inventory_safe = archive.inventory_safe.clone();
+        for entry in player_storage.entries.iter_mut().chain(inventory_safe.entries.iter_mut()) {
+            entry.item_tree_data = prepare_tree(&entry.item_tree_data);
+        }
+
+        apply_sections! {
+            "EconomyData" => get_currency => archive.economy_data.clone(),
+            "MasterKeys" => get_master_keys => archive.master_key.clone(),
+            "GameClock" => get_game_lock => archive.game_lock.clone(),
+            "QuestData" => get_quests => archive.quests.clone(),
+            "EXP_Value" => get_exp => archive.exp,
+            "MainCharacterHealth" => get_health => archive.health,
+            "Inventory/PlayerStorage" => get_player_storage => player_storage,
+            "Inventory/Inventory_Safe" => get_inventory_safe => inventory_safe,
+            "Item/MainCharacterItemData" => get_character_items => prepare_tree(&archive.character_item_data),
+        }
+        Ok(())
+    }
+
+    /// 输出为游戏可读取的存档文本
+    pub fn write(&self) -> String {
+        let mut out = String::with_capacity(self.save_data.source().len());
+        let mut cursor = 0;
+        for section in self.save_data.sections() {
+            if let Some(edited) = self.edits.get(&section.key) {
+                out.push_str(&self.save_data.source()[cursor..section.value.start]);
+                out.push_str(edited);
+                cursor = section.value.end;
+            }
+        }
+        out.push_str(&self.save_data.source()[cursor..]);
+        out
+    }
+
+    /// 输出存档字节，按原文件的加密、压缩方式重新封装
+    pub fn write_bytes(&self) -> Vec<u8> {
+        let mut plain = Vec::new();
+        if self.bom {
+            plain.extend_from_slice("\u{feff}".as_bytes());
+        }
+        plain.extend_from_slice(self.write().as_bytes());
+        encode_container(&plain, &self.layers, &self.password)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_archive;
+
+    const SAMPLE: &str = include_str!("../fixtures/sample.sav");
+
+    fn section<'a>(text: &'a str, key: &str) -> &'a str {
+        let sections = crate::format::index_sections(text).unwrap();
+        let section = sections.iter().find(|section| section.key == key).unwrap();
+        &text[section.value.clone()]
+    }
+
+    #[test]
+    fn test_round_trip_without_edits() {
+        let document = SaveDocument::parse(SAMPLE.as_bytes()).unwrap();
+        assert_eq!(document.write(), SAMPLE);
+
+        let archive = parse_archive(SAMPLE.as_bytes()).unwrap();
+        let mut document = SaveDocument::parse(SAMPLE.as_bytes()).unwrap();
+        document.apply_archive(&archive).unwrap();
+        assert_eq!(document.write(), SAMPLE);
+    }
+
+    #[test]
+    fn test_scan_keys() {
+        let document = SaveDocument::parse(SAMPLE.as_bytes()).unwrap();
+        assert_eq!(document.save_data().keys().next(), Some("CreatedWithVersion"));
+        assert_eq!(section(SAMPLE, "EXP_Value"), "{\n\t\t\"__type\" : \"int\"5320\n\t}");
+    }
+
+    #[test]
+    fn test_edit_money_keeps_other_sections() {
+        let mut document = SaveDocument::parse(SAMPLE.as_bytes()).unwrap();
+        document.set_money(99999).unwrap();
+        let written = document.write();
+
+        let archive = parse_archive(written.as_bytes()).unwrap();
+        assert_eq!(archive.economy_data.money, 99999);
+        assert_eq!(archive.economy_data.unlocked_items, vec![254, 256, 381]);
+        assert!(section(written.as_str(), "EconomyData").contains("Duckov.Economy.EconomyManager+SaveData"));
+        for key in ["MasterKeys", "Inventory/PlayerStorage", "CustomFace_MainCharacter"] {
+            assert_eq!(section(&written, key), section(SAMPLE, key));
+        }
+    }
+
+    #[test]
+    fn test_edit_money_above_f32_precision() {
+        let mut document = SaveDocument::parse(SAMPLE.as_bytes()).unwrap();
+        document.set_money(16_777_216).unwrap();
+        let written = document.write();
+
+        let mut document = SaveDocument::parse(written.as_bytes()).unwrap();
+        document.set_money(16_777_217).unwrap();
+        let written = document.write();
+        assert_eq!(parse_archive(written.as_bytes()).unwrap().economy_data.money, 16_777_217);
+    }
+
+    #[test]
+    fn test_apply_edited_archive() {
+        let mut archive = parse_archive(SAMPLE.as_bytes()).unwrap();
+        archive.exp = 6000;
+        archive.master_key[1].active = true;
+        archive.game_lock.days = 20;
+        let weapon = &mut archive.character_item_data.entries[1];
+        weapon.decoded.insert("Durability".to_string(), crate::variable::VariableValue::Float(100.0));
+
+        let mut document = SaveDocument::parse(SAMPLE.as_bytes()).unwrap();
+        document.apply_archive(&archive).unwrap();
+        let written = document.write();
+
+        assert!(written.contains("\"__type\" : \"int\"6000"));
+        let reparsed = parse_archive(written.as_bytes()).unwrap();
+        assert_eq!(reparsed.exp, 6000);
+        assert!(reparsed.master_key[1].active);
+        assert_eq!(reparsed.game_lock.days, 20);
+        assert_eq!(reparsed.character_item_data.entries[1].decoded["Durability"].as_f32(), Some(100.0));
+        assert!(!section(&written, "Item/MainCharacterItemData").contains("decoded"));
+        assert_eq!(section(&written, "DeathList"), section(SAMPLE, "DeathList"));
+    }
+
+    #[test]
+    fn test_remove_entry_keeps_unmodeled_fields() {
+        let text = SAMPLE
+            .replacen(r#""inventoryPosition" : 3,"#, r#""inventoryPosition" : 3, "locked" : true,"#, 1)
+            .replacen(r#""instanceID" : 201,"#, r#""instanceID" : 201, "modLevel" : 2,"#, 1);
+        let mut archive = parse_archive(text.as_bytes()).unwrap();
+        let removed = archive.player_storage.entries.remove(0);
+        assert_eq!(removed.inventory_position, 0);
+
+        let mut document = SaveDocument::parse(text.as_bytes()).unwrap();
+        document.apply_archive(&archive).unwrap();
+        let written = document.write();
+
+        let storage = section(&written, "Inventory/PlayerStorage");
+        assert!(storage.contains("\"locked\" : true"), "{}", storage);
+        assert!(storage.contains("\"modLevel\" : 2"), "{}", storage);
+        let reparsed = parse_archive(written.as_bytes()).unwrap();
+        let positions: Vec<i32> = reparsed.player_storage.entries.iter().map(|e| e.inventory_position).collect();
+        let expected: Vec<i32> = archive.player_storage.entries.iter().map(|e| e.inventory_position).collect();
+        assert_eq!(positions, expected);
+    }
+
+    #[test]
+    fn test_encrypted_round_trip() {
+        let layers = [ContainerLayer::Gzip, ContainerLayer::Encrypted { iv: *b"0123456789abcdef" }];
+        let data = encode_container(SAMPLE.as_bytes(), &layers, "duck");
+        let options = ParseOptions { password: "duck".to_string(), ..Default::default() };
+
+        let document = SaveDocument::parse_with(&data, &options).unwrap();
+        assert_eq!(document.write_bytes(), data);
+    }
+
+    #[test]
+    fn test_quirk_types_round_trip() {
+        let header = r#"{"CreatedWithVersion":{"__type":"Duckov.VersionData,TeamSoda.Duckov.Core","value":{"mainVersion":1,"subVersion":0,"buildVersion":30,"suffix":""}},"#;
+        let cases: [(&str, &str, Value, &str); 6] = [
+            ("bool", "false", Value::from(true), "true"),
+            ("int", "10", Value::from(-42), "-42"),
+            ("long", "9007199254740993", Value::from(9007199254740995u64), "9007199254740995"),
+            ("float", "1.5", Value::from(2.5), "2.5"),
+            ("double", "-3.25", Value::from(0.125), "0.125"),
+            ("string", r#""鸭""#, Value::from("quack \"duck\""), r#""quack \"duck\"""#),
+        ];
+        for (type_name, original, edited, literal) in cases {
+            let text = format!(r#"{}"X":{{"__type":"{}"{}}}}}"#, header, type_name, original);
+            let mut document = SaveDocument::parse(text.as_bytes()).unwrap();
+            document.set_section("X", &edited).unwrap();
+            let written = document.write();
+            assert_eq!(written, format!(r#"{}"X":{{"__type":"{}"{}}}}}"#, header, type_name, literal));
+
+            let reparsed = SaveDocument::parse(written.as_bytes()).unwrap();
+            assert_eq!(reparsed.save_data().value("X").unwrap().unwrap()["value"], edited, "{}", type_name);
+        }
+    }
+
+    #[test]
+    fn test_compact_section_stays_compact() {
+        let text = r#"{"CreatedWithVersion":{"__type":"Duckov.VersionData,TeamSoda.Duckov.Core","value":{"mainVersion":1,"subVersion":0,"buildVersion":30,"suffix":""}},"EXP_Value":{"__type":"int"10}}"#;
+        let mut document = SaveDocument::parse(text.as_bytes()).unwrap();
+        document.set_section("EXP_Value", &42).unwrap();
+        assert_eq!(
+            document.write(),
+            r#"{"CreatedWithVersion":{"__type":"Duckov.VersionData,TeamSoda.Duckov.Core","value":{"mainVersion":1,"subVersion":0,"buildVersion":30,"suffix":""}},"EXP_Value":{"__type":"int"42}}"#
+        );
+    }
+}
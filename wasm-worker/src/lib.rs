@@ -0,0 +1,130 @@
+//! 在专用 Worker 中运行 wasm 任务的消息协议
+//!
+//! 主线程向 Worker 发送 `{ id, ... }` 请求，Worker 按同一个 `id` 回复若干
+//! `progress` 事件，最后以 `done`、`error` 或 `cancelled` 之一结束。
+//! 取消通过一个共享的 `Int32Array` 标志传递：主线程写入 1，Worker 在各阶段之间读取。
+//! 页面不是跨源隔离（没有 SharedArrayBuffer）时不传标志，主线程直接终止 Worker。
+//!
+//! savefile-parse 用于解析存档，wgpu-renderer 用于在 Worker 中烘焙场景。
+
+use js_sys::{Array, Atomics, Int32Array, Reflect, Uint8Array};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::DedicatedWorkerGlobalScope;
+
+/// Worker 发回主线程的消息，带有 `type` 标签
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkerEvent<P, R, E> {
+    Progress { id: u32, progress: P },
+    Done { id: u32, result: R },
+    Error { id: u32, error: E },
+    Cancelled { id: u32 },
+}
+
+/// 主线程设置的取消标志，没有共享内存时永远不会被取消
+#[derive(Debug, Clone, Default)]
+pub struct CancelFlag(Option<Int32Array>);
+
+impl CancelFlag {
+    pub fn new(flag: Option<Int32Array>) -> Self {
+        CancelFlag(flag)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        match &self.0 {
+            Some(flag) => Atomics::load(flag, 0).is_ok_and(|value| value != 0),
+            None => false,
+        }
+    }
+}
+
+/// Worker 中正在执行的一个请求
+#[derive(Debug, Clone)]
+pub struct WorkerTask {
+    id: u32,
+    cancel: CancelFlag,
+}
+
+impl WorkerTask {
+    pub fn new(id: u32, cancel: Option<Int32Array>) -> Self {
+        WorkerTask { id, cancel: CancelFlag::new(cancel) }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// 发送进度，返回 false 表示主线程已经取消
+    pub fn progress<P: Serialize>(&self, progress: &P) -> bool {
+        post(&WorkerEvent::<&P, (), ()>::Progress { id: self.id, progress });
+        !self.is_cancelled()
+    }
+
+    /// 发送最终结果，之后不应再使用该任务
+    pub fn finish<R: Serialize, E: Serialize>(self, result: Result<R, E>) {
+        let id = self.id;
+        match result {
+            Ok(result) => post(&WorkerEvent::<(), R, ()>::Done { id, result }),
+            Err(error) => post(&WorkerEvent::<(), (), E>::Error { id, error }),
+        }
+    }
+
+    /// 以 `Uint8Array` 发送二进制结果，缓冲区直接转移给主线程，不逐字节序列化
+    pub fn finish_bytes(self, bytes: &[u8]) {
+        let result = Uint8Array::from(bytes);
+        let message = to_message(&WorkerEvent::<(), (), ()>::Done { id: self.id, result: () });
+        Reflect::set(&message, &JsValue::from_str("result"), &result).unwrap_throw();
+        scope()
+            .post_message_with_transfer(&message, &Array::of1(&result.buffer()))
+            .unwrap_throw();
+    }
+
+    pub fn cancelled(self) {
+        post(&WorkerEvent::<(), (), ()>::Cancelled { id: self.id });
+    }
+}
+
+fn to_message<P: Serialize, R: Serialize, E: Serialize>(event: &WorkerEvent<P, R, E>) -> JsValue {
+    // 与 savefile-parse 的输出一致：map 输出为普通对象，None 输出为 null
+    match event.serialize(&serde_wasm_bindgen::Serializer::json_compatible()) {
+        Ok(message) => message,
+        Err(e) => JsValue::from_str(&e.to_string()),
+    }
+}
+
+fn scope() -> DedicatedWorkerGlobalScope {
+    js_sys::global().unchecked_into()
+}
+
+fn post<P: Serialize, R: Serialize, E: Serialize>(event: &WorkerEvent<P, R, E>) {
+    scope().post_message(&to_message(event)).unwrap_throw();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Event = WorkerEvent<String, u32, String>;
+
+    #[test]
+    fn test_event_shape() {
+        let progress = Event::Progress { id: 3, progress: "decode".to_string() };
+        assert_eq!(
+            serde_json::to_string(&progress).unwrap(),
+            r#"{"type":"progress","id":3,"progress":"decode"}"#
+        );
+        let cancelled: Event = serde_json::from_str(r#"{"type":"cancelled","id":3}"#).unwrap();
+        assert_eq!(cancelled, Event::Cancelled { id: 3 });
+    }
+
+    #[test]
+    fn test_no_flag_never_cancelled() {
+        assert!(!CancelFlag::default().is_cancelled());
+    }
+}
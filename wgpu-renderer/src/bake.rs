@@ -0,0 +1,1084 @@
+// 离线烘焙：把 .unity 场景及其引用的 mesh、材质预先解析为二进制包，运行时无需再解析 YAML
+//
+// 包格式（小端）：
+//   magic "WRBK" | version u32 | payload 长度 u64 | payload crc32 u32 | payload
+// payload 依次为实体表、mesh 表、材质表
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use cgmath::Point3;
+use serde::Serialize;
+use wgpu::{Device, Queue, SurfaceConfiguration};
+use crate::entity::TransformSystem;
+use crate::materials::MaterialDesc;
+use crate::mesh::{Indices, Mesh, MeshData, SubMesh, AABB};
+use crate::prefab::PrefabLibrary;
+use crate::resource::{MaterialId, MeshId, ResourceManager};
+use crate::scene::{collect_scene_objects, Scene, SceneObject};
+use crate::unity::{UnityReference, UnityScene, UnityVertexAttribute, UnityVertexAttributeDescriptor, UnityVertexFormat};
+
+pub const BAKE_MAGIC: &[u8; 4] = b"WRBK";
+// 包格式变化时递增，旧包需要重新烘焙
+pub const BAKE_VERSION: u32 = 1;
+pub const BAKE_EXTENSION: &str = "bake";
+
+const HEADER_SIZE: usize = 20;
+// Option<u32> 的空值
+const NONE_INDEX: u32 = u32::MAX;
+
+// 扁平化后的实体
+#[derive(Debug, Clone, PartialEq)]
+pub struct BakedEntity {
+    pub id: u32,
+    // 世界矩阵，没有 Transform 参与计算的实体（光照等）为 None
+    pub world: Option<[[f32; 4]; 4]>,
+    // 自身及父节点的显示状态
+    pub visible: bool,
+    // meshes 中的下标
+    pub mesh: Option<u32>,
+    // 每个材质槽位在 materials 中的下标，找不到的材质为 None
+    pub materials: Vec<Option<u32>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BakedMesh {
+    pub reference: UnityReference,
+    // unity 内置 mesh 在运行时生成，为 None
+    pub data: Option<MeshData>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BakedMaterial {
+    pub guid: MaterialId,
+    pub desc: MaterialDesc,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BakedScene {
+    pub entities: Vec<BakedEntity>,
+    pub meshes: Vec<BakedMesh>,
+    pub materials: Vec<BakedMaterial>,
+}
+
+impl BakedScene {
+    // 实体的材质槽位，供 ResourceManager::load_baked_materials 使用
+    pub fn material_slots(&self, entity: &BakedEntity) -> Vec<Option<(&MaterialId, &MaterialDesc)>> {
+        entity
+            .materials
+            .iter()
+            .map(|slot| {
+                slot.and_then(|i| self.materials.get(i as usize))
+                    .map(|material| (&material.guid, &material.desc))
+            })
+            .collect()
+    }
+}
+
+/// 烘焙场景对象：与 Scene::loading_scene 相同的父子关系与显隐规则，read 按 guid 读取资源文件
+pub fn bake_objects(
+    objects: &[SceneObject],
+    mut read: impl FnMut(&str) -> anyhow::Result<Option<Vec<u8>>>,
+) -> anyhow::Result<BakedScene> {
+    let mut transform_system = TransformSystem::new();
+    let mut display_map = HashMap::new();
+    for object in objects {
+        if !object.active {
+            display_map.insert(object.entity, false);
+        }
+        if object.has_transform && let Some(parent) = object.parent {
+            transform_system.set_parent(parent, object.entity);
+        }
+        // 光照暂时不渲染
+        if !object.is_light {
+            transform_system.add_transform(object.entity, object.transform);
+        }
+    }
+    transform_system.update(&mut display_map);
+
+    let mut baked = BakedScene::default();
+    // 内置 mesh 共用同一个 guid，按 Mesh::mesh_id（guid 与 fileID）去重
+    let mut mesh_index: HashMap<MeshId, u32> = HashMap::new();
+    let mut material_index: HashMap<String, Option<u32>> = HashMap::new();
+    for object in objects {
+        let entity = object.entity;
+        let mut baked_entity = BakedEntity {
+            id: entity.id(),
+            world: transform_system.get_world_matrix(entity).map(Into::into),
+            visible: display_map.get(&entity).copied().unwrap_or(true),
+            mesh: None,
+            materials: Vec::new(),
+        };
+
+        if let Some(m_mesh) = object.mesh.as_ref().filter(|_| !object.is_light) {
+            let mesh_id = Mesh::mesh_id(m_mesh);
+            let index = match mesh_index.get(&mesh_id) {
+                Some(&index) => index,
+                None => {
+                    let data = if Mesh::is_builtin(m_mesh) {
+                        None
+                    } else {
+                        let Some(bytes) = read(&m_mesh.guid)? else {
+                            anyhow::bail!("实体 {} 的 mesh {} 不在 guid.json 中", entity.id(), m_mesh.guid);
+                        };
+                        Some(MeshData::from_unity_asset(std::str::from_utf8(&bytes)?)?)
+                    };
+                    let index = baked.meshes.len() as u32;
+                    baked.meshes.push(BakedMesh { reference: m_mesh.clone(), data });
+                    mesh_index.insert(mesh_id, index);
+                    index
+                }
+            };
+            baked_entity.mesh = Some(index);
+
+            for m_material in &object.materials {
+                let guid = &m_material.guid;
+                let index = match material_index.get(guid) {
+                    Some(&index) => index,
+                    None => {
+                        let index = match read(guid)? {
+                            Some(bytes) => {
+                                baked.materials.push(BakedMaterial {
+                                    guid: guid.clone(),
+                                    desc: MaterialDesc::from_unity_bytes(&bytes)?,
+                                });
+                                Some(baked.materials.len() as u32 - 1)
+                            }
+                            None => None,
+                        };
+                        material_index.insert(guid.clone(), index);
+                        index
+                    }
+                };
+                baked_entity.materials.push(index);
+            }
+        }
+        baked.entities.push(baked_entity);
+    }
+
+    Ok(baked)
+}
+
+/// 烘焙的各个阶段，在 Worker 中烘焙时作为进度发回主线程
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BakeStage {
+    Manifest,
+    Scene,
+    Prefabs,
+    Assets,
+    Bake,
+}
+
+/// 烘焙场景文件，路径与运行时一样相对 GAME_PROJECT_PATH
+pub async fn bake_scene(scene_path: &str) -> anyhow::Result<BakedScene> {
+    let baked = bake_scene_with_progress(scene_path, |_| true).await?;
+    Ok(baked.expect("进度回调没有取消"))
+}
+
+/// 每个阶段开始前调用 progress，返回 false 时停止烘焙并返回 None
+pub async fn bake_scene_with_progress(
+    scene_path: &str,
+    mut progress: impl FnMut(BakeStage) -> bool,
+) -> anyhow::Result<Option<BakedScene>> {
+    if !progress(BakeStage::Manifest) {
+        return Ok(None);
+    }
+    let manifest = crate::resource::AssetManifest::load().await?;
+
+    if !progress(BakeStage::Scene) {
+        return Ok(None);
+    }
+    let mut unity_scene = UnityScene::new().from_str(PathBuf::from(scene_path)).await?;
+
+    if !progress(BakeStage::Prefabs) {
+        return Ok(None);
+    }
+    let prefabs = PrefabLibrary::load(&manifest, &unity_scene).await;
+    unity_scene.resolve_prefabs(&prefabs);
+    let objects = collect_scene_objects(&unity_scene)?;
+
+    if !progress(BakeStage::Assets) {
+        return Ok(None);
+    }
+    // 先读取所有引用到的资源文件，bake_objects 本身是同步的
+    let mut files = HashMap::new();
+    for object in objects.iter().filter(|object| !object.is_light) {
+        let Some(m_mesh) = &object.mesh else {
+            continue;
+        };
+        let meshes = Some(m_mesh).filter(|m_mesh| !Mesh::is_builtin(m_mesh));
+        for reference in meshes.into_iter().chain(&object.materials) {
+            if !files.contains_key(&reference.guid) && manifest.get(&reference.guid).is_some() {
+                files.insert(reference.guid.clone(), manifest.load_guid_file(&reference.guid).await?);
+            }
+        }
+    }
+
+    if !progress(BakeStage::Bake) {
+        return Ok(None);
+    }
+    bake_objects(&objects, |guid| Ok(files.get(guid).cloned())).map(Some)
+}
+
+/// 场景文件对应的烘焙包路径
+pub fn package_path(scene_path: &str) -> String {
+    PathBuf::from(scene_path)
+        .with_extension(BAKE_EXTENSION)
+        .to_string_lossy()
+        .into_owned()
+}
+
+pub fn is_package(path: &str) -> bool {
+    path.ends_with(&format!(".{}", BAKE_EXTENSION))
+}
+
+pub fn write_package(baked: &BakedScene) -> Vec<u8> {
+    let mut payload = Writer::default();
+    payload.write_scene(baked);
+    let payload = payload.bytes;
+
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + payload.len());
+    bytes.extend_from_slice(BAKE_MAGIC);
+    bytes.extend_from_slice(&BAKE_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    bytes
+}
+
+pub fn read_package(bytes: &[u8]) -> anyhow::Result<BakedScene> {
+    if bytes.len() < HEADER_SIZE || &bytes[..4] != BAKE_MAGIC {
+        anyhow::bail!("不是烘焙包");
+    }
+    let mut header = Reader::new(&bytes[4..HEADER_SIZE]);
+    let version = header.u32()?;
+    if version != BAKE_VERSION {
+        anyhow::bail!("烘焙包版本 {} 与当前版本 {} 不一致，请重新烘焙", version, BAKE_VERSION);
+    }
+    let length = header.u64()?;
+    let checksum = header.u32()?;
+
+    let payload = &bytes[HEADER_SIZE..];
+    if payload.len() as u64 != length {
+        anyhow::bail!("烘焙包长度 {} 与记录的 {} 不一致", payload.len(), length);
+    }
+    if crc32fast::hash(payload) != checksum {
+        anyhow::bail!("烘焙包校验失败");
+    }
+
+    let mut reader = Reader::new(payload);
+    let baked = reader.read_scene()?;
+    if !reader.is_end() {
+        anyhow::bail!("烘焙包末尾有多余数据");
+    }
+    Ok(baked)
+}
+
+// 场景来源：.unity 文件或烘焙包
+pub enum SceneSource {
+    Unity(Box<UnityScene>),
+    Baked(BakedScene),
+}
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    // 页面注册的烘焙函数 `(scenePath) => Promise<Uint8Array>`，在专用 Worker 中执行 bake_scene
+    static SCENE_BAKER: std::cell::RefCell<Option<js_sys::Function>> = const { std::cell::RefCell::new(None) };
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn set_scene_baker(baker: Option<js_sys::Function>) {
+    SCENE_BAKER.with(|current| *current.borrow_mut() = baker);
+}
+
+// 交给页面注册的 Worker 烘焙，返回烘焙包字节；没有注册时返回 None
+#[cfg(target_arch = "wasm32")]
+async fn bake_in_worker(scene_path: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    use wasm_bindgen::{JsCast, JsValue};
+
+    let Some(baker) = SCENE_BAKER.with(|baker| baker.borrow().clone()) else {
+        return Ok(None);
+    };
+    let error = |e: JsValue| anyhow::anyhow!("Worker 烘焙场景 {} 失败: {:?}", scene_path, e);
+    let promise: js_sys::Promise = baker
+        .call1(&JsValue::NULL, &JsValue::from_str(scene_path))
+        .map_err(error)?
+        .dyn_into()
+        .map_err(error)?;
+    let bytes = wasm_bindgen_futures::JsFuture::from(promise).await.map_err(error)?;
+    Ok(Some(js_sys::Uint8Array::new(&bytes).to_vec()))
+}
+
+impl SceneSource {
+    pub async fn load(scene_path: &str) -> anyhow::Result<Self> {
+        if is_package(scene_path) {
+            let bytes = ResourceManager::load_binary(scene_path).await?;
+            return Ok(Self::Baked(read_package(&bytes)?));
+        }
+
+        // 页面注册了 Worker 时，YAML 解析、预制体展开和 mesh 解码都在 Worker 中完成
+        #[cfg(target_arch = "wasm32")]
+        if let Some(bytes) = bake_in_worker(scene_path).await? {
+            return Ok(Self::Baked(read_package(&bytes)?));
+        }
+
+        let unity_scene = UnityScene::new().from_str(PathBuf::from(scene_path)).await?;
+        Ok(Self::Unity(Box::new(unity_scene)))
+    }
+
+    pub fn object_count(&self) -> usize {
+        match self {
+            Self::Unity(unity_scene) => unity_scene.game_object_raw.len(),
+            Self::Baked(baked) => baked.entities.len(),
+        }
+    }
+
+    // 加载场景资源，resource_manager 需要已经加载 guid.json
+    pub async fn load_into(
+        self,
+        device: &Device,
+        queue: &Queue,
+        scene: &mut Scene,
+        resource_manager: &mut ResourceManager,
+        config: &SurfaceConfiguration,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Unity(mut unity_scene) => {
+                // 展开预制体实例
+                let prefabs = PrefabLibrary::load(resource_manager.manifest(), &unity_scene).await;
+                unity_scene.resolve_prefabs(&prefabs);
+                Scene::loading_scene(device, queue, scene, &mut unity_scene, resource_manager, config).await
+            }
+            Self::Baked(baked) => {
+                Scene::loading_baked(device, queue, scene, &baked, resource_manager, config).await
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i32(&mut self, value: i32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i64(&mut self, value: i64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn len(&mut self, len: usize) {
+        self.u32(len as u32);
+    }
+
+    fn index(&mut self, index: Option<u32>) {
+        self.u32(index.unwrap_or(NONE_INDEX));
+    }
+
+    fn blob(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    fn str(&mut self, value: &str) {
+        self.len(value.len());
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    fn opt_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                self.str(value);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn point(&mut self, point: Point3<f32>) {
+        for v in [point.x, point.y, point.z] {
+            self.f32(v);
+        }
+    }
+
+    fn write_scene(&mut self, baked: &BakedScene) {
+        self.len(baked.entities.len());
+        for entity in &baked.entities {
+            self.u32(entity.id);
+            self.u8(entity.visible as u8);
+            match &entity.world {
+                Some(world) => {
+                    self.u8(1);
+                    for v in world.iter().flatten() {
+                        self.f32(*v);
+                    }
+                }
+                None => self.u8(0),
+            }
+            self.index(entity.mesh);
+            self.len(entity.materials.len());
+            for slot in &entity.materials {
+                self.index(*slot);
+            }
+        }
+
+        self.len(baked.meshes.len());
+        for mesh in &baked.meshes {
+            self.i64(mesh.reference.file_id);
+            self.str(&mesh.reference.guid);
+            self.i32(mesh.reference.ref_type);
+            match &mesh.data {
+                Some(data) => {
+                    self.u8(1);
+                    self.mesh_data(data);
+                }
+                None => self.u8(0),
+            }
+        }
+
+        self.len(baked.materials.len());
+        for material in &baked.materials {
+            let desc = &material.desc;
+            self.str(&material.guid);
+            self.str(&desc.name);
+            for texture in [&desc.albedo, &desc.normal, &desc.metallic, &desc.ao] {
+                self.opt_str(texture.as_deref());
+            }
+        }
+    }
+
+    fn mesh_data(&mut self, data: &MeshData) {
+        self.str(&data.name);
+        self.u32(data.vertex_count);
+        self.len(data.vertex_descriptors.len());
+        for desc in &data.vertex_descriptors {
+            self.u8(desc.attribute.into());
+            self.u8(desc.stream);
+            self.u8(desc.offset);
+            self.u8(desc.format.into());
+            self.u8(desc.dimension);
+        }
+        self.blob(&data.vertices);
+
+        self.u8(match data.indices {
+            Indices::U16(_) => 0,
+            Indices::U32(_) => 1,
+        });
+        self.blob(data.indices.as_bytes());
+
+        self.len(data.sub_meshes.len());
+        for sub_mesh in &data.sub_meshes {
+            self.u32(sub_mesh.first_byte);
+            self.u32(sub_mesh.index_count);
+            self.i32(sub_mesh.base_vertex);
+            self.u32(sub_mesh.topology);
+        }
+        self.point(data.aabb.min);
+        self.point(data.aabb.max);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn is_end(&self) -> bool {
+        self.pos == self.bytes.len()
+    }
+
+    fn take(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| anyhow::anyhow!("烘焙包数据不完整，偏移 {}", self.pos))?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        Ok(self.take(N)?.try_into()?)
+    }
+
+    fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn bool(&mut self) -> anyhow::Result<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            v => Err(anyhow::anyhow!("烘焙包中无效的布尔值 {}", v)),
+        }
+    }
+
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> anyhow::Result<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> anyhow::Result<i32> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    fn i64(&mut self) -> anyhow::Result<i64> {
+        Ok(i64::from_le_bytes(self.array()?))
+    }
+
+    fn f32(&mut self) -> anyhow::Result<f32> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    fn len(&mut self) -> anyhow::Result<usize> {
+        Ok(self.u32()? as usize)
+    }
+
+    fn index(&mut self) -> anyhow::Result<Option<u32>> {
+        let index = self.u32()?;
+        Ok((index != NONE_INDEX).then_some(index))
+    }
+
+    fn blob(&mut self) -> anyhow::Result<&'a [u8]> {
+        let len = usize::try_from(self.u64()?)?;
+        self.take(len)
+    }
+
+    fn str(&mut self) -> anyhow::Result<String> {
+        let len = self.len()?;
+        Ok(std::str::from_utf8(self.take(len)?)?.to_string())
+    }
+
+    fn opt_str(&mut self) -> anyhow::Result<Option<String>> {
+        if self.bool()? { Ok(Some(self.str()?)) } else { Ok(None) }
+    }
+
+    fn point(&mut self) -> anyhow::Result<Point3<f32>> {
+        Ok(Point3::new(self.f32()?, self.f32()?, self.f32()?))
+    }
+
+    // 按记录的数量读取，数量来自文件，不直接用于预分配
+    fn list<T>(&mut self, mut read: impl FnMut(&mut Self) -> anyhow::Result<T>) -> anyhow::Result<Vec<T>> {
+        let len = self.len()?;
+        let mut items = Vec::with_capacity(len.min(self.bytes.len() - self.pos));
+        for _ in 0..len {
+            items.push(read(self)?);
+        }
+        Ok(items)
+    }
+
+    fn read_scene(&mut self) -> anyhow::Result<BakedScene> {
+        let entities = self.list(|r| {
+            let id = r.u32()?;
+            let visible = r.bool()?;
+            let world = if r.bool()? {
+                let mut world = [[0.0; 4]; 4];
+                for v in world.iter_mut().flatten() {
+                    *v = r.f32()?;
+                }
+                Some(world)
+            } else {
+                None
+            };
+            Ok(BakedEntity {
+                id,
+                world,
+                visible,
+                mesh: r.index()?,
+                materials: r.list(Self::index)?,
+            })
+        })?;
+
+        let meshes = self.list(|r| {
+            let reference = UnityReference {
+                file_id: r.i64()?,
+                guid: r.str()?,
+                ref_type: r.i32()?,
+            };
+            let data = if r.bool()? { Some(r.mesh_data()?) } else { None };
+            Ok(BakedMesh { reference, data })
+        })?;
+
+        let materials = self.list(|r| {
+            Ok(BakedMaterial {
+                guid: r.str()?,
+                desc: MaterialDesc {
+                    name: r.str()?,
+                    albedo: r.opt_str()?,
+                    normal: r.opt_str()?,
+                    metallic: r.opt_str()?,
+                    ao: r.opt_str()?,
+                },
+            })
+        })?;
+
+        for entity in &entities {
+            let out_of_range = entity.mesh.is_some_and(|i| i as usize >= meshes.len())
+                || entity.materials.iter().flatten().any(|&i| i as usize >= materials.len());
+            if out_of_range {
+                anyhow::bail!("实体 {} 引用了不存在的 mesh 或材质", entity.id);
+            }
+        }
+
+        Ok(BakedScene { entities, meshes, materials })
+    }
+
+    fn mesh_data(&mut self) -> anyhow::Result<MeshData> {
+        let name = self.str()?;
+        let vertex_count = self.u32()?;
+        let vertex_descriptors = self.list(|r| {
+            Ok(UnityVertexAttributeDescriptor {
+                attribute: UnityVertexAttribute::from_u8(r.u8()?)?,
+                stream: r.u8()?,
+                offset: r.u8()?,
+                format: UnityVertexFormat::from_u8(r.u8()?)?,
+                dimension: r.u8()?,
+            })
+        })?;
+        let vertices = self.blob()?.to_vec();
+
+        let index_format = self.u8()?;
+        let index_bytes = self.blob()?;
+        let indices = match index_format {
+            0 if index_bytes.len() % 2 == 0 => Indices::U16(
+                index_bytes.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]])).collect(),
+            ),
+            1 if index_bytes.len() % 4 == 0 => Indices::U32(
+                index_bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect(),
+            ),
+            _ => anyhow::bail!("mesh {} 的索引数据无效", name),
+        };
+
+        let sub_meshes = self.list(|r| {
+            Ok(SubMesh {
+                first_byte: r.u32()?,
+                index_count: r.u32()?,
+                base_vertex: r.i32()?,
+                topology: r.u32()?,
+            })
+        })?;
+        let aabb = AABB::new(self.point()?, self.point()?);
+
+        // CRC 只能发现意外损坏，上传前按 .asset 的规则检查
+        let data = MeshData { name, vertices, vertex_count, vertex_descriptors, indices, sub_meshes, aabb };
+        data.validate()?;
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::Entity;
+    use crate::resource::AssetManifest;
+
+    const MESH_GUID: &str = "6f1d2c3b4a5968778695a4b3c2d1e0f1";
+    const MAT_GUID: &str = "a1b2c3d4e5f60718293a4b5c6d7e8f90";
+    // unity 内置的白色贴图，加载时不需要读取文件
+    const TEX_GUID: &str = "0000000000000000f000000000000000";
+    const MISSING_GUID: &str = "ffffffffffffffffffffffffffffffff";
+
+    // 根节点带旋转，子节点带 mesh 与两个材质槽位（第二个找不到），另一个子节点是光照；
+    // 隐藏的根节点下挂一个内置立方体，另有一个根节点挂内置球体
+    fn scene() -> String {
+        format!("%YAML 1.1
+--- !u!1 &10
+GameObject:
+  m_Component:
+  - component: {{fileID: 11}}
+  m_Layer: 0
+  m_Name: Root
+  m_IsActive: 1
+--- !u!4 &11
+Transform:
+  m_GameObject: {{fileID: 10}}
+  m_LocalRotation: {{x: 0, y: 0.38268343, z: 0, w: 0.9238795}}
+  m_LocalPosition: {{x: 1, y: 2, z: 3}}
+  m_LocalScale: {{x: 2, y: 2, z: 2}}
+  m_Children:
+  - {{fileID: 21}}
+  - {{fileID: 61}}
+  m_Father: {{fileID: 0}}
+--- !u!1 &20
+GameObject:
+  m_Component:
+  - component: {{fileID: 21}}
+  - component: {{fileID: 22}}
+  - component: {{fileID: 23}}
+  m_Layer: 0
+  m_Name: Child
+  m_IsActive: 1
+--- !u!4 &21
+Transform:
+  m_GameObject: {{fileID: 20}}
+  m_LocalRotation: {{x: 0, y: 0, z: 0, w: 1}}
+  m_LocalPosition: {{x: 0, y: 1, z: -4}}
+  m_LocalScale: {{x: 1, y: 1, z: 1}}
+  m_Children: []
+  m_Father: {{fileID: 11}}
+--- !u!33 &22
+MeshFilter:
+  m_GameObject: {{fileID: 20}}
+  m_Mesh: {{fileID: 4300000, guid: {MESH_GUID}, type: 2}}
+--- !u!23 &23
+MeshRenderer:
+  m_GameObject: {{fileID: 20}}
+  m_Enabled: 1
+  m_Materials:
+  - {{fileID: 2100000, guid: {MAT_GUID}, type: 2}}
+  - {{fileID: 2100000, guid: {MISSING_GUID}, type: 2}}
+--- !u!1 &30
+GameObject:
+  m_Component:
+  - component: {{fileID: 31}}
+  m_Layer: 0
+  m_Name: Hidden
+  m_IsActive: 0
+--- !u!4 &31
+Transform:
+  m_GameObject: {{fileID: 30}}
+  m_LocalRotation: {{x: 0, y: 0, z: 0, w: 1}}
+  m_LocalPosition: {{x: 5, y: 0, z: 0}}
+  m_LocalScale: {{x: 1, y: 1, z: 1}}
+  m_Children:
+  - {{fileID: 41}}
+  m_Father: {{fileID: 0}}
+--- !u!1 &40
+GameObject:
+  m_Component:
+  - component: {{fileID: 41}}
+  - component: {{fileID: 42}}
+  - component: {{fileID: 43}}
+  m_Layer: 0
+  m_Name: Cube
+  m_IsActive: 1
+--- !u!4 &41
+Transform:
+  m_GameObject: {{fileID: 40}}
+  m_LocalRotation: {{x: 0, y: 0, z: 0, w: 1}}
+  m_LocalPosition: {{x: 0, y: 0, z: 1}}
+  m_LocalScale: {{x: 1, y: 1, z: 1}}
+  m_Children: []
+  m_Father: {{fileID: 31}}
+--- !u!33 &42
+MeshFilter:
+  m_GameObject: {{fileID: 40}}
+  m_Mesh: {{fileID: 10202, guid: 0000000000000000e000000000000000, type: 0}}
+--- !u!23 &43
+MeshRenderer:
+  m_GameObject: {{fileID: 40}}
+  m_Enabled: 1
+  m_Materials:
+  - {{fileID: 2100000, guid: {MAT_GUID}, type: 2}}
+--- !u!1 &50
+GameObject:
+  m_Component:
+  - component: {{fileID: 51}}
+  - component: {{fileID: 52}}
+  - component: {{fileID: 53}}
+  m_Layer: 0
+  m_Name: Sphere
+  m_IsActive: 1
+--- !u!4 &51
+Transform:
+  m_GameObject: {{fileID: 50}}
+  m_LocalRotation: {{x: 0, y: 0, z: 0, w: 1}}
+  m_LocalPosition: {{x: -3, y: 0, z: 2}}
+  m_LocalScale: {{x: 1, y: 1, z: 1}}
+  m_Children: []
+  m_Father: {{fileID: 0}}
+--- !u!33 &52
+MeshFilter:
+  m_GameObject: {{fileID: 50}}
+  m_Mesh: {{fileID: 10207, guid: 0000000000000000e000000000000000, type: 0}}
+--- !u!23 &53
+MeshRenderer:
+  m_GameObject: {{fileID: 50}}
+  m_Enabled: 1
+  m_Materials:
+  - {{fileID: 2100000, guid: {MAT_GUID}, type: 2}}
+--- !u!1 &60
+GameObject:
+  m_Component:
+  - component: {{fileID: 61}}
+  - component: {{fileID: 62}}
+  m_Layer: 0
+  m_Name: Lamp
+  m_IsActive: 1
+--- !u!4 &61
+Transform:
+  m_GameObject: {{fileID: 60}}
+  m_LocalRotation: {{x: 0, y: 0, z: 0, w: 1}}
+  m_LocalPosition: {{x: 0, y: 3, z: 0}}
+  m_LocalScale: {{x: 1, y: 1, z: 1}}
+  m_Children: []
+  m_Father: {{fileID: 11}}
+--- !u!108 &62
+Light:
+  m_GameObject: {{fileID: 60}}
+  m_Enabled: 1
+")
+    }
+
+    // 一个三角形，只有 position 通道
+    fn triangle_asset() -> String {
+        let mut vertices = Vec::new();
+        for v in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            vertices.extend_from_slice(&v.to_le_bytes());
+        }
+        let mut channels = String::from("    - stream: 0\n      offset: 0\n      format: 0\n      dimension: 3\n");
+        for _ in 1..14 {
+            channels.push_str("    - stream: 0\n      offset: 0\n      format: 0\n      dimension: 0\n");
+        }
+        format!(
+            "Mesh:\n  m_Name: Triangle\n  m_SubMeshes:\n  - serializedVersion: 2\n    firstByte: 0\n    indexCount: 3\n    topology: 0\n    baseVertex: 0\n    firstVertex: 0\n    vertexCount: 3\n  m_IndexFormat: 0\n  m_IndexBuffer: 000001000200\n  m_LocalAABB:\n    m_Center: {{x: 0.5, y: 0.5, z: 0}}\n    m_Extent: {{x: 0.5, y: 0.5, z: 0}}\n  m_VertexData:\n    serializedVersion: 3\n    m_VertexCount: 3\n    m_Channels:\n{}    m_DataSize: {}\n    _typelessdata: {}\n",
+            channels, vertices.len(), hex::encode(&vertices),
+        )
+    }
+
+    fn material_asset() -> String {
+        format!("Material:
+  m_Name: Brick
+  m_SavedProperties:
+    serializedVersion: 3
+    m_TexEnvs:
+      _MainTex:
+        m_Texture: {{fileID: 2800000, guid: {TEX_GUID}, type: 3}}
+        m_Scale: {{x: 1, y: 1}}
+        m_Offset: {{x: 0, y: 0}}
+    m_Ints: {{}}
+    m_Colors: {{}}
+")
+    }
+
+    fn assets() -> HashMap<String, Vec<u8>> {
+        HashMap::from([
+            (MESH_GUID.to_string(), triangle_asset().into_bytes()),
+            (MAT_GUID.to_string(), material_asset().into_bytes()),
+        ])
+    }
+
+    fn bake() -> BakedScene {
+        let objects = collect_scene_objects(&UnityScene::from_text(&scene())).unwrap();
+        let assets = assets();
+        bake_objects(&objects, |guid| Ok(assets.get(guid).cloned())).unwrap()
+    }
+
+    // 加载完成后每个实体在 Scene 与 ResourceManager 中的状态，世界矩阵单独按误差比较
+    #[derive(Debug, PartialEq)]
+    struct LoadedEntity {
+        id: u32,
+        visible: bool,
+        mesh: Option<LoadedMesh>,
+        materials: Option<Vec<Option<MaterialId>>>,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct LoadedMesh {
+        id: MeshId,
+        vertex_count: u32,
+        index_count: u32,
+        sub_meshes: Vec<SubMesh>,
+        aabb: AABB,
+    }
+
+    // 用 noop 后端走一遍运行时的加载流程
+    fn load(source: SceneSource) -> (Scene, ResourceManager) {
+        let (device, queue) = wgpu::Device::noop(&Default::default());
+        let config = SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            width: 800,
+            height: 600,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        let mut scene = Scene::new(&device, &config, 16);
+        let mut resource_manager = ResourceManager::new(&device, &queue);
+        resource_manager.set_manifest(AssetManifest::from_files(assets()));
+        pollster::block_on(source.load_into(&device, &queue, &mut scene, &mut resource_manager, &config)).unwrap();
+        (scene, resource_manager)
+    }
+
+    fn loaded_entities(scene: &Scene, resource_manager: &ResourceManager) -> Vec<LoadedEntity> {
+        scene
+            .entities
+            .iter()
+            .map(|entity| LoadedEntity {
+                id: entity.id(),
+                visible: scene.is_display_by_logic(entity),
+                mesh: resource_manager.get_mesh(entity).map(|mesh| LoadedMesh {
+                    id: mesh.id.clone(),
+                    vertex_count: mesh.vertex_count,
+                    index_count: mesh.index_count,
+                    sub_meshes: mesh.sub_meshes.clone(),
+                    aabb: mesh.aabb,
+                }),
+                materials: resource_manager.get_materials(entity).map(|slots| {
+                    slots.iter().map(|slot| slot.as_ref().map(|material| material.id.clone())).collect()
+                }),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_package_matches_yaml_scene() {
+        let (yaml_scene, yaml_resources) = load(SceneSource::Unity(Box::new(UnityScene::from_text(&scene()))));
+        let baked = read_package(&write_package(&bake())).unwrap();
+        let (baked_scene, baked_resources) = load(SceneSource::Baked(baked));
+
+        let yaml_entities = loaded_entities(&yaml_scene, &yaml_resources);
+        assert_eq!(yaml_entities, loaded_entities(&baked_scene, &baked_resources));
+
+        for entity in &yaml_scene.entities {
+            let expected = yaml_scene.transform_system.get_world_matrix(*entity);
+            let actual = baked_scene.transform_system.get_world_matrix(*entity);
+            assert_eq!(expected.is_some(), actual.is_some(), "{:?}", entity);
+            let (Some(expected), Some(actual)) = (expected, actual) else {
+                continue;
+            };
+            let (e, a): (&[[f32; 4]; 4], &[[f32; 4]; 4]) = (expected.as_ref(), actual.as_ref());
+            for (e, a) in e.iter().flatten().zip(a.iter().flatten()) {
+                assert!((e - a).abs() < 1e-5, "{:?}: {:?} != {:?}", entity, expected, actual);
+            }
+        }
+
+        let entity = |id: u32| yaml_entities.iter().find(|entity| entity.id == id).unwrap();
+        // 每个实体只登记一次
+        assert_eq!(yaml_entities.len(), 6);
+        // 子节点跟随父节点的平移与缩放：y = 2 + 2 * 1
+        let child = yaml_scene.transform_system.get_world_matrix(Entity::new(20)).unwrap();
+        assert!((child.w.y - 4.0).abs() < 1e-5, "{:?}", child);
+        // 光照只登记实体，没有世界矩阵与 mesh
+        assert!(yaml_scene.transform_system.get_world_matrix(Entity::new(60)).is_none());
+        assert_eq!(entity(60).mesh, None);
+        // 隐藏的父节点带着子节点一起隐藏
+        assert!(!entity(40).visible);
+        assert!(entity(50).visible);
+
+        // .asset 中的 mesh 与缺失的材质槽位
+        let mesh = entity(20).mesh.as_ref().unwrap();
+        assert_eq!((mesh.vertex_count, mesh.index_count), (3, 3));
+        assert_eq!(entity(20).materials, Some(vec![Some(MAT_GUID.to_string()), None]));
+        // 两个内置 mesh 共用 guid，但不能合并
+        assert_ne!(entity(40).mesh.as_ref().unwrap().id, entity(50).mesh.as_ref().unwrap().id);
+    }
+
+    #[test]
+    fn test_builtin_meshes_keep_file_id() {
+        let baked = bake();
+        let mesh = |id: u32| {
+            let entity = baked.entities.iter().find(|entity| entity.id == id).unwrap();
+            &baked.meshes[entity.mesh.unwrap() as usize]
+        };
+        assert_eq!(baked.meshes.len(), 3);
+        assert_eq!(mesh(40).reference.file_id, 10202);
+        assert_eq!(mesh(50).reference.file_id, 10207);
+        assert!(mesh(40).data.is_none() && mesh(50).data.is_none());
+        assert_eq!(mesh(20).data, Some(MeshData::from_unity_asset(&triangle_asset()).unwrap()));
+
+        // 材质只保存一份，找不到的槽位为空
+        let child = baked.entities.iter().find(|entity| entity.id == 20).unwrap();
+        let slots = baked.material_slots(child);
+        let (guid, desc) = slots[0].unwrap();
+        assert_eq!(guid, MAT_GUID);
+        assert_eq!(desc.albedo.as_deref(), Some(TEX_GUID));
+        assert!(slots[1].is_none());
+        assert_eq!(baked.materials.len(), 1);
+    }
+
+    #[test]
+    fn test_package_round_trip() {
+        let baked = bake();
+        assert_eq!(read_package(&write_package(&baked)).unwrap(), baked);
+    }
+
+    #[test]
+    fn test_missing_mesh_fails() {
+        let objects = collect_scene_objects(&UnityScene::from_text(&scene())).unwrap();
+        assert!(bake_objects(&objects, |_| Ok(None)).is_err());
+    }
+
+    #[test]
+    fn test_rejects_corrupted_package() {
+        let bytes = write_package(&bake());
+
+        let mut corrupted = bytes.clone();
+        *corrupted.last_mut().unwrap() ^= 0xFF;
+        assert!(read_package(&corrupted).unwrap_err().to_string().contains("校验"));
+
+        let mut old_version = bytes.clone();
+        old_version[4..8].copy_from_slice(&(BAKE_VERSION + 1).to_le_bytes());
+        assert!(read_package(&old_version).unwrap_err().to_string().contains("重新烘焙"));
+
+        let mut wrong_magic = bytes.clone();
+        wrong_magic[0] = b'X';
+        assert!(read_package(&wrong_magic).is_err());
+
+        assert!(read_package(&bytes[..bytes.len() - 1]).is_err());
+        assert!(read_package(&bytes[..HEADER_SIZE - 1]).is_err());
+    }
+
+    // CRC 可以重新计算，手工修改过的 mesh 数据在读取时按 .asset 的规则拒绝
+    #[test]
+    fn test_rejects_invalid_mesh_data() {
+        let tampered = |edit: fn(&mut MeshData)| {
+            let mut baked = bake();
+            edit(baked.meshes.iter_mut().find_map(|mesh| mesh.data.as_mut()).unwrap());
+            read_package(&write_package(&baked))
+        };
+        assert!(tampered(|_| {}).is_ok());
+        assert!(tampered(|data| data.sub_meshes[0].index_count += 3).is_err());
+        assert!(tampered(|data| data.sub_meshes[0].first_byte = 1).is_err());
+        assert!(tampered(|data| data.sub_meshes[0].base_vertex = 1).is_err());
+        assert!(tampered(|data| data.vertices.truncate(data.vertices.len() - 4)).is_err());
+        assert!(tampered(|data| data.vertex_count += 1).is_err());
+        assert!(tampered(|data| data.vertex_descriptors[0].format = UnityVertexFormat::Zero).is_err());
+        assert!(tampered(|data| data.vertex_descriptors[4].attribute = UnityVertexAttribute::TexCoord1).is_err());
+        assert!(UnityVertexFormat::from_u8(12).is_err());
+        assert!(UnityVertexAttribute::from_u8(14).is_err());
+    }
+
+    #[test]
+    fn test_package_path() {
+        assert_eq!(package_path("Scenes/Level_1.unity"), "Scenes/Level_1.bake");
+        assert!(is_package("Scenes/Level_1.bake"));
+        assert!(!is_package("Scenes/Level_1.unity"));
+    }
+
+    #[test]
+    fn test_cancel_before_first_stage() {
+        let mut stages = Vec::new();
+        let baked = pollster::block_on(bake_scene_with_progress("Scenes/Level_1.unity", |stage| {
+            stages.push(stage);
+            false
+        }))
+        .unwrap();
+        assert!(baked.is_none());
+        assert_eq!(stages, [BakeStage::Manifest]);
+        assert_eq!(serde_json::to_string(&BakeStage::Prefabs).unwrap(), r#""prefabs""#);
+    }
+}
@@ -0,0 +1,42 @@
+// 离线烘焙场景：cargo run --bin bake -- <场景路径> [输出路径]
+// 场景路径相对 GAME_PROJECT_PATH，默认输出到同目录下的 .bake 文件
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> anyhow::Result<()> {
+    use std::path::PathBuf;
+    use log::info;
+    use wgpu_renderer::bake::{bake_scene, package_path, write_package};
+
+    wgpu_renderer::init_logger();
+    dotenv::dotenv().ok();
+
+    let mut args = std::env::args().skip(1);
+    let scene_path = args
+        .next()
+        .or_else(|| std::env::var("SCENE_PATH").ok())
+        .ok_or_else(|| anyhow::anyhow!("用法: bake <场景路径> [输出路径]"))?;
+    let output = match args.next() {
+        Some(output) => PathBuf::from(output),
+        None => PathBuf::from(std::env::var("GAME_PROJECT_PATH")?).join(package_path(&scene_path)),
+    };
+
+    let baked = pollster::block_on(bake_scene(&scene_path))?;
+    let bytes = write_package(&baked);
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&output, &bytes)?;
+
+    info!(
+        "Baked {}: {} entities, {} meshes, {} materials, {} bytes -> {}",
+        scene_path,
+        baked.entities.len(),
+        baked.meshes.len(),
+        baked.materials.len(),
+        bytes.len(),
+        output.display(),
+    );
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -15,447 +15,6 @@ pub struct Vertex {
     tex_coords: [f16; 2],// uv坐标
 }
 
-// #[repr(C)]
-// #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-// pub struct VertexTexFloat32 {
-//     position: [f32; 3],// 4 * 3
-//     normal: [f16; 4], // 法线 2*4
-//     // 切线
-//     tangent: [f16; 4],//2*4
-//     tex_coords: [f16; 2],// uv坐标2*2
-// }
-
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct VertexFloat32 {    // sizeof 48
-    position: [f32; 3],
-    normal: [f32; 3], // 法线
-    // 切线
-    tangent: [f32; 4],
-    tex_coords: [f32; 2],// uv坐标
-}
-
-
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct VertexColorUVx3Float32 {    // sizeof 80
-    position: [f32; 3],
-    normal: [f32; 3], // 法线
-    // 切线
-    tangent: [f32; 4],
-    color: [f32; 4],
-    tex_coords: [f32; 2],// uv0坐标
-    uv_coords: [f32; 2],// uv1坐标
-    uv1_coords: [f32; 2],// uv2坐标
-}
-
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct VertexFloat32x6 {    // sizeof 88
-    position: [f32; 3],
-    normal: [f32; 3], // 法线
-    // 切线
-    tangent: [f32; 4],
-    color: [f32; 4],
-    tex_coords: [f32; 4],// uv0坐标
-    uv_coords: [f32; 4],// uv1坐标
-}
-
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct VertexColorUVFloat32 {    // sizeof 64
-    position: [f32; 3],
-    normal: [f32; 3], // 法线
-    // 切线
-    tangent: [f32; 4],
-    color: [f32; 4],
-    tex_coords: [f32; 2],// uv0坐标
-}
-
-// SM_House05_Roof1 44
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct VertexFloat16x4Float {
-    position: [f32; 3],
-    normal: [f16; 4], // 法线
-    // 切线
-    tangent: [f16; 4],
-    tex_coords: [f16; 2],// uv坐标
-    uv_coords: [f32; 2],// uv1坐标
-    uv1_coords: [f16; 2],// uv2坐标
-}
-
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct VertexTexUvFloat32 { // size_of: 56
-    position: [f32; 3],
-    normal: [f32; 3], // 法线
-    // 切线
-    tangent: [f32; 4],
-    tex_coords: [f32; 2],// uv0坐标
-    uv_coords: [f32; 2],// uv1坐标
-}
-
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct VertexColorFloat3x4U8 {    // size_of: 52
-    position: [f32; 3],
-    normal: [f32; 3], // 法线
-    // 切线
-    tangent: [f32; 4],
-    color: [u8; 4], // 颜色
-    tex_coords: [f32; 2],// uv0坐标
-}
-
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct VertexColor {    // size_of: 36
-    position: [f32; 3],
-    normal: [f16; 4], // 法线
-    // 切线
-    tangent: [f16; 4],
-    color: [u8; 4], // 颜色
-    tex_coords: [f16; 2],// uv0坐标
-}
-
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct VertexUvFloat1632 { // size_of: 40
-    position: [f32; 3],
-    normal: [f16; 4], // 法线
-    // 切线
-    tangent: [f16; 4],
-    tex_coords: [f16; 2],// uv0坐标
-    uv_coords: [f32; 2],// uv1坐标
-}
-
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct VertexColorUv32 { // size_of: 78
-    position: [f32; 3],
-    normal: [f32; 3], // 法线
-    tangent: [f32; 4],
-    color: [u8; 4], // 颜色
-    tex_coords: [f32; 4],// uv0坐标
-    uv_coords: [f32; 4],// uv1坐标
-}
-
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct VertexColorUv32f { // size_of: 72
-    position: [f32; 3],   // offset 0,  12 bytes
-    normal: [f32; 3],     // offset 12, 12 bytes
-    tangent: [f32; 4],    // offset 24, 16 bytes
-    color: [f32; 4],      // offset 40, 16 bytes
-    tex_coords: [f32; 2], // offset 56, 8 bytes
-    tex_coords1: [f32; 2],// offset 64, 8 bytes
-}
-
-pub trait IVertex {
-    fn flip_z_axis(&mut self);
-}
-
-impl IVertex for Vertex {
-    fn flip_z_axis(&mut self) {
-        // 翻转Z轴（位置）
-        self.position[2] = -self.position[2];
-        // 翻转法线Z
-        self.normal[2] = f16::from_f32(-self.normal[2].to_f32());
-
-        // 翻转切线Z和手性
-        self.tangent[2] = f16::from_f32(-self.tangent[2].to_f32());
-        self.tangent[3] = f16::from_f32(-self.tangent[3].to_f32());
-
-        let u = self.tex_coords[0].to_f32();
-        let v = self.tex_coords[1].to_f32();
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.tex_coords[0] = f16::from_f32(u.fract().abs());
-        self.tex_coords[1] = f16::from_f32(v.fract().abs());
-    }
-}
-impl IVertex for VertexFloat16x4Float {
-    fn flip_z_axis(&mut self) {
-        // 翻转Z轴（位置）
-        self.position[2] = -self.position[2];
-        // 翻转法线Z
-        self.normal[2] = f16::from_f32(-self.normal[2].to_f32());
-
-        // 翻转切线Z和手性
-        self.tangent[2] = f16::from_f32(-self.tangent[2].to_f32());
-        self.tangent[3] = f16::from_f32(-self.tangent[3].to_f32());
-
-        let u = self.tex_coords[0].to_f32();
-        let v = self.tex_coords[1].to_f32();
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.tex_coords[0] = f16::from_f32(u.fract().abs());
-        self.tex_coords[1] = f16::from_f32(v.fract().abs());
-
-        let u0 = self.uv_coords[0];
-        let v0 = self.uv_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.uv_coords[0] = u0.fract().abs();
-        self.uv_coords[1] = v0.fract().abs();
-
-        let u1 = self.uv1_coords[0].to_f32();
-        let v1 = self.uv1_coords[1].to_f32();
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.uv1_coords[0] = f16::from_f32(u1.fract().abs());
-        self.uv1_coords[1] = f16::from_f32(v1.fract().abs());
-
-    }
-}
-impl IVertex for VertexColor {
-    fn flip_z_axis(&mut self) {
-        // 翻转Z轴（位置）
-        self.position[2] = -self.position[2];
-        // 翻转法线Z
-        self.normal[2] = f16::from_f32(-self.normal[2].to_f32());
-
-        // 翻转切线Z和手性
-        self.tangent[2] = f16::from_f32(-self.tangent[2].to_f32());
-        self.tangent[3] = f16::from_f32(-self.tangent[3].to_f32());
-
-        let u = self.tex_coords[0].to_f32();
-        let v = self.tex_coords[1].to_f32();
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.tex_coords[0] = f16::from_f32(u.fract().abs());
-        self.tex_coords[1] = f16::from_f32(v.fract().abs());
-    }
-}
-
-impl IVertex for VertexColorFloat3x4U8 {
-    fn flip_z_axis(&mut self) {
-        // 翻转Z轴（位置）
-        self.position[2] = -self.position[2];
-        // 翻转法线Z
-        self.normal[2] = -self.normal[2];
-
-
-        // 翻转切线Z和手性
-        self.tangent[2] = -self.tangent[2];
-        self.tangent[3] = -self.tangent[3];
-
-        let u = self.tex_coords[0];
-        let v = self.tex_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.tex_coords[0] = u.fract().abs();
-        self.tex_coords[1] = v.fract().abs();
-    }
-}
-
-impl IVertex for VertexColorUVx3Float32 {
-    fn flip_z_axis(&mut self) {
-        // 翻转Z轴（位置）
-        self.position[2] = -self.position[2];
-        // 翻转法线Z
-        self.normal[2] = -self.normal[2];
-
-        // 翻转切线Z和手性
-        self.tangent[2] = -self.tangent[2];
-        self.tangent[3] = -self.tangent[3];
-
-        let u = self.tex_coords[0];
-        let v = self.tex_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.tex_coords[0] = u.fract().abs();
-        self.tex_coords[1] = v.fract().abs();
-
-        let u1 = self.tex_coords[0];
-        let v1 = self.tex_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.uv_coords[0] = u1.fract().abs();
-        self.uv_coords[1] = v1.fract().abs();
-
-        let u2 = self.tex_coords[0];
-        let v2 = self.tex_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.uv1_coords[0] = u2.fract().abs();
-        self.uv1_coords[1] = v2.fract().abs();
-    }
-}
-impl IVertex for VertexFloat32x6 {
-    fn flip_z_axis(&mut self) {
-        // 翻转Z轴（位置）
-        self.position[2] = -self.position[2];
-        // 翻转法线Z
-        self.normal[2] = -self.normal[2];
-
-        // 翻转切线Z和手性
-        self.tangent[2] = -self.tangent[2];
-        self.tangent[3] = -self.tangent[3];
-
-        let u = self.tex_coords[0];
-        let v = self.tex_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.tex_coords[0] = u.fract().abs();
-        self.tex_coords[1] = v.fract().abs();
-        
-        // todo uv 4个如何处理？
-        let u1 = self.tex_coords[0];
-        let v1 = self.tex_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.uv_coords[0] = u1.fract().abs();
-        self.uv_coords[1] = v1.fract().abs();
-    }
-}
-
-impl IVertex for VertexColorUVFloat32 {
-    fn flip_z_axis(&mut self) {
-        // 翻转Z轴（位置）
-        self.position[2] = -self.position[2];
-        // 翻转法线Z
-        self.normal[2] = -self.normal[2];
-
-        // 翻转切线Z和手性
-        self.tangent[2] = -self.tangent[2];
-        self.tangent[3] = -self.tangent[3];
-
-        let u = self.tex_coords[0];
-        let v = self.tex_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.tex_coords[0] = u.fract().abs();
-        self.tex_coords[1] = v.fract().abs();
-    }
-}
-
-impl IVertex for VertexFloat32 {
-    fn flip_z_axis(&mut self) {
-        // 翻转Z轴（位置）
-        self.position[2] = -self.position[2];
-        // 翻转法线Z
-        self.normal[2] = -self.normal[2];
-
-        // 翻转切线Z和手性
-        self.tangent[2] = -self.tangent[2];
-        self.tangent[3] = -self.tangent[3];
-
-        let u = self.tex_coords[0];
-        let v = self.tex_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.tex_coords[0] = u.fract().abs();
-        self.tex_coords[1] = v.fract().abs();
-    }
-}
-
-impl IVertex for VertexTexUvFloat32 {
-    fn flip_z_axis(&mut self) {
-        // 翻转Z轴（位置）
-        self.position[2] = -self.position[2];
-        // 翻转法线Z
-        self.normal[2] = -self.normal[2];
-
-        // 翻转切线Z和手性
-        self.tangent[2] = -self.tangent[2];
-        self.tangent[3] = -self.tangent[3];
-
-        let u = self.tex_coords[0];
-        let v = self.tex_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.tex_coords[0] = u.fract().abs();
-        self.tex_coords[1] = v.fract().abs();
-
-        let u1 = self.uv_coords[0];
-        let v1 = self.uv_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.uv_coords[0] = u1.fract().abs();
-        self.uv_coords[1] = v1.fract().abs();
-    }
-}
-impl IVertex for VertexUvFloat1632 {
-    fn flip_z_axis(&mut self) {
-        // 翻转Z轴（位置）
-        self.position[2] = -self.position[2];
-        // 翻转法线Z
-        self.normal[2] = -self.normal[2];
-
-        // 翻转切线Z和手性
-        self.tangent[2] = -self.tangent[2];
-        self.tangent[3] = -self.tangent[3];
-
-        let u = self.tex_coords[0].to_f32();
-        let v = self.tex_coords[1].to_f32();
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.tex_coords[0] = f16::from_f32(u.fract().abs());
-        self.tex_coords[1] = f16::from_f32(v.fract().abs());
-
-        let u1 = self.uv_coords[0];
-        let v1 = self.uv_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.uv_coords[0] = u1.fract().abs();
-        self.uv_coords[1] = v1.fract().abs();
-    }
-}
-
-impl IVertex for VertexColorUv32 {
-    fn flip_z_axis(&mut self) {
-        // 翻转Z轴（位置）
-        self.position[2] = -self.position[2];
-        // 翻转法线Z
-        self.normal[2] = -self.normal[2];
-
-        // 翻转切线Z和手性
-        self.tangent[2] = -self.tangent[2];
-        self.tangent[3] = -self.tangent[3];
-
-        let u = self.tex_coords[0];
-        let v = self.tex_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.tex_coords[0] = u.fract().abs();
-        self.tex_coords[1] = v.fract().abs();
-
-        let u1 = self.uv_coords[0];
-        let v1 = self.uv_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.uv_coords[0] = u1.fract().abs();
-        self.uv_coords[1] = v1.fract().abs();
-    }
-}
-impl IVertex for VertexColorUv32f {
-    fn flip_z_axis(&mut self) {
-        // 翻转Z轴（位置）
-        self.position[2] = -self.position[2];
-        // 翻转法线Z
-        self.normal[2] = -self.normal[2];
-
-        // 翻转切线Z和手性
-        self.tangent[2] = -self.tangent[2];
-        self.tangent[3] = -self.tangent[3];
-
-        let u = self.tex_coords[0];
-        let v = self.tex_coords[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.tex_coords[0] = u.fract().abs();
-        self.tex_coords[1] = v.fract().abs();
-
-        let u1 = self.tex_coords1[0];
-        let v1 = self.tex_coords1[1];
-
-        // 使用fract()获取小数部分，映射到[0,1]
-        self.tex_coords1[0] = u1.fract().abs();
-        self.tex_coords1[1] = v1.fract().abs();
-    }
-}
-
 impl Vertex {
     pub fn analyze_uv_pattern_by_normal(vertices: &[Vertex], indices: &[u16]) {
         let mut x_faces_uvs = Vec::new();
@@ -656,6 +215,17 @@ impl Transform {
         }
     }
 
+    // 直接使用已算好的矩阵作为局部矩阵，烘焙包中的世界矩阵使用
+    pub fn from_matrix(matrix: Matrix4<f32>) -> Self {
+        Self {
+            position: matrix.w.truncate(),
+            local_matrix: matrix,
+            world_matrix: matrix,
+            is_dirty: false,
+            ..Self::new()
+        }
+    }
+
     // 计算局部变换矩阵
     pub fn compute_local_matrix(&mut self) {
         if self.is_dirty {
@@ -12,10 +12,12 @@ mod mesh;
 mod map;
 mod frustum;
 mod stat;
+mod prefab;
+mod vertex;
+pub mod bake;
 
 use std::cell::RefCell;
 use log::{error, info, warn};
-use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
 use winit::{
@@ -36,7 +38,7 @@ use winit::window::WindowId;
 use crate::entity::{Entity, TransformSystem};
 use crate::materials::{Texture};
 use crate::ray::Ray;
-use crate::unity::UnityScene;
+use crate::bake::SceneSource;
 
 // 全局场景路径存储（用于 wasm 和本地环境）
 use std::sync::{OnceLock, Mutex};
@@ -173,23 +175,19 @@ impl State {
         // 做场景资源转换，所需资源
         // 获取场景路径（支持 wasm 通过 set_scene_path 设置，或从环境变量读取）
         let scene_path = get_scene_path();
-        let path = PathBuf::from(&scene_path);
 
         set_loading_state(SceneLoadingState::LoadingScene, 0.3, "Loading scene file...");
 
-        // 在 wasm 环境下使用 worker，在非 wasm 环境下使用正常方法
-        let mut unity_scene = {
-            let mut uns = UnityScene::new();
-            uns.from_str(path.clone()).await.map_err(|e| {
-                error!("Failed to load unity scene: {:?}", e);
-                e
-            })?
-        };
+        // .bake 为离线烘焙包，其余按 .unity 解析
+        let source = SceneSource::load(&scene_path).await.map_err(|e| {
+            error!("Failed to load unity scene: {:?}", e);
+            e
+        })?;
 
         set_loading_state(SceneLoadingState::LoadingScene, 0.4, "Scene file parsed");
 
         // 3. 加载资源
-        let mut scene = Scene::new(&device, &config, unity_scene.game_object_raw.len() * 2);
+        let mut scene = Scene::new(&device, &config, source.object_count() * 2);
         let mut resource_manager = ResourceManager::new(&device, &queue);
         set_loading_state(SceneLoadingState::LoadingScene, 0.45, "Creating scene structure...");
 
@@ -202,7 +200,7 @@ impl State {
 
         set_loading_state(SceneLoadingState::LoadingAssets, 0.6, "Loading scene assets...");
 
-        Scene::loading_scene(&device, &queue, &mut scene, &mut unity_scene, &mut resource_manager, &config).await.map_err(|e| {
+        source.load_into(&device, &queue, &mut scene, &mut resource_manager, &config).await.map_err(|e| {
             error!("Failed to load scene scene: {:?}", e);
             e
         })?;
@@ -335,18 +333,15 @@ impl State {
             &format!("Parsing scene file: {}...", scene_name)
         );
 
-        let mut unity_scene = {
-            let mut uns = UnityScene::new();
-            uns.from_str(path.clone()).await.map_err(|e| {
-                set_loading_state(
-                    SceneLoadingState::SceneParseError,
-                    0.20,
-                    &format!("Failed to parse scene: {}", e)
-                );
-                error!("Failed to load unity scene: {:?}", e);
-                e
-            })?
-        };
+        let source = SceneSource::load(&scene_path).await.map_err(|e| {
+            set_loading_state(
+                SceneLoadingState::SceneParseError,
+                0.20,
+                &format!("Failed to parse scene: {}", e)
+            );
+            error!("Failed to load unity scene: {:?}", e);
+            e
+        })?;
 
         self.scene.reload();
 
@@ -357,11 +352,10 @@ impl State {
             "Loading meshes, textures and materials..."
         );
 
-        Scene::loading_scene(
+        source.load_into(
             &self.device,
             &self.queue,
             &mut self.scene,
-            &mut unity_scene,
             &mut self.resource_manager,
             &self.config,
         )
@@ -840,6 +834,21 @@ pub fn run_web() -> Result<(), wasm_bindgen::JsValue> {
     Ok(())
 }
 
+/// 在专用 Worker 中烘焙 .unity 场景：按 `id` 发送 BakeStage 进度，最后发回烘焙包字节（见 wasm-worker 的消息协议），
+/// `cancel` 为主线程共享的取消标志，写入非 0 后在下一个阶段开始前停止
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub async fn worker_bake_scene(id: u32, scene_path: String, cancel: Option<js_sys::Int32Array>) {
+    console_error_panic_hook::set_once();
+    let task = wasm_worker::WorkerTask::new(id, cancel);
+    let result = bake::bake_scene_with_progress(&scene_path, |stage| task.progress(&stage)).await;
+    match result {
+        Ok(Some(baked)) => task.finish_bytes(&bake::write_package(&baked)),
+        Ok(None) => task.cancelled(),
+        Err(e) => task.finish(Err::<(), _>(format!("{:#}", e))),
+    }
+}
+
 #[wasm_bindgen]
 pub struct Commander{}
 
@@ -870,6 +879,16 @@ impl Commander {
         SCENE_PATH.get_or_init(|| path);
     }
 
+    /// 注册场景烘焙函数 `(scenePath) => Promise<Uint8Array>`，.unity 场景交给它在 Worker 中解析，
+    /// 主线程只负责上传 GPU 资源；未注册时在主线程解析
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen]
+    pub fn set_scene_baker(
+        #[wasm_bindgen(unchecked_param_type = "(scenePath: string) => Promise<Uint8Array>")] baker: js_sys::Function,
+    ) {
+        bake::set_scene_baker(Some(baker));
+    }
+
     /// 设置相机位置
     #[cfg(target_arch = "wasm32")]
     #[wasm_bindgen]
@@ -343,17 +343,49 @@ pub struct Material{
 
 }
 
-impl Material{
-    pub async fn from_unity_bytes(bytes: &[u8], id: &MaterialId, device: &Device, queue: &Queue, resource_manager: &mut ResourceManager) -> anyhow::Result<Self>{
+// 材质球中渲染用到的部分：名称与各贴图的 guid，不依赖 GPU，离线烘焙也使用
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaterialDesc {
+    pub name: String,
+    pub albedo: Option<String>,    // _MainTex
+    pub normal: Option<String>,    // _NormalMap
+    pub metallic: Option<String>,  // _MetallicSmoothness
+    pub ao: Option<String>,        // _EmissionMap
+}
+
+impl MaterialDesc {
+    pub fn from_unity_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
         let content = std::str::from_utf8(bytes)?;
         let mat = serde_yaml::from_str::<MatYaml>(content)?;
         let unity_material = mat.material;
         let tex_envs = unity_material.saved_properties.tex_envs;
-        let mut albedo_texture = None;
-        let mut normal_texture = None;
-        let mut metallic_texture = None;
-        let mut ao_texture = None;
+        let guid = |property: Option<TextureProperty>| property.and_then(|p| p.texture.guid);
+
+        Ok(Self {
+            name: unity_material.name,
+            albedo: guid(tex_envs.main_tex),
+            normal: guid(tex_envs.normal_map),
+            metallic: guid(tex_envs.metallic_smoothness),
+            ao: guid(tex_envs.m_texture),
+        })
+    }
+}
+
+impl Material{
+    pub async fn from_unity_bytes(bytes: &[u8], id: &MaterialId, device: &Device, queue: &Queue, resource_manager: &mut ResourceManager) -> anyhow::Result<Self>{
+        let desc = MaterialDesc::from_unity_bytes(bytes)?;
+        Self::from_desc(&desc, id, device, queue, resource_manager).await
+    }
 
+    // 没有贴图或者方块模式下使用白色贴图
+    async fn load_slot(guid: &Option<String>, block_mesh: bool, device: &Device, queue: &Queue, resource_manager: &mut ResourceManager) -> anyhow::Result<Arc<Texture>> {
+        match (guid, block_mesh) {
+            (Some(guid), false) => resource_manager.load_texture(device, queue, guid).await,
+            _ => Ok(resource_manager.get_white_texture()),
+        }
+    }
+
+    pub async fn from_desc(desc: &MaterialDesc, id: &MaterialId, device: &Device, queue: &Queue, resource_manager: &mut ResourceManager) -> anyhow::Result<Self>{
         let block_mesh = get_block_mesh();
 
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor{
@@ -370,77 +402,23 @@ impl Material{
             ..Default::default()
         });
 
-        let mut builder = MaterialLayoutBuilder::new();
+        println!("Loading texture {:?}...", &desc.albedo);
 
-        let mut entries = Vec::new();
+        let albedo_texture = Self::load_slot(&desc.albedo, block_mesh, device, queue, resource_manager).await?;
+        let normal_texture = Self::load_slot(&desc.normal, block_mesh, device, queue, resource_manager).await?;
+        let metallic_texture = Self::load_slot(&desc.metallic, block_mesh, device, queue, resource_manager).await?;
+        let ao_texture = Self::load_slot(&desc.ao, block_mesh, device, queue, resource_manager).await?;
 
-        albedo_texture = Some(resource_manager.get_white_texture());
-        
-        println!("Loading texture {:?}...", &tex_envs.main_tex);
-        
-        if let (Some(main_tex), false) = (tex_envs.main_tex, block_mesh) {
-            if let Some(guid) = main_tex.texture.guid {
-                println!("main_tex: {:?}", guid);
-                // let file_path = resource_manager.get_guid_file(&guid).unwrap();
-                // 临时设置主贴图
-                // let texture_bytes = load_binary(file_path).await?;
-                let texture = resource_manager.load_texture(device, queue, &guid).await?;
-                albedo_texture = Some(texture);
-            }
-        }
-
-        entries.push(wgpu::BindGroupEntry{
-            binding: builder.next_binding,
-            resource: wgpu::BindingResource::TextureView(&albedo_texture.as_ref().unwrap().view),
-        });
-        builder.add_texture();
-
-        normal_texture = Some(resource_manager.get_white_texture());
-
-        if let (Some(normal_map), false) = (tex_envs.normal_map, block_mesh) {
-            if let Some(guid) = normal_map.texture.guid {
-                let texture = resource_manager.load_texture(device, queue, &guid).await?;
-                normal_texture = Some(texture);
-            }
-        }
-
-        entries.push(wgpu::BindGroupEntry{
-            binding: builder.next_binding,
-            resource: wgpu::BindingResource::TextureView(&normal_texture.as_ref().unwrap().view),
-        });
-        builder.add_texture();
-
-        metallic_texture = Some(resource_manager.get_white_texture());
-
-        // 暂时创建白色的
-        if let (Some(metallic_smoothness), false) = (tex_envs.metallic_smoothness, block_mesh) {
-            if let Some(guid) = metallic_smoothness.texture.guid {
-                let texture = resource_manager.load_texture(device, queue, &guid).await?;
-                metallic_texture = Some(texture);
-            }
-        }
-        
-        entries.push(wgpu::BindGroupEntry{
-            binding: builder.next_binding,
-            resource: wgpu::BindingResource::TextureView(&metallic_texture.as_ref().unwrap().view),
-        });
-        builder.add_texture();
-
-        ao_texture = Some(resource_manager.get_white_texture());
-
-        if let (Some(m_texture), false) = (tex_envs.m_texture , block_mesh){
-            if let Some(guid) = m_texture.texture.guid {
-                let texture = resource_manager.load_texture(device, queue, &guid).await?;
-                ao_texture = Some(texture);
-            }
+        let mut builder = MaterialLayoutBuilder::new();
+        let mut entries = Vec::new();
+        for texture in [&albedo_texture, &normal_texture, &metallic_texture, &ao_texture] {
+            entries.push(wgpu::BindGroupEntry{
+                binding: builder.next_binding,
+                resource: wgpu::BindingResource::TextureView(&texture.view),
+            });
+            builder.add_texture();
         }
 
-        entries.push(wgpu::BindGroupEntry{
-            binding: builder.next_binding,
-            resource: wgpu::BindingResource::TextureView(&ao_texture.as_ref().unwrap().view),
-        });
-        builder.add_texture();
-
         entries.push(wgpu::BindGroupEntry{
             binding: builder.next_binding,
             resource: wgpu::BindingResource::Sampler(&sampler),
@@ -448,23 +426,23 @@ impl Material{
         builder.add_sampler();
 
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor{
-            label: Some(&format!("Material bind_group_layout : {}", unity_material.name)),
+            label: Some(&format!("Material bind_group_layout : {}", desc.name)),
             entries: &builder.entries,
         });
 
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor{
-            label: Some(&format!("Material bind_group : {}", unity_material.name)),
+            label: Some(&format!("Material bind_group : {}", desc.name)),
             layout: &bind_group_layout,
             entries: &entries,
         });
 
         Ok(Self{
             id: id.clone(),
-            name: unity_material.name,
-            albedo_texture,
-            normal_texture,
-            metallic_texture,
-            ao_texture,
+            name: desc.name.clone(),
+            albedo_texture: Some(albedo_texture),
+            normal_texture: Some(normal_texture),
+            metallic_texture: Some(metallic_texture),
+            ao_texture: Some(ao_texture),
             metallic: 0.0,
             roughness: 0.0,
             base_color: [1.0,1.0,1.0,1.0],
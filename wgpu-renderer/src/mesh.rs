@@ -1,13 +1,19 @@
+use std::ops::Range;
 use cgmath::{Matrix4, Point3, Transform};
-use wgpu::{BufferAddress, Device, SurfaceConfiguration};
+use wgpu::{Device, SurfaceConfiguration};
 use wgpu::util::DeviceExt;
-use crate::entity::{InstanceRaw, IVertex, Vertex, VertexBufferLayoutOwned, VertexColor, VertexColorFloat3x4U8, VertexColorUVFloat32, VertexColorUVx3Float32, VertexFloat32, VertexTexUvFloat32, VertexUvFloat1632, VertexFloat16x4Float, VertexFloat32x6, VertexColorUv32, VertexColorUv32f};
+use crate::entity::{InstanceRaw, VertexBufferLayoutOwned};
 use crate::materials::{Material, Texture};
 use crate::resource::MeshId;
 use crate::scene::Scene;
-use crate::unity::{Channel, MeshAsset, UnityVertexAttribute, UnityVertexAttributeDescriptor, UnityVertexFormat};
+use crate::unity::{Channel, MeshAsset, UnityReference, UnityVertexAttribute, UnityVertexAttributeDescriptor, UnityVertexFormat};
+use crate::vertex::{decode_vertices, MeshVertex};
 
-#[derive(Debug, Clone, Copy)]
+// unity 内置资源的 guid
+pub const BUILTIN_MESH_GUID: &str = "0000000000000000e000000000000000";
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct AABB {
     pub min: Point3<f32>,
     pub max: Point3<f32>,
@@ -84,7 +90,11 @@ pub struct Mesh{
 
     // 顶点数量
     pub vertex_count: u32,
+    pub index_format: wgpu::IndexFormat,
+    // index buffer 中的索引总数
     pub index_count: u32,
+    // 子网格，第 i 个对应 MeshRenderer 的第 i 个材质
+    pub sub_meshes: Vec<SubMesh>,
 
     // unity顶点描述
     pub vertex_descriptors: Vec<UnityVertexAttributeDescriptor>,
@@ -94,254 +104,214 @@ pub struct Mesh{
     pub aabb: AABB,
 }
 
-// todo 处理mesh多个材质 HiddenWarehouse场景下 SM_House_03_Roof1 物体
-// -[ ] 处理材质颜色的问题
-impl Mesh{
-    // 转换并反转缠绕顺序
-    // pub fn parse_index_buffer(hex_string: &str) -> Vec<u32> {
-    //     let mut indices = parse_unity_index_buffer(hex_string);
-    //
-    //     // 反转每个三角形的缠绕顺序
-    //     for chunk in indices.chunks_exact_mut(3) {
-    //         chunk.swap(0, 2);
-    //     }
-    //     // println!("{:?}", indices);
-    //
-    //     indices
-    // }
-
-    fn parse_vertex_buffer(hex_string: &str, size_of: &BufferAddress, vertex_count: usize) -> Vec<u8> {
-        // 创建顶点数组
-        // let mut vertices: Vec<T> = Vec::with_capacity(vertex_count);
-        // // 清理数据
-        // let hex_clean: String = hex_string.chars().filter(|c| !c.is_whitespace()).collect();
-        //
-        // let bytes_per_vertex = 36;
-        // let stride = bytes_per_vertex * 2; // 每字节 2 个十六进制字符, unity的数据是16进制
-        //
-        // for i in 0..vertex_count {
-        //     let start = i * stride;
-        //     if start + stride > hex_clean.len() {
-        //         break;
-        //     }
-        //
-        //     let vertex_hex = &hex_clean[start..start + stride];
-        //
-        //     // 解析位置 (前12字节 = 24个十六进制字符)
-        //     let pos_x = parse_f32_le(&vertex_hex[0..8]);
-        //     let pos_y = parse_f32_le(&vertex_hex[8..16]);
-        //     let pos_z = parse_f32_le(&vertex_hex[16..24]);
-        //
-        //     // 解析法向量 (12-24字节)
-        //     let norm_x = parse_f32_le(&vertex_hex[24..32]);// offset 12
-        //     let norm_y = parse_f32_le(&vertex_hex[32..40]);
-        //     let norm_z = parse_f32_le(&vertex_hex[40..48]);
-        //
-        //     // 解析dimension:4
-        //     // let color_r = parse_f32_le(&vertex_hex[48..56]);
-        //     // let color_g = parse_f32_le(&vertex_hex[56..64]);
-        //     // let color_b = parse_f32_le(&vertex_hex[64..72]);
-        //     // let color_a = parse_f32_le(&vertex_hex[72..80]);
-        //
-        //     // 解析 UV (28-36字节)
-        //     let uv_x = parse_f32_le(&vertex_hex[56..64]);
-        //     let uv_y = parse_f32_le(&vertex_hex[64..72]);
-        //
-        // }
-
-        // 移除可能的空格和换行
-        let cleaned = hex_string.replace([' ', '\n', '\r'], "");
-
-        // 解码十六进制字符串为字节
-        let bytes = hex::decode(cleaned).expect("Invalid hex string");
-
-        println!("Vertex sizeof count: {}", size_of);
-
-        match size_of {
-            32 => {
-                // 检查字节数是否是顶点大小的整数倍
-                assert_eq!(bytes.len() % std::mem::size_of::<Vertex>(), 0);
-                let vertices: &[Vertex] = bytemuck::cast_slice(&bytes);
-
-                let mut vertices = vertices.to_vec();
-
-                vertices.iter_mut().for_each(|v| v.flip_z_axis());
-
-                // println!("detect_uv_mapping_32_type(&vertices) :{}", Vertex::detect_uv_mapping_type(&vertices));
-                println!("Vertex sizeof: {} count: {}", size_of, vertices.len());
-                bytemuck::cast_slice(&vertices).to_vec()
-            }
-            56 => {
-                // 检查字节数是否是顶点大小的整数倍
-                assert_eq!(bytes.len() % std::mem::size_of::<VertexTexUvFloat32>(), 0);
-                let vertices: &[VertexTexUvFloat32] = bytemuck::cast_slice(&bytes);
-
-                let mut vertices = vertices.to_vec();
-
-                vertices.iter_mut().for_each(|v| v.flip_z_axis());
-
-                // println!("detect_uv_mapping_32_type(&vertices) :{}", Vertex::detect_uv_mapping_type(&vertices));
-                println!("Vertex sizeof: {} count: {}", size_of, vertices.len());
-                bytemuck::cast_slice(&vertices).to_vec()
-            }
-            36 => {
-                // 检查字节数是否是顶点大小的整数倍
-                assert_eq!(bytes.len() % std::mem::size_of::<VertexColor>(), 0);
-                let vertices: &[VertexColor] = bytemuck::cast_slice(&bytes);
-
-                let mut vertices = vertices.to_vec();
-
-                vertices.iter_mut().for_each(|v| v.flip_z_axis());
-
-                // println!("detect_uv_mapping_32_type(&vertices) :{}", Vertex::detect_uv_mapping_type(&vertices));
-                println!("Vertex sizeof: {} count: {}", size_of, vertices.len());
-                bytemuck::cast_slice(&vertices).to_vec()
-            }
-            40 => {
-                // 检查字节数是否是顶点大小的整数倍
-                assert_eq!(bytes.len() % std::mem::size_of::<VertexUvFloat1632>(), 0);
-                let vertices: &[VertexUvFloat1632] = bytemuck::cast_slice(&bytes);
-
-                let mut vertices = vertices.to_vec();
-
-                vertices.iter_mut().for_each(|v| v.flip_z_axis());
-
-                // println!("detect_uv_mapping_32_type(&vertices) :{}", Vertex::detect_uv_mapping_type(&vertices));
-                println!("Vertex sizeof: {} count: {}", size_of, vertices.len());
-                bytemuck::cast_slice(&vertices).to_vec()
-            }
-            48 => {
-                // 检查字节数是否是顶点大小的整数倍
-                assert_eq!(bytes.len() % std::mem::size_of::<VertexFloat32>(), 0);
-                let vertices: &[VertexFloat32] = bytemuck::cast_slice(&bytes);
-
-                let mut vertices = vertices.to_vec();
-
-                vertices.iter_mut().for_each(|v| v.flip_z_axis());
-
-                // println!("detect_uv_mapping_32_type(&vertices) :{}", Vertex::detect_uv_mapping_type(&vertices));
-                println!("Vertex sizeof: {} count: {}", size_of, vertices.len());
-                bytemuck::cast_slice(&vertices).to_vec()
-            }
-            80 => {
-                // 检查字节数是否是顶点大小的整数倍
-                assert_eq!(bytes.len() % std::mem::size_of::<VertexColorUVx3Float32>(), 0);
-                let vertices: &[VertexColorUVx3Float32] = bytemuck::cast_slice(&bytes);
-
-                let mut vertices = vertices.to_vec();
-
-                vertices.iter_mut().for_each(|v| v.flip_z_axis());
+// 子网格在 index buffer 中的区间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubMesh {
+    pub first_byte: u32,
+    pub index_count: u32,
+    pub base_vertex: i32,
+    // unity MeshTopology：0 三角形，2 四边形，3 线，4 线带，5 点
+    pub topology: u32,
+}
 
-                // println!("detect_uv_mapping_32_type(&vertices) :{}", Vertex::detect_uv_mapping_type(&vertices));
-                println!("Vertex sizeof: {} count: {}", size_of, vertices.len());
-                bytemuck::cast_slice(&vertices).to_vec()
-            }
-            52 => {
-                // 检查字节数是否是顶点大小的整数倍
-                assert_eq!(bytes.len() % std::mem::size_of::<VertexColorFloat3x4U8>(), 0);
-                let vertices: &[VertexColorFloat3x4U8] = bytemuck::cast_slice(&bytes);
+impl SubMesh {
+    /// 覆盖整个 index buffer 的子网格，内置 mesh 使用
+    pub fn whole(index_count: u32) -> Self {
+        Self { first_byte: 0, index_count, base_vertex: 0, topology: 0 }
+    }
 
-                let mut vertices = vertices.to_vec();
+    pub fn is_triangles(&self) -> bool {
+        self.topology == 0
+    }
 
-                vertices.iter_mut().for_each(|v| v.flip_z_axis());
+    /// draw_indexed 使用的索引区间
+    pub fn index_range(&self, format: wgpu::IndexFormat) -> Range<u32> {
+        let first = self.first_byte / format.byte_size() as u32;
+        first..first + self.index_count
+    }
 
-                // println!("detect_uv_mapping_32_type(&vertices) :{}", Vertex::detect_uv_mapping_type(&vertices));
-                println!("Vertex sizeof: {} count: {}", size_of, vertices.len());
-                bytemuck::cast_slice(&vertices).to_vec()
-            }
-            44 => {
-                // 检查字节数是否是顶点大小的整数倍
-                assert_eq!(bytes.len() % std::mem::size_of::<VertexFloat16x4Float>(), 0);
-                let vertices: &[VertexFloat16x4Float] = bytemuck::cast_slice(&bytes);
+    pub fn triangle_count(&self) -> u32 {
+        if self.is_triangles() { self.index_count / 3 } else { 0 }
+    }
+}
 
-                let mut vertices = vertices.to_vec();
+/// 第 slot 个材质绘制的子网格；材质多于子网格时，多出的材质重复绘制最后一个子网格（与 unity 一致）
+pub fn sub_mesh_for_slot(slot: usize, sub_mesh_count: usize) -> Option<usize> {
+    Some(slot.min(sub_mesh_count.checked_sub(1)?))
+}
 
-                vertices.iter_mut().for_each(|v| v.flip_z_axis());
+impl From<&crate::unity::SubMesh> for SubMesh {
+    fn from(raw: &crate::unity::SubMesh) -> Self {
+        Self {
+            first_byte: raw.first_byte,
+            index_count: raw.index_count,
+            base_vertex: raw.base_vertex as i32,
+            topology: raw.topology,
+        }
+    }
+}
 
-                // println!("detect_uv_mapping_32_type(&vertices) :{}", Vertex::detect_uv_mapping_type(&vertices));
-                bytemuck::cast_slice(&vertices).to_vec()
-            }
-            76 => {
-                // 检查字节数是否是顶点大小的整数倍
-                assert_eq!(bytes.len() % std::mem::size_of::<VertexColorUv32>(), 0);
-                let vertices: &[VertexColorUv32] = bytemuck::cast_slice(&bytes);
+// 解码后的索引，保持 unity 中的位宽
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Indices {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
 
-                let mut vertices = vertices.to_vec();
+impl Indices {
+    pub fn format(&self) -> wgpu::IndexFormat {
+        match self {
+            Indices::U16(_) => wgpu::IndexFormat::Uint16,
+            Indices::U32(_) => wgpu::IndexFormat::Uint32,
+        }
+    }
 
-                vertices.iter_mut().for_each(|v| v.flip_z_axis());
+    pub fn len(&self) -> usize {
+        match self {
+            Indices::U16(indices) => indices.len(),
+            Indices::U32(indices) => indices.len(),
+        }
+    }
 
-                // println!("detect_uv_mapping_32_type(&vertices) :{}", Vertex::detect_uv_mapping_type(&vertices));
-                println!("Vertex sizeof: {} count: {}", size_of, vertices.len());
-                bytemuck::cast_slice(&vertices).to_vec()
-            }
-            72 => {
-                // 检查字节数是否是顶点大小的整数倍
-                assert_eq!(bytes.len() % std::mem::size_of::<VertexColorUv32f>(), 0);
-                let vertices: &[VertexColorUv32f] = bytemuck::cast_slice(&bytes);
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 
-                let mut vertices = vertices.to_vec();
+    pub fn get(&self, i: usize) -> Option<u32> {
+        match self {
+            Indices::U16(indices) => indices.get(i).map(|&index| index as u32),
+            Indices::U32(indices) => indices.get(i).copied(),
+        }
+    }
 
-                vertices.iter_mut().for_each(|v| v.flip_z_axis());
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Indices::U16(indices) => bytemuck::cast_slice(indices),
+            Indices::U32(indices) => bytemuck::cast_slice(indices),
+        }
+    }
+}
 
-                // println!("detect_uv_mapping_32_type(&vertices) :{}", Vertex::detect_uv_mapping_type(&vertices));
-                println!("Vertex sizeof: {} count: {}", size_of, vertices.len());
-                bytemuck::cast_slice(&vertices).to_vec()
-            }
-            64 => {
-                // 检查字节数是否是顶点大小的整数倍
-                assert_eq!(bytes.len() % std::mem::size_of::<VertexColorUVFloat32>(), 0);
-                let vertices: &[VertexColorUVFloat32] = bytemuck::cast_slice(&bytes);
+/// unity m_IndexFormat：0 为 UInt16，1 为 UInt32
+pub fn index_format_from_unity(value: u8) -> anyhow::Result<wgpu::IndexFormat> {
+    match value {
+        0 => Ok(wgpu::IndexFormat::Uint16),
+        1 => Ok(wgpu::IndexFormat::Uint32),
+        _ => Err(anyhow::anyhow!("Unknown index format: {}", value)),
+    }
+}
 
-                let mut vertices = vertices.to_vec();
+// .asset 解码后、上传 GPU 前的 mesh 数据
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshData {
+    pub name: String,
+    pub vertices: Vec<u8>,
+    pub vertex_count: u32,
+    pub vertex_descriptors: Vec<UnityVertexAttributeDescriptor>,
+    pub indices: Indices,
+    pub sub_meshes: Vec<SubMesh>,
+    pub aabb: AABB,
+}
 
-                vertices.iter_mut().for_each(|v| v.flip_z_axis());
+impl MeshData {
+    pub fn from_unity_asset(content: &str) -> anyhow::Result<Self> {
+        // 获取mesh文件
+        let raw = serde_yaml::from_str::<MeshAsset>(content)?.mesh;
+        if raw.sub_mesh.is_empty() {
+            return Err(anyhow::anyhow!("Mesh does not contain sub mesh"));
+        }
 
-                // println!("detect_uv_mapping_32_type(&vertices) :{}", Vertex::detect_uv_mapping_type(&vertices));
-                println!("Vertex sizeof: {} count: {}", size_of, vertices.len());
-                bytemuck::cast_slice(&vertices).to_vec()
-            }
-            88 => {
-                // 检查字节数是否是顶点大小的整数倍
-                assert_eq!(bytes.len() % std::mem::size_of::<VertexFloat32x6>(), 0);
-                let vertices: &[VertexFloat32x6] = bytemuck::cast_slice(&bytes);
+        // 按 m_Channels 解码顶点，统一转换为 MeshVertex 布局
+        let source_descriptors = Mesh::render_descriptors(raw.vertex_data.m_channels)
+            .map_err(|e| anyhow::anyhow!("Mesh {}: {}", raw.m_name, e))?;
+        let vertex_bytes = Mesh::decode_hex(&raw.vertex_data._type_less_data)?;
+        let vertices = decode_vertices(&vertex_bytes, raw.vertex_data.vertex_count, &source_descriptors)
+            .map_err(|e| anyhow::anyhow!("Mesh {}: {}", raw.m_name, e))?;
 
-                let mut vertices = vertices.to_vec();
+        let index_format = index_format_from_unity(raw.index_format)?;
+        let indices = Mesh::parse_index_buffer(&raw.index_buffer, index_format)?;
+        // println!("analyze_uv_pattern_by_normal(&vertices) :{:?}", Vertex::analyze_uv_pattern_by_normal(&vertices, &indices));
+        let sub_meshes: Vec<SubMesh> = raw.sub_mesh.iter().map(SubMesh::from).collect();
+
+        let data = MeshData {
+            name: raw.m_name,
+            vertices: bytemuck::cast_slice(&vertices).to_vec(),
+            vertex_count: u32::try_from(raw.vertex_data.vertex_count)?,
+            vertex_descriptors: MeshVertex::descriptors(),
+            indices,
+            sub_meshes,
+            aabb: AABB::from_unity(&raw.m_local_aabb.m_center, &raw.m_local_aabb.m_extent),
+        };
+        data.validate()?;
+        Ok(data)
+    }
 
-                vertices.iter_mut().for_each(|v| v.flip_z_axis());
+    // 上传前的检查，.asset 与烘焙包共用：顶点为 MeshVertex 布局且长度一致，子网格与索引都在范围内
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.vertex_descriptors != MeshVertex::descriptors() {
+            anyhow::bail!("Mesh {} vertex layout is not MeshVertex", self.name);
+        }
+        let expected = self.vertex_count as usize * size_of::<MeshVertex>();
+        if self.vertices.len() != expected {
+            anyhow::bail!("Mesh {} vertex data is {} bytes, expected {}", self.name, self.vertices.len(), expected);
+        }
 
-                // println!("detect_uv_mapping_32_type(&vertices) :{}", Vertex::detect_uv_mapping_type(&vertices));
-                println!("Vertex sizeof: {} count: {}", size_of, vertices.len());
-                bytemuck::cast_slice(&vertices).to_vec()
+        let index_size = self.indices.format().byte_size() as u32;
+        for (i, sub) in self.sub_meshes.iter().enumerate() {
+            let first = sub.first_byte / index_size;
+            let end = first as usize + sub.index_count as usize;
+            if sub.first_byte % index_size != 0 || end > self.indices.len() {
+                anyhow::bail!("Mesh {} sub mesh {} out of index buffer", self.name, i);
             }
-            _ => {
-                println!("Vertex _____ sizeof count: {}", size_of);
-                // 检查字节数是否是顶点大小的整数倍
-                assert_eq!(bytes.len() % std::mem::size_of::<Vertex>(), 0);
-                let vertices: &[Vertex] = bytemuck::cast_slice(&bytes);
-
-                let mut vertices = vertices.to_vec();
-
-                vertices.iter_mut().for_each(|v| v.flip_z_axis());
-
-                println!("__ anther Vertex sizeof: {} count: {}", size_of, vertices.len());
-
-                bytemuck::cast_slice(&vertices).to_vec()
+            for index in first as usize..end {
+                let vertex = self.indices.get(index).unwrap_or_default() as i64 + sub.base_vertex as i64;
+                if !(0..self.vertex_count as i64).contains(&vertex) {
+                    anyhow::bail!("Mesh {} sub mesh {} index {} out of vertex range", self.name, i, vertex);
+                }
             }
         }
-
+        Ok(())
     }
+}
+
+// -[ ] 处理材质颜色的问题
+impl Mesh{
+    // 转换并反转缠绕顺序
+    // pub fn parse_index_buffer(hex_string: &str) -> Vec<u32> {
+    //     let mut indices = parse_unity_index_buffer(hex_string);
+    //
+    //     // 反转每个三角形的缠绕顺序
+    //     for chunk in indices.chunks_exact_mut(3) {
+    //         chunk.swap(0, 2);
+    //     }
+    //     // println!("{:?}", indices);
+    //
+    //     indices
+    // }
 
-    fn parse_index_buffer(hex_string: &str) -> Vec<u16> {
-        // 移除空格和换行
+    // 移除空格和换行后解码十六进制字符串
+    fn decode_hex(hex_string: &str) -> anyhow::Result<Vec<u8>> {
         let cleaned = hex_string.replace([' ', '\n', '\r'], "");
+        Ok(hex::decode(cleaned)?)
+    }
 
-        // 解码十六进制字符串为字节
-        let bytes = hex::decode(cleaned).expect("Invalid hex string");
+    fn parse_index_buffer(hex_string: &str, format: wgpu::IndexFormat) -> anyhow::Result<Indices> {
+        let bytes = Self::decode_hex(hex_string)?;
         // println!("Index: sizeof {:?}", bytes);
-        // 将字节转换为 u16 索引数组
-        let indices: &[u16] = bytemuck::cast_slice(&bytes);
+        if bytes.len() % format.byte_size() != 0 {
+            return Err(anyhow::anyhow!("Index buffer size {} is not a multiple of {:?}", bytes.len(), format));
+        }
+        // 按小端转换为索引数组
+        let indices = match format {
+            wgpu::IndexFormat::Uint16 => Indices::U16(
+                bytes.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]])).collect(),
+            ),
+            wgpu::IndexFormat::Uint32 => Indices::U32(
+                bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect(),
+            ),
+        };
 
-        indices.to_vec()
+        Ok(indices)
     }
 
     /// 创建默认的 Cube mesh（1x1x1 立方体，中心在原点）
@@ -465,7 +435,9 @@ impl Mesh{
             name: "Default_Cube".to_string(),
             vertex_buffer,
             index_buffer,
+            index_format: wgpu::IndexFormat::Uint16,
             index_count: 36,
+            sub_meshes: vec![SubMesh::whole(36)],
             vertex_count: 24,
             vertex_descriptors,
             render_pipeline,
@@ -558,7 +530,9 @@ impl Mesh{
             name: "Default_Quad".to_string(),
             vertex_buffer,
             index_buffer,
+            index_format: wgpu::IndexFormat::Uint16,
             index_count: 6,
+            sub_meshes: vec![SubMesh::whole(6)],
             vertex_count: 4,
             vertex_descriptors,
             render_pipeline,
@@ -1012,7 +986,9 @@ impl Mesh{
             name: name.to_string(),
             vertex_buffer,
             index_buffer,
+            index_format: wgpu::IndexFormat::Uint16,
             index_count: indices.len() as u32,
+            sub_meshes: vec![SubMesh::whole(indices.len() as u32)],
             vertex_count: (vertices.len() / 12) as u32,
             vertex_descriptors,
             render_pipeline,
@@ -1023,52 +999,73 @@ impl Mesh{
     // 初始化pipeline 以及各类的布局
     pub async fn from_unity_data(buff: &[u8], id: &MeshId, device: &Device, scene: &Scene, material: &Material, config: &SurfaceConfiguration) -> anyhow::Result<Mesh> {
         let content = std::str::from_utf8(buff)?;
-        // 获取mesh文件
-        let raw_asset = serde_yaml::from_str::<MeshAsset>(content).map_err(|e| {
-            println!("Failed to parse mesh asset: {:?}", e);
-            e
-        })?;
-        let raw = raw_asset.mesh;
-        let Some(sub_mesh) = raw.sub_mesh.get(0) else {
-            return Err(anyhow::anyhow!("Mesh does not contain sub mesh"));
-        };
+        let data = MeshData::from_unity_asset(content)?;
+        Ok(Self::from_data(&data, id, device, scene, material, config))
+    }
 
-        let vertex_descriptors = Self::render_descriptors(raw.vertex_data.m_channels);
-        print!("{:?},", Self::get_vertex_stride(&vertex_descriptors));
-        
-        // 处理材质数据
-        let size_of = Self::get_vertex_stride(&vertex_descriptors);
-        let vertices = Mesh::parse_vertex_buffer(&raw.vertex_data._type_less_data, &size_of, raw.vertex_data.vertex_count);
+    // 上传已解码的 mesh 数据，.asset 与烘焙包共用
+    pub fn from_data(data: &MeshData, id: &MeshId, device: &Device, scene: &Scene, material: &Material, config: &SurfaceConfiguration) -> Mesh {
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor{
-            label: Some(&format!("Mesh_Vertice: {}", raw.m_name)),
-            contents: bytemuck::cast_slice(&vertices),
+            label: Some(&format!("Mesh_Vertice: {}", data.name)),
+            contents: &data.vertices,
             usage: wgpu::BufferUsages::VERTEX,
         });
 
-        // println!("vertices: {:?}", vertices);
-        let indices = Mesh::parse_index_buffer(&raw.index_buffer);
-        // println!("analyze_uv_pattern_by_normal(&vertices) :{:?}", Vertex::analyze_uv_pattern_by_normal(&vertices, &indices));
-        println!("indices: length {:?},", indices.len());
-
         let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor{
-            label: Some(&format!("Mesh_Index: {}", raw.m_name)),
-            contents: bytemuck::cast_slice(&indices),
+            label: Some(&format!("Mesh_Index: {}", data.name)),
+            contents: data.indices.as_bytes(),
             usage: wgpu::BufferUsages::INDEX,
         });
 
-        let render_pipeline = Self::create_render_pipeline(device, scene, config, material, &vertex_descriptors, &raw.m_name);
+        let render_pipeline = Self::create_render_pipeline(device, scene, config, material, &data.vertex_descriptors, &data.name);
 
-        Ok(Mesh{
+        Mesh{
             id: id.clone(),
-            name: format!("Mesh: {}", raw.m_name),
+            name: format!("Mesh: {}", data.name),
             vertex_buffer,
             index_buffer,
-            index_count: sub_mesh.index_count,
-            vertex_count: sub_mesh.vertex_count,
-            vertex_descriptors,
+            index_format: data.indices.format(),
+            index_count: data.indices.len() as u32,
+            sub_meshes: data.sub_meshes.clone(),
+            vertex_count: data.vertex_count,
+            vertex_descriptors: data.vertex_descriptors.clone(),
             render_pipeline,
-            aabb: AABB::from_unity(&raw.m_local_aabb.m_center, &raw.m_local_aabb.m_extent),
-        })
+            aabb: data.aabb,
+        }
+    }
+
+    // 常见的 Unity 内置 Mesh fileID：
+    // fileIDMesh 类型
+    // 10202 Cube（立方体）
+    // 10206 Cylinder（圆柱体）
+    // 10207 Sphere（球体）
+    // 10208 Capsule（胶囊体）
+    // 10209 Plane（平面，10×10 单位）
+    // 10210 Quad（四边形，1×1 单位）
+    pub fn is_builtin(m_mesh: &UnityReference) -> bool {
+        m_mesh.guid == BUILTIN_MESH_GUID && matches!(m_mesh.file_id, 10202 | 10206..=10210)
+    }
+
+    // 内置 mesh 共用同一个 guid，缓存时带上 fileID 区分
+    pub fn mesh_id(m_mesh: &UnityReference) -> MeshId {
+        format!("{}:{}", m_mesh.guid, m_mesh.file_id)
+    }
+
+    pub fn create_builtin(m_mesh: &UnityReference, device: &Device, scene: &Scene, material: &Material, config: &SurfaceConfiguration) -> Option<Mesh> {
+        if m_mesh.guid != BUILTIN_MESH_GUID {
+            return None;
+        }
+        let id = &Self::mesh_id(m_mesh);
+        let mesh = match m_mesh.file_id {
+            10202 => Mesh::create_default_cube(id, device, scene, material, config),
+            10206 => Mesh::create_default_cylinder(id, device, scene, material, config),
+            10207 => Mesh::create_default_sphere(id, device, scene, material, config),
+            10208 => Mesh::create_default_capsule(id, device, scene, material, config),
+            10209 => Mesh::create_default_plane(id, device, scene, material, config),
+            10210 => Mesh::create_default_quad(id, device, scene, material, config),
+            _ => return None,
+        };
+        Some(mesh)
     }
 
     fn create_render_pipeline(device: &Device, scene: &Scene, config: &SurfaceConfiguration, material: &Material, vertex_descriptors: &Vec<UnityVertexAttributeDescriptor>, label: &String) -> wgpu::RenderPipeline {
@@ -1184,21 +1181,188 @@ impl Mesh{
         }
     }
 
-    pub fn render_descriptors(m_channels: Vec<Channel>) -> Vec<UnityVertexAttributeDescriptor> {
-        // 根据channel 渲染
+    pub fn render_descriptors(m_channels: Vec<Channel>) -> anyhow::Result<Vec<UnityVertexAttributeDescriptor>> {
+        // 根据channel 渲染，下标即顶点属性
         let mut vertex_descriptors: Vec<UnityVertexAttributeDescriptor> = Vec::new();
         for (i, channel)     in m_channels.iter().enumerate() {
+            let Ok(index) = u8::try_from(i) else {
+                anyhow::bail!("Too many vertex channels: {}", m_channels.len());
+            };
             vertex_descriptors.push(UnityVertexAttributeDescriptor{
-                attribute: UnityVertexAttribute::from_u8(i as u8),
-                format: UnityVertexFormat::from_u8(channel.format),
+                attribute: UnityVertexAttribute::from_u8(index)?,
+                format: UnityVertexFormat::from_u8(channel.format)?,
                 dimension: channel.dimension,
                 stream: channel.stream,
                 offset: channel.offset,
             })
         }
 
-        vertex_descriptors
+        Ok(vertex_descriptors)
+    }
+
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sub_mesh_range() {
+        let raw: crate::unity::SubMesh = serde_yaml::from_str(
+            "serializedVersion: 2\nfirstByte: 72\nindexCount: 12\ntopology: 0\nbaseVertex: 8\nfirstVertex: 8\nvertexCount: 8\n",
+        ).unwrap();
+        let sub = SubMesh::from(&raw);
+        assert_eq!(sub.index_range(wgpu::IndexFormat::Uint16), 36..48);
+        assert_eq!(sub.index_range(wgpu::IndexFormat::Uint32), 18..30);
+        assert_eq!(sub.base_vertex, 8);
+        assert!(sub.is_triangles());
+        assert_eq!(SubMesh::whole(6).index_range(wgpu::IndexFormat::Uint16), 0..6);
+    }
+
+    // 生成 n x n 网格的 .asset：顶点数超过 u16，两个子网格各占一半行，索引为 32 位
+    fn grid_mesh_asset(n: u32) -> String {
+        use half::f16;
+        let mut vertices = Vec::new();
+        for row in 0..n {
+            for col in 0..n {
+                for v in [col as f32 * 0.1, 0.0, row as f32 * 0.1] {
+                    vertices.extend_from_slice(&v.to_le_bytes());
+                }
+                // normal、tangent、uv
+                for v in [0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0] {
+                    vertices.extend_from_slice(&f16::from_f32(v).to_le_bytes());
+                }
+            }
+        }
+        let half = (n - 1) / 2;
+        let mut indices = Vec::new();
+        let mut sub_meshes = String::new();
+        for rows in [0..half, half..n - 1] {
+            let first_byte = indices.len();
+            for row in rows {
+                for col in 0..n - 1 {
+                    let i = row * n + col;
+                    for index in [i, i + n, i + 1, i + 1, i + n, i + n + 1] {
+                        indices.extend_from_slice(&index.to_le_bytes());
+                    }
+                }
+            }
+            sub_meshes.push_str(&format!(
+                "  - serializedVersion: 2\n    firstByte: {}\n    indexCount: {}\n    topology: 0\n    baseVertex: 0\n    firstVertex: 0\n    vertexCount: {}\n",
+                first_byte, (indices.len() - first_byte) / 4, n * n,
+            ));
+        }
+        let mut channels = String::new();
+        for (offset, format, dimension) in [(0, 0, 3), (12, 1, 4), (20, 1, 4), (0, 0, 0), (28, 1, 2)] {
+            channels.push_str(&format!(
+                "    - stream: 0\n      offset: {}\n      format: {}\n      dimension: {}\n",
+                offset, format, dimension,
+            ));
+        }
+        for _ in 5..14 {
+            channels.push_str("    - stream: 0\n      offset: 0\n      format: 0\n      dimension: 0\n");
+        }
+        let size = (n - 1) as f32 * 0.1 / 2.0;
+        format!(
+            "Mesh:\n  m_Name: Grid\n  m_SubMeshes:\n{}  m_IndexFormat: 1\n  m_IndexBuffer: {}\n  m_LocalAABB:\n    m_Center: {{x: {size}, y: 0, z: {size}}}\n    m_Extent: {{x: {size}, y: 0, z: {size}}}\n  m_VertexData:\n    serializedVersion: 3\n    m_VertexCount: {}\n    m_Channels:\n{}    m_DataSize: {}\n    _typelessdata: {}\n",
+            sub_meshes, hex::encode(&indices), n * n, channels, vertices.len(), hex::encode(&vertices),
+        )
     }
 
+    #[test]
+    fn test_uint32_mesh_fixture() {
+        let n = 260;
+        let data = MeshData::from_unity_asset(&grid_mesh_asset(n)).unwrap();
+        assert!(data.vertex_count > u16::MAX as u32);
+        assert_eq!(data.indices.format(), wgpu::IndexFormat::Uint32);
+        assert_eq!(data.indices.as_bytes().len(), data.indices.len() * 4);
+
+        // 三角形数量：每个子网格的行数 * 每行的四边形 * 2
+        let quads_per_row = n - 1;
+        let triangles: Vec<u32> = data.sub_meshes.iter().map(SubMesh::triangle_count).collect();
+        assert_eq!(triangles, vec![129 * quads_per_row * 2, 130 * quads_per_row * 2]);
+
+        // 所有索引都落在顶点范围内，且超过 u16 的索引没有被截断
+        let mut max_index = 0;
+        for sub in &data.sub_meshes {
+            for i in sub.index_range(data.indices.format()) {
+                let index = data.indices.get(i as usize).unwrap() as i64 + sub.base_vertex as i64;
+                assert!(index < data.vertex_count as i64);
+                max_index = max_index.max(index);
+            }
+        }
+        assert_eq!(max_index, (n * n - 1) as i64);
+
+        // 顶点包围盒与 m_LocalAABB 一致（均已翻转 Z）
+        let stride = Mesh::get_vertex_stride(&data.vertex_descriptors) as usize;
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for vertex in data.vertices.chunks_exact(stride) {
+            for axis in 0..3 {
+                let v = f32::from_le_bytes(vertex[axis * 4..axis * 4 + 4].try_into().unwrap());
+                min[axis] = min[axis].min(v);
+                max[axis] = max[axis].max(v);
+            }
+        }
+        for axis in 0..3 {
+            assert!((data.aabb.min[axis] - min[axis]).abs() < 1e-4);
+            assert!((data.aabb.max[axis] - max[axis]).abs() < 1e-4);
+        }
+    }
 
-}
\ No newline at end of file
+    // 14 个通道中只有 Position 与蒙皮数据（BlendWeight 12、BlendIndices 13），蒙皮数据不能覆盖顶点位置
+    #[test]
+    fn test_blend_channels_keep_position() {
+        let mut channels: Vec<Channel> = (0..14)
+            .map(|_| Channel { stream: 0, offset: 0, format: 0, dimension: 0 })
+            .collect();
+        channels[0] = Channel { stream: 0, offset: 0, format: 0, dimension: 3 };
+        channels[12] = Channel { stream: 0, offset: 12, format: 0, dimension: 4 };
+        channels[13] = Channel { stream: 0, offset: 28, format: 10, dimension: 4 };
+        let descriptors = Mesh::render_descriptors(channels).unwrap();
+        assert_eq!(descriptors[8].attribute, UnityVertexAttribute::TexCoord4);
+        assert_eq!(descriptors[11].attribute, UnityVertexAttribute::TexCoord7);
+        assert_eq!(descriptors[12].attribute, UnityVertexAttribute::BlendWeight);
+        assert_eq!(descriptors[13].attribute, UnityVertexAttribute::BlendIndices);
+
+        let mut data = Vec::new();
+        for v in [1.0f32, 2.0, 3.0, 0.25, 0.25, 0.25, 0.25] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        for index in [7u32, 8, 9, 10] {
+            data.extend_from_slice(&index.to_le_bytes());
+        }
+        let vertices = decode_vertices(&data, 1, &descriptors).unwrap();
+        assert_eq!(vertices[0].position, [1.0, 2.0, -3.0]);
+
+        let mut too_many: Vec<Channel> = (0..15)
+            .map(|_| Channel { stream: 0, offset: 0, format: 0, dimension: 0 })
+            .collect();
+        too_many[0].dimension = 3;
+        assert!(Mesh::render_descriptors(too_many).is_err());
+        assert!(UnityVertexAttribute::from_u8(14).is_err());
+    }
+
+    #[test]
+    fn test_parse_index_buffer() {
+        assert_eq!(
+            Mesh::parse_index_buffer("0100 ffff", wgpu::IndexFormat::Uint16).unwrap(),
+            Indices::U16(vec![1, 65535]),
+        );
+        assert_eq!(
+            Mesh::parse_index_buffer("01000100", wgpu::IndexFormat::Uint32).unwrap(),
+            Indices::U32(vec![0x0001_0001]),
+        );
+        assert!(Mesh::parse_index_buffer("010001", wgpu::IndexFormat::Uint32).is_err());
+        assert!(index_format_from_unity(2).is_err());
+    }
+
+    #[test]
+    fn test_sub_mesh_for_slot() {
+        assert_eq!(sub_mesh_for_slot(0, 2), Some(0));
+        assert_eq!(sub_mesh_for_slot(1, 2), Some(1));
+        assert_eq!(sub_mesh_for_slot(3, 2), Some(1));
+        assert_eq!(sub_mesh_for_slot(0, 0), None);
+    }
+}
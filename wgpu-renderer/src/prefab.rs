@@ -0,0 +1,521 @@
+use std::collections::{HashMap, HashSet};
+use anyhow::{anyhow, bail};
+use log::{info, warn};
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+use crate::resource::AssetManifest;
+use crate::unity::{preprocess_yaml, split_documents, UnityDocument, UnityScene};
+
+// 预制体实例展开
+// 场景中大部分物体以 PrefabInstance 放置，只记录 m_SourcePrefab 与 m_Modifications，
+// 需要加载 .prefab 把其中的对象展开到场景里。
+// 预制体中的对象在实例所在文件里的 fileID 为 (实例 fileID ^ 源 fileID) & i64::MAX，
+// stripped 文档与 m_Modifications 的 target 都按这个规则引用，嵌套预制体逐层换算即可。
+
+// 嵌套层数上限，防止预制体互相引用
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileRef {
+    #[serde(rename = "fileID")]
+    pub file_id: i64,
+    #[serde(default)]
+    pub guid: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PropertyModification {
+    pub target: FileRef,
+    #[serde(rename = "propertyPath")]
+    pub property_path: String,
+    #[serde(default)]
+    pub value: Value,
+    #[serde(rename = "objectReference", default)]
+    pub object_reference: Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PrefabModification {
+    #[serde(rename = "m_TransformParent")]
+    pub transform_parent: FileRef,
+    #[serde(rename = "m_Modifications", default)]
+    pub modifications: Vec<PropertyModification>,
+    #[serde(rename = "m_RemovedComponents", default)]
+    pub removed_components: Vec<FileRef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UnityPrefabInstance {
+    #[serde(rename = "m_Modification")]
+    pub modification: PrefabModification,
+    #[serde(rename = "m_SourcePrefab")]
+    pub source_prefab: FileRef,
+}
+
+impl UnityPrefabInstance {
+    pub fn from_yaml(content: &str) -> anyhow::Result<Self> {
+        Ok(serde_yaml::from_str(&preprocess_yaml(content))?)
+    }
+
+    fn source_guid(&self) -> anyhow::Result<&str> {
+        self.source_prefab
+            .guid
+            .as_deref()
+            .ok_or_else(|| anyhow!("PrefabInstance 缺少 m_SourcePrefab.guid"))
+    }
+}
+
+// 展开后的对象，file_id 已换算到实例所在的文件
+#[derive(Debug, Clone)]
+pub struct PrefabObject {
+    pub file_id: i64,
+    pub class_name: String,
+    pub value: Value,
+}
+
+pub fn instance_file_id(instance_id: i64, source_id: i64) -> i64 {
+    (instance_id ^ source_id) & i64::MAX
+}
+
+// 遍历所有不带 guid 的 {fileID: x} 引用
+fn for_each_local_ref(value: &mut Value, f: &mut impl FnMut(&mut Value)) {
+    match value {
+        Value::Mapping(map) => {
+            let external = map
+                .get("guid")
+                .is_some_and(|guid| guid.as_str().is_none_or(|guid| !guid.is_empty()));
+            if !external && let Some(file_id) = map.get_mut("fileID") {
+                f(file_id);
+            }
+            for (_, child) in map.iter_mut() {
+                for_each_local_ref(child, f);
+            }
+        }
+        Value::Sequence(seq) => seq.iter_mut().for_each(|child| for_each_local_ref(child, f)),
+        _ => {}
+    }
+}
+
+// 按 propertyPath 写入属性，支持 `m_Materials.Array.data[0]` 与 `Array.size`
+pub fn set_property(target: &mut Value, path: &str, value: Value) -> bool {
+    let parts: Vec<&str> = path.split('.').filter(|part| *part != "Array").collect();
+    let mut current = target;
+    for (i, part) in parts.iter().enumerate() {
+        let last = i + 1 == parts.len();
+        if *part == "size" && last {
+            let (Value::Sequence(seq), Some(size)) = (&mut *current, value.as_u64()) else {
+                return false;
+            };
+            seq.resize(size as usize, Value::Null);
+            return true;
+        }
+        let index = part
+            .strip_prefix("data[")
+            .and_then(|rest| rest.strip_suffix(']'))
+            .and_then(|index| index.parse::<usize>().ok());
+        current = match (current, index) {
+            (Value::Sequence(seq), Some(index)) => {
+                if seq.len() <= index {
+                    seq.resize(index + 1, Value::Null);
+                }
+                &mut seq[index]
+            }
+            (Value::Mapping(map), None) => map.entry(Value::from(*part)).or_insert(Value::Null),
+            // 中间节点缺失时按路径创建
+            (node @ Value::Null, None) => {
+                *node = Value::Mapping(Mapping::new());
+                let Value::Mapping(map) = node else { unreachable!() };
+                map.entry(Value::from(*part)).or_insert(Value::Null)
+            }
+            _ => return false,
+        };
+    }
+    *current = value;
+    true
+}
+
+fn is_transform(class_name: &str) -> bool {
+    class_name == "Transform" || class_name == "RectTransform"
+}
+
+// 预制体的 fileID 都是 i64，m_Father 为 0 的 Transform 是根节点
+fn father_id(object: &PrefabObject) -> Option<i64> {
+    object.value.get("m_Father")?.get("fileID")?.as_i64()
+}
+
+/// 已加载的预制体，按 guid 索引
+#[derive(Debug, Default)]
+pub struct PrefabLibrary {
+    prefabs: HashMap<String, Vec<UnityDocument>>,
+}
+
+impl PrefabLibrary {
+    pub fn insert(&mut self, guid: &str, text: &str) {
+        self.prefabs.insert(guid.to_string(), split_documents(text));
+    }
+
+    // 加载场景用到的所有预制体，包括嵌套的，加载失败的只记录日志
+    pub async fn load(manifest: &AssetManifest, unity_scene: &UnityScene) -> Self {
+        let mut library = PrefabLibrary::default();
+        let mut failed = HashSet::new();
+        let mut pending: Vec<String> = unity_scene
+            .prefab_instances_raw
+            .iter()
+            .filter_map(|(_, content)| UnityPrefabInstance::from_yaml(content).ok())
+            .filter_map(|instance| instance.source_prefab.guid)
+            .collect();
+
+        while let Some(guid) = pending.pop() {
+            if library.prefabs.contains_key(&guid) || failed.contains(&guid) {
+                continue;
+            }
+            let text = match manifest.load_guid_file(&guid).await {
+                Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                Err(e) => {
+                    warn!("预制体 {} 加载失败: {:?}", guid, e);
+                    failed.insert(guid);
+                    continue;
+                }
+            };
+            library.insert(&guid, &text);
+            pending.extend(
+                library.prefabs[&guid]
+                    .iter()
+                    .filter(|document| document.class_name == "PrefabInstance" && !document.stripped)
+                    .filter_map(|document| UnityPrefabInstance::from_yaml(&document.content).ok())
+                    .filter_map(|instance| instance.source_prefab.guid),
+            );
+        }
+        info!("加载预制体 {} 个，失败 {} 个", library.prefabs.len(), failed.len());
+        library
+    }
+
+    // 展开一组文档，嵌套的预制体实例递归展开，stripped 文档与展开后的对象同 id，直接跳过
+    fn expand(&self, documents: &[UnityDocument], depth: usize) -> anyhow::Result<Vec<PrefabObject>> {
+        let mut objects = Vec::new();
+        for document in documents.iter().filter(|document| !document.stripped) {
+            if document.class_name == "PrefabInstance" {
+                let instance = UnityPrefabInstance::from_yaml(&document.content)?;
+                objects.extend(self.instantiate(document.file_id, &instance, depth + 1)?);
+                continue;
+            }
+            objects.push(PrefabObject {
+                file_id: document.file_id,
+                class_name: document.class_name.clone(),
+                value: serde_yaml::from_str(&preprocess_yaml(&document.content))?,
+            });
+        }
+        Ok(objects)
+    }
+
+    /// 展开一个预制体实例，返回的对象 fileID 与引用都已换算到实例所在的文件
+    pub fn instantiate(
+        &self,
+        instance_id: i64,
+        instance: &UnityPrefabInstance,
+        depth: usize,
+    ) -> anyhow::Result<Vec<PrefabObject>> {
+        if depth > MAX_DEPTH {
+            bail!("预制体嵌套超过 {} 层", MAX_DEPTH);
+        }
+        let guid = instance.source_guid()?;
+        let Some(documents) = self.prefabs.get(guid) else {
+            bail!("预制体 {} 未加载", guid);
+        };
+        let mut objects = self.expand(documents, depth)?;
+        let modification = &instance.modification;
+
+        let removed: HashSet<i64> = modification.removed_components.iter().map(|target| target.file_id).collect();
+        objects.retain(|object| !removed.contains(&object.file_id));
+
+        // 修改的 target 是预制体内的 fileID
+        let positions: HashMap<i64, usize> =
+            objects.iter().enumerate().map(|(i, object)| (object.file_id, i)).collect();
+        for property in &modification.modifications {
+            let Some(&index) = positions.get(&property.target.file_id) else {
+                continue;
+            };
+            let has_reference = property
+                .object_reference
+                .get("fileID")
+                .and_then(Value::as_i64)
+                .is_some_and(|file_id| file_id != 0);
+            let value = if has_reference {
+                property.object_reference.clone()
+            } else {
+                property.value.clone()
+            };
+            if !set_property(&mut objects[index].value, &property.property_path, value) {
+                warn!("预制体 {} 的修改 {} 无法应用", guid, property.property_path);
+            }
+        }
+
+        // 换算到实例所在的文件
+        let local: HashSet<i64> = objects.iter().map(|object| object.file_id).collect();
+        for object in &mut objects {
+            object.file_id = instance_file_id(instance_id, object.file_id);
+            for_each_local_ref(&mut object.value, &mut |file_id| {
+                if let Some(id) = file_id.as_i64().filter(|id| local.contains(id)) {
+                    *file_id = Value::from(instance_file_id(instance_id, id));
+                }
+            });
+        }
+
+        // 根节点挂到 m_TransformParent 下
+        let parent = modification.transform_parent.file_id;
+        for object in objects.iter_mut().filter(|object| is_transform(&object.class_name)) {
+            if father_id(object) == Some(0) {
+                set_property(&mut object.value, "m_Father.fileID", Value::from(parent));
+            }
+        }
+
+        Ok(objects)
+    }
+}
+
+impl UnityScene {
+    /// 展开场景中的预制体实例，对象的 fileID 重新分配为不冲突的 u32
+    pub fn resolve_prefabs(&mut self, library: &PrefabLibrary) {
+        let mut objects = Vec::new();
+        for (instance_id, content) in std::mem::take(&mut self.prefab_instances_raw) {
+            let result = UnityPrefabInstance::from_yaml(&content)
+                .and_then(|instance| library.instantiate(instance_id, &instance, 0));
+            match result {
+                Ok(instance_objects) => objects.extend(instance_objects),
+                Err(e) => warn!("预制体实例 {} 展开失败: {:?}", instance_id, e),
+            }
+        }
+        if objects.is_empty() {
+            return;
+        }
+
+        // 超出 u32 或与场景对象冲突的 fileID 重新分配
+        let mut next_id = self.index.keys().copied().max().unwrap_or(0) + 1;
+        let mut remap: HashMap<i64, u32> = HashMap::new();
+        for object in &objects {
+            let id = match u32::try_from(object.file_id) {
+                Ok(id) if !self.index.contains_key(&id) => id,
+                _ => {
+                    next_id += 1;
+                    next_id - 1
+                }
+            };
+            remap.insert(object.file_id, id);
+        }
+        let changed: HashMap<i64, u32> = remap
+            .iter()
+            .filter(|(from, to)| **from != i64::from(**to))
+            .map(|(from, to)| (*from, *to))
+            .collect();
+
+        for mut object in objects {
+            for_each_local_ref(&mut object.value, &mut |file_id| {
+                if let Some(id) = file_id.as_i64().and_then(|id| changed.get(&id)) {
+                    *file_id = Value::from(*id);
+                }
+            });
+            match serde_yaml::to_string(&object.value) {
+                Ok(content) => self.insert_raw(remap[&object.file_id], &object.class_name, content),
+                Err(e) => warn!("预制体对象 {} 序列化失败: {:?}", object.file_id, e),
+            }
+        }
+
+        // 场景对象通过 stripped 文档引用预制体中的对象，同样需要换算
+        if !changed.is_empty() {
+            let re = regex::Regex::new(r"fileID: (\d+)").unwrap();
+            let replace = |content: &mut String| {
+                let replaced = re.replace_all(content, |caps: &regex::Captures| {
+                    match caps[1].parse::<i64>().ok().and_then(|id| changed.get(&id)) {
+                        Some(id) => format!("fileID: {}", id),
+                        None => caps[0].to_string(),
+                    }
+                });
+                if let std::borrow::Cow::Owned(replaced) = replaced {
+                    *content = replaced;
+                }
+            };
+            self.game_object_raw.values_mut().for_each(replace);
+            self.transforms_raw.values_mut().for_each(replace);
+            self.mesh_renderers_raw.values_mut().for_each(replace);
+            self.mesh_filters_raw.values_mut().for_each(replace);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unity::{UnityGameObject, UnityMeshRenderer, UnityTransform};
+
+    const CRATE_GUID: &str = "0a1b2c3d4e5f60718293a4b5c6d7e8f9";
+    const LAMP_GUID: &str = "11112222333344445555666677778888";
+
+    // 灯：一个 GameObject 与 Transform
+    const LAMP: &str = "%YAML 1.1
+--- !u!1 &100
+GameObject:
+  m_Component:
+  - component: {fileID: 200}
+  m_Layer: 0
+  m_Name: Lamp
+  m_IsActive: 1
+--- !u!4 &200
+Transform:
+  m_GameObject: {fileID: 100}
+  m_LocalRotation: {x: 0, y: 0, z: 0, w: 1}
+  m_LocalPosition: {x: 0, y: 2, z: 0}
+  m_LocalScale: {x: 1, y: 1, z: 1}
+  m_Children: []
+  m_Father: {fileID: 0}
+";
+
+    // 箱子：自身带网格，并在子节点上嵌套一个灯
+    fn crate_prefab() -> String {
+        format!("%YAML 1.1
+--- !u!1 &1000
+GameObject:
+  m_Component:
+  - component: {{fileID: 4000}}
+  - component: {{fileID: 23000}}
+  m_Layer: 0
+  m_Name: Crate
+  m_IsActive: 1
+--- !u!4 &4000
+Transform:
+  m_GameObject: {{fileID: 1000}}
+  m_LocalRotation: {{x: 0, y: 0, z: 0, w: 1}}
+  m_LocalPosition: {{x: 0, y: 0, z: 0}}
+  m_LocalScale: {{x: 1, y: 1, z: 1}}
+  m_Children: []
+  m_Father: {{fileID: 0}}
+--- !u!23 &23000
+MeshRenderer:
+  m_GameObject: {{fileID: 1000}}
+  m_Enabled: 1
+  m_Materials:
+  - {{fileID: 2100000, guid: 00000000000000000000000000000001, type: 2}}
+--- !u!1001 &5000
+PrefabInstance:
+  m_Modification:
+    m_TransformParent: {{fileID: 4000}}
+    m_Modifications:
+    - target: {{fileID: 100, guid: {LAMP_GUID}, type: 3}}
+      propertyPath: m_Name
+      value: CrateLamp
+      objectReference: {{fileID: 0}}
+  m_SourcePrefab: {{fileID: 100100000, guid: {LAMP_GUID}, type: 3}}
+")
+    }
+
+    // 场景：放置一个箱子，修改位置、显隐与材质，并修改嵌套灯的高度
+    fn scene() -> String {
+        let lamp_transform = instance_file_id(5000, 200);
+        format!("%YAML 1.1
+--- !u!1 &10
+GameObject:
+  m_Component:
+  - component: {{fileID: 11}}
+  m_Layer: 0
+  m_Name: Ground
+  m_IsActive: 1
+--- !u!4 &11
+Transform:
+  m_GameObject: {{fileID: 10}}
+  m_LocalRotation: {{x: 0, y: 0, z: 0, w: 1}}
+  m_LocalPosition: {{x: 0, y: 0, z: 0}}
+  m_LocalScale: {{x: 1, y: 1, z: 1}}
+  m_Children: []
+  m_Father: {{fileID: 0}}
+--- !u!1001 &7000000000
+PrefabInstance:
+  m_Modification:
+    m_TransformParent: {{fileID: 11}}
+    m_Modifications:
+    - target: {{fileID: 4000, guid: {CRATE_GUID}, type: 3}}
+      propertyPath: m_LocalPosition.x
+      value: 5.5
+      objectReference: {{fileID: 0}}
+    - target: {{fileID: 1000, guid: {CRATE_GUID}, type: 3}}
+      propertyPath: m_IsActive
+      value: 0
+      objectReference: {{fileID: 0}}
+    - target: {{fileID: 23000, guid: {CRATE_GUID}, type: 3}}
+      propertyPath: m_Materials.Array.data[0]
+      value:
+      objectReference: {{fileID: 2100000, guid: 00000000000000000000000000000002, type: 2}}
+    - target: {{fileID: {lamp_transform}, guid: {CRATE_GUID}, type: 3}}
+      propertyPath: m_LocalPosition.y
+      value: 3
+      objectReference: {{fileID: 0}}
+  m_SourcePrefab: {{fileID: 100100000, guid: {CRATE_GUID}, type: 3}}
+")
+    }
+
+    fn resolved_scene() -> UnityScene {
+        let mut library = PrefabLibrary::default();
+        library.insert(CRATE_GUID, &crate_prefab());
+        library.insert(LAMP_GUID, LAMP);
+        let mut unity_scene = UnityScene::from_text(&scene());
+        unity_scene.resolve_prefabs(&library);
+        unity_scene
+    }
+
+    fn find_object(unity_scene: &UnityScene, name: &str) -> (u32, UnityGameObject) {
+        unity_scene
+            .game_object_raw
+            .iter()
+            .map(|(id, raw)| (*id, serde_yaml::from_str::<UnityGameObject>(raw).unwrap()))
+            .find(|(_, object)| object.m_name == name)
+            .unwrap()
+    }
+
+    fn transform_of(unity_scene: &UnityScene, object: &UnityGameObject) -> UnityTransform {
+        let id = object.m_component[0].component.file_id;
+        serde_yaml::from_str(&unity_scene.transforms_raw[&id]).unwrap()
+    }
+
+    #[test]
+    fn test_instance_modifications() {
+        let unity_scene = resolved_scene();
+        assert!(unity_scene.prefab_instances_raw.is_empty());
+        assert_eq!(unity_scene.game_object_raw.len(), 3);
+
+        let (_, crate_object) = find_object(&unity_scene, "Crate");
+        assert_eq!(crate_object.m_is_active, 0);
+        let transform = transform_of(&unity_scene, &crate_object);
+        assert_eq!(transform.m_local_position.x, 5.5);
+        // 根节点挂在场景的 Ground 下
+        assert_eq!(transform.m_father.unwrap().file_id, 11);
+
+        let renderer_id = crate_object.m_component[1].component.file_id;
+        assert_eq!(unity_scene.index[&renderer_id], "MeshRenderer");
+        let renderer: UnityMeshRenderer = serde_yaml::from_str(&unity_scene.mesh_renderers_raw[&renderer_id]).unwrap();
+        assert_eq!(renderer.m_children[0].guid, "00000000000000000000000000000002");
+    }
+
+    #[test]
+    fn test_nested_prefab() {
+        let unity_scene = resolved_scene();
+        let (crate_id, crate_object) = find_object(&unity_scene, "Crate");
+        let crate_transform_id = crate_object.m_component[0].component.file_id;
+        assert_ne!(crate_id, crate_transform_id);
+
+        // 嵌套预制体的修改与外层实例对嵌套对象的修改都生效
+        let (lamp_id, lamp) = find_object(&unity_scene, "CrateLamp");
+        let transform = transform_of(&unity_scene, &lamp);
+        assert_eq!(transform.m_local_position.y, 3.0);
+        assert_eq!(transform.m_father.unwrap().file_id, crate_transform_id);
+        assert_eq!(transform.m_game_object.file_id, lamp_id);
+    }
+
+    #[test]
+    fn test_set_property_paths() {
+        let mut value: Value = serde_yaml::from_str("m_Materials: [a, b]\nm_LocalPosition: {x: 0}").unwrap();
+        assert!(set_property(&mut value, "m_Materials.Array.size", Value::from(1)));
+        assert!(set_property(&mut value, "m_Materials.Array.data[2]", Value::from("c")));
+        assert!(set_property(&mut value, "m_LocalPosition.z", Value::from(2.0)));
+        assert_eq!(value["m_Materials"], serde_yaml::from_str::<Value>("[a, null, c]").unwrap());
+        assert_eq!(value["m_LocalPosition"]["z"].as_f64(), Some(2.0));
+        assert!(!set_property(&mut value, "m_Materials.foo", Value::Null));
+    }
+}
@@ -5,8 +5,8 @@ use std::sync::Arc;
 use log::*;
 use wgpu::{Device, Queue, SurfaceConfiguration};
 use crate::entity::{Entity};
-use crate::materials::{Material, Texture};
-use crate::mesh::Mesh;
+use crate::materials::{Material, MaterialDesc, Texture};
+use crate::mesh::{Mesh, MeshData};
 use crate::scene::Scene;
 use crate::unity::UnityReference;
 
@@ -25,8 +25,7 @@ use wasm_bindgen::prelude::*;
 
 #[cfg(target_arch = "wasm32")]
 fn format_url(file_name: &str) -> reqwest::Url {
-    let window = web_sys::window().unwrap();
-    let location = window.location();
+    // 不依赖 window，Worker 中同样可用
     #[cfg(debug_assertions)]
     const API_URL: &str = "http://127.0.0.1:8000";
 
@@ -210,6 +209,57 @@ fn transfer_file(file_path: &str)  -> anyhow::Result<Vec<u8>> {
 pub type MeshId = String;
 pub type MaterialId = String;
 
+// guid.json：guid -> 资源文件路径，不依赖 GPU，离线烘焙也使用
+#[derive(Debug, Default)]
+pub struct AssetManifest {
+    paths: HashMap<String, String>,
+    // 测试中直接提供的文件内容，按 guid 索引
+    #[cfg(test)]
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl AssetManifest {
+    pub async fn load() -> anyhow::Result<Self> {
+        let guids = ResourceManager::load_binary("guid.json").await.map_err(|e| {
+            info!("Failed to load guid: {}", e);
+            e
+        })?;
+
+        Ok(Self {
+            paths: serde_json::from_str(std::str::from_utf8(&guids)?)?,
+            #[cfg(test)]
+            files: HashMap::new(),
+        })
+    }
+
+    #[cfg(test)]
+    pub fn from_files(files: HashMap<String, Vec<u8>>) -> Self {
+        let paths = files.keys().map(|guid| (guid.clone(), guid.clone())).collect();
+        Self { paths, files }
+    }
+
+    pub fn get(&self, guid: &str) -> Option<&String> {
+        self.paths.get(guid)
+    }
+
+    // 按 guid.json 读取资源文件
+    pub async fn load_guid_file(&self, guid: &str) -> anyhow::Result<Vec<u8>> {
+        let Some(file_path) = self.get(guid) else {
+            anyhow::bail!("guid.json 中没有 {}", guid);
+        };
+        #[cfg(test)]
+        if let Some(bytes) = self.files.get(guid) {
+            return Ok(bytes.clone());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        let bytes = transfer_file(file_path)?;
+        #[cfg(target_arch = "wasm32")]
+        let bytes = ResourceManager::load_binary(file_path).await?;
+
+        Ok(bytes)
+    }
+}
+
 /// 资源加载状态
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResourceLoadState {
@@ -228,11 +278,12 @@ struct ResourceUsageInfo {
 
 #[derive(Debug)]
 pub struct ResourceManager {
-    materials: HashMap<Entity, Arc<Material>>,
+    // 按 MeshRenderer 的材质槽位保存，第 i 个对应 mesh 的第 i 个子网格
+    materials: HashMap<Entity, Vec<Option<Arc<Material>>>>,
     meshes: HashMap<Entity, Arc<Mesh>>,
     mesh_manifest: HashMap<MeshId, Arc<Mesh>>,
     material_manifest: HashMap<MaterialId, Arc<Material>>,
-    manifest: HashMap<String, String>,
+    manifest: AssetManifest,
     texture_manifest: HashMap<String, Arc<Texture>>,
     white_texture: Arc<Texture>,
 
@@ -267,7 +318,7 @@ impl ResourceManager {
             meshes: Default::default(),
             mesh_manifest: Default::default(),
             material_manifest: Default::default(),
-            manifest: HashMap::default(),
+            manifest: AssetManifest::default(),
             texture_manifest: Default::default(),
             white_texture: Arc::new(white_texture),
 
@@ -280,15 +331,19 @@ impl ResourceManager {
     }
     
     pub async fn loading_mapping(&mut self) -> anyhow::Result<()>{
-        let guids = Self::load_binary("guid.json").await.map_err(|e| {
-            info!("Failed to load guid: {}", e);
-            e
-        })?;
-        
-        self.manifest = serde_json::from_str(std::str::from_utf8(&guids)?)?;
+        self.manifest = AssetManifest::load().await?;
         Ok(())
     }
 
+    pub fn manifest(&self) -> &AssetManifest {
+        &self.manifest
+    }
+
+    #[cfg(test)]
+    pub fn set_manifest(&mut self, manifest: AssetManifest) {
+        self.manifest = manifest;
+    }
+
     // 读取二进制数据
     pub async fn load_binary(file_name: &str) -> anyhow::Result<Vec<u8>> {
         #[cfg(target_arch = "wasm32")]
@@ -337,8 +392,8 @@ impl ResourceManager {
         Ok(data)
     }
 
-     pub fn has_mesh(&self, guid: &str) -> Option<Arc<Mesh>> {
-        self.mesh_manifest.get(guid).map(Arc::clone)
+     pub fn has_mesh(&self, id: &str) -> Option<Arc<Mesh>> {
+        self.mesh_manifest.get(id).map(Arc::clone)
     }
     
      pub fn has_material(&self, guid: &str) -> Option<Arc<Material>> {
@@ -347,61 +402,40 @@ impl ResourceManager {
 
     // 加载Mesh资源，顶点格式数据之类的
     pub async fn load_mesh(&mut self, m_mesh: &UnityReference, entity: Entity, device: &Device, scene: &Scene, material: &Material, config: &SurfaceConfiguration) -> anyhow::Result<u32> {
+        self.load_mesh_with(m_mesh, None, entity, device, scene, material, config).await
+    }
+
+    // 烘焙包中的 mesh 已经解码，直接上传
+    #[allow(clippy::too_many_arguments)]
+    pub async fn load_baked_mesh(&mut self, m_mesh: &UnityReference, data: Option<&MeshData>, entity: Entity, device: &Device, scene: &Scene, material: &Material, config: &SurfaceConfiguration) -> anyhow::Result<u32> {
+        self.load_mesh_with(m_mesh, data, entity, device, scene, material, config).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn load_mesh_with(&mut self, m_mesh: &UnityReference, data: Option<&MeshData>, entity: Entity, device: &Device, scene: &Scene, material: &Material, config: &SurfaceConfiguration) -> anyhow::Result<u32> {
         let guid = &m_mesh.guid;
-        let file_id = &m_mesh.file_id;
+        let id = Mesh::mesh_id(m_mesh);
         // println!("Loading mesh {:?}", m_mesh);
 
-        let mesh: Arc<Mesh> = if let Some(mesh) = self.has_mesh(guid) {
+        let mesh: Arc<Mesh> = if let Some(mesh) = self.has_mesh(&id) {
             mesh
         } else {
-            // 常见的 Unity 内置 Mesh fileID：
-            // fileIDMesh 类型
-            // 10202 Cube（立方体）
-            // 10206 Cylinder（圆柱体）
-            // 10207 Sphere（球体）
-            // 10208 Capsule（胶囊体）
-            // 10209 Plane（平面，10×10 单位）
-            // 10210 Quad（四边形，1×1 单位）
-            let mesh = match (file_id, guid.as_str()) {
-                (10202, "0000000000000000e000000000000000") => {
-                    Mesh::create_default_cube(guid, device, scene, material, config)
-                },
-                (10206, "0000000000000000e000000000000000") => {
-                    Mesh::create_default_cylinder(guid, device, scene, material, config)
-                },
-                (10207, "0000000000000000e000000000000000") => {
-                    Mesh::create_default_sphere(guid, device, scene, material, config)
-                },
-                (10208, "0000000000000000e000000000000000") => {
-                    Mesh::create_default_capsule(guid, device, scene, material, config)
-                },
-                (10209, "0000000000000000e000000000000000") => {
-                    Mesh::create_default_plane(guid, device, scene, material, config)
-                },
-                (10210, "0000000000000000e000000000000000") => {
-                    Mesh::create_default_quad(guid, device, scene, material, config)
-                },
-                _ => {
-                    let file_path = self.manifest.get(guid).unwrap();
-
-                    #[cfg(not(target_arch = "wasm32"))]
-                    let bytes = transfer_file(&file_path)?;
-
-                    #[cfg(target_arch = "wasm32")]
-                    let bytes = ResourceManager::load_binary(file_path).await.map_err(|e| {
-                        println!("load_mesh error: {:?}", e);
-                        e
-                    })?;
-
-                    Mesh::from_unity_data(&bytes, guid, device, scene, material, config).await.map_err(|e| {
-                        println!("Failed to load mesh: {:?}", guid);
-                        e
-                    })?
-                }
+            let mesh = if let Some(mesh) = Mesh::create_builtin(m_mesh, device, scene, material, config) {
+                mesh
+            } else if let Some(data) = data {
+                Mesh::from_data(data, &id, device, scene, material, config)
+            } else {
+                let bytes = self.manifest.load_guid_file(guid).await.inspect_err(|e| {
+                    println!("load_mesh error: {:?}", e);
+                })?;
+
+                Mesh::from_unity_data(&bytes, &id, device, scene, material, config).await.inspect_err(|_e| {
+                    println!("Failed to load mesh: {:?}", guid);
+                })?
             };
-            
+
             let mesh_arc = Arc::new(mesh);
-            self.mesh_manifest.insert(guid.to_string(), Arc::clone(&mesh_arc));
+            self.mesh_manifest.insert(id, Arc::clone(&mesh_arc));
             mesh_arc
         };
         self.meshes.insert(entity, mesh);
@@ -409,26 +443,47 @@ impl ResourceManager {
         Ok(entity.id())
     }
 
-    // 加载mat资源材质包，暂时使用实体的Id
-    pub async fn load_material(&mut self, entity: Entity, guid: &MaterialId, device: &Device, queue: &Queue) -> anyhow::Result<u32> {
-        println!("Loading {:?} material: {:?}", &entity, guid);
+    // 加载 MeshRenderer 的全部材质槽位，找不到的槽位留空
+    pub async fn load_materials(&mut self, entity: Entity, m_materials: &[UnityReference], device: &Device, queue: &Queue) -> anyhow::Result<u32> {
+        let mut slots = Vec::with_capacity(m_materials.len());
+        for m_material in m_materials {
+            println!("Loading {:?} material: {:?}", &entity, m_material.guid);
+            slots.push(self.load_material(&m_material.guid, None, device, queue).await?);
+        }
+        self.materials.insert(entity, slots);
+        Ok(entity.id())
+    }
+
+    // 烘焙包中的材质槽位，材质已解析为 (guid, MaterialDesc)
+    pub async fn load_baked_materials(&mut self, entity: Entity, slots: &[Option<(&MaterialId, &MaterialDesc)>], device: &Device, queue: &Queue) -> anyhow::Result<u32> {
+        let mut materials = Vec::with_capacity(slots.len());
+        for slot in slots {
+            let material = match slot {
+                Some((guid, desc)) => self.load_material(guid, Some(desc), device, queue).await?,
+                None => None,
+            };
+            materials.push(material);
+        }
+        self.materials.insert(entity, materials);
+        Ok(entity.id())
+    }
+
+    // 加载mat资源材质包
+    async fn load_material(&mut self, guid: &MaterialId, desc: Option<&MaterialDesc>, device: &Device, queue: &Queue) -> anyhow::Result<Option<Arc<Material>>> {
         // 处理材默认材质问题
         let material: Arc<Material> = if let Some(mat) = self.has_material(guid) {
             mat
+        } else if let Some(desc) = desc {
+            let material_arc = Arc::new(Material::from_desc(desc, guid, device, queue, self).await?);
+            self.material_manifest.insert(guid.to_string(), Arc::clone(&material_arc));
+            material_arc
         } else {
-            let Some(file_path) = self.manifest.get(guid) else {
+            if self.manifest.get(guid).is_none() {
                 info!("No mesh or material found for {}", guid);
-                return Ok(0);
-            };
-            #[cfg(not(target_arch = "wasm32"))]
-            let mat_bytes = transfer_file(&file_path).map_err(|e| {
-                println!("transfer_file error: {:?}", e);
-                e
-            })?;
-            #[cfg(target_arch = "wasm32")]
-            let mat_bytes = ResourceManager::load_binary(file_path).await.map_err(|e| {
+                return Ok(None);
+            }
+            let mat_bytes = self.manifest.load_guid_file(guid).await.inspect_err(|e| {
                 println!("Load mat asset error: {:?}, file_name: {:?}", e, guid);
-                e
             })?;
 
             // 后续处理多布局layout的问题, 可能共用mesh, 会有优化部分, 先使用entity_id
@@ -438,8 +493,7 @@ impl ResourceManager {
             self.material_manifest.insert(guid.to_string(), Arc::clone(&material_arc));
             material_arc
         };
-        self.materials.insert(entity, material);
-        Ok(entity.id())
+        Ok(Some(material))
     }
     
     // 加载贴图
@@ -451,14 +505,8 @@ impl ResourceManager {
             let tex = if let Some(texture) = Texture::from_unity_guid(device, queue, guid) {
                 texture
             } else {
-                let file_path = self.manifest.get(guid).unwrap();
-
-                #[cfg(not(target_arch = "wasm32"))]
-                let texture_bytes = transfer_file(&file_path)?;
-                #[cfg(target_arch = "wasm32")]
-                let texture_bytes = ResourceManager::load_binary(file_path).await.map_err(|e| {
-                    println!("Load mat asset error: {:?}, file_name: {:?}", e, guid);
-                    e
+                let texture_bytes = self.manifest.load_guid_file(guid).await.inspect_err(|e| {
+                    println!("Load texture error: {:?}, file_name: {:?}", e, guid);
                 })?;
 
                 // 后续处理多布局layout的问题, 可能共用mesh, 会有优化部分, 先使用entity_id
@@ -474,8 +522,13 @@ impl ResourceManager {
         Ok(texture)
     }
 
+    // 第一个加载成功的材质
     pub fn get_material(&self, entity: &Entity) -> Option<&Arc<Material>> {
-        self.materials.get(entity)
+        self.materials.get(entity)?.iter().flatten().next()
+    }
+
+    pub fn get_materials(&self, entity: &Entity) -> Option<&[Option<Arc<Material>>]> {
+        self.materials.get(entity).map(Vec::as_slice)
     }
 
     pub fn get_mesh(&self, entity: &Entity) -> Option<&Arc<Mesh>> {
@@ -486,7 +539,7 @@ impl ResourceManager {
         self.texture_manifest.get(guid).map(Arc::clone)
     }
     
-    pub fn get_guid_file(&self, id: &String) -> Option<&String> {
+    pub fn get_guid_file(&self, id: &str) -> Option<&String> {
         self.manifest.get(id)
     }
     pub fn get_white_texture(&self) -> Arc<Texture> {
@@ -7,7 +7,7 @@ use wgpu::{Device, Queue, SurfaceConfiguration};
 use crate::camera::Camera;
 use crate::entity::{Entity, InstanceRaw, Transform, TransformSystem};
 use crate::light::{DirectionalLight, LightManager, PointLight};
-use crate::materials::Texture;
+use crate::mesh::sub_mesh_for_slot;
 use crate::resource::{MaterialId, MeshId, ResourceManager};
 use crate::utils::get_background_color;
 
@@ -17,8 +17,9 @@ use log::{error, info};
 use wgpu::util::DeviceExt;
 
 use crate::ray::Ray;
+use crate::bake::BakedScene;
 use crate::unity::{
-    Component, UnityGameObject, UnityMeshFilter, UnityMeshRenderer, UnityScene, UnityTransform,
+    UnityGameObject, UnityMeshFilter, UnityMeshRenderer, UnityReference, UnityScene, UnityTransform,
 };
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
@@ -79,6 +80,7 @@ pub struct Scene {
 // render一次批量
 struct RenderBatch {
     pub mesh_id: MeshId,
+    pub sub_mesh: usize,
     pub material_id: MaterialId,
     pub entities: Vec<Entity>,                 // 属于这个批次的entities
     pub instance_buffer: Option<wgpu::Buffer>, // 延迟创建
@@ -86,8 +88,8 @@ struct RenderBatch {
 }
 
 pub struct RenderBatchSystem {
-    // 按(mesh_id, material_id)分组的渲染批次
-    batches: HashMap<(MeshId, MaterialId), RenderBatch>,
+    // 按(mesh_id, 子网格, material_id)分组的渲染批次
+    batches: HashMap<(MeshId, usize, MaterialId), RenderBatch>,
     // 是否需要重建批次(当entity增删或组件变化时)
     dirty: bool,
 }
@@ -172,27 +174,170 @@ impl RenderBatchSystem {
             let Some(mesh) = resource_manager.get_mesh(entity) else {
                 continue;
             };
-            let Some(material) = resource_manager.get_material(entity) else {
+            let Some(materials) = resource_manager.get_materials(entity) else {
                 continue;
             };
-            // let id = mesh.id.clone();
-            let key = (mesh.id.clone(), material.id.clone());
-            self.batches
-                .entry(key)
-                .or_insert_with(|| RenderBatch {
-                    mesh_id: mesh.id.clone(),
-                    material_id: material.id.clone(),
-                    entities: Vec::new(),
-                    instance_buffer: None,
-                    instance_count: 0,
-                })
-                .entities
-                .push(entity.clone());
+            for (slot, material) in materials.iter().enumerate() {
+                let Some(material) = material else {
+                    continue;
+                };
+                let Some(sub_mesh) = sub_mesh_for_slot(slot, mesh.sub_meshes.len()) else {
+                    break;
+                };
+                // 线、点等拓扑暂不渲染
+                if !mesh.sub_meshes[sub_mesh].is_triangles() {
+                    continue;
+                }
+                let key = (mesh.id.clone(), sub_mesh, material.id.clone());
+                self.batches
+                    .entry(key)
+                    .or_insert_with(|| RenderBatch {
+                        mesh_id: mesh.id.clone(),
+                        sub_mesh,
+                        material_id: material.id.clone(),
+                        entities: Vec::new(),
+                        instance_buffer: None,
+                        instance_count: 0,
+                    })
+                    .entities
+                    .push(*entity);
+            }
         }
         self.dirty = true;
     }
 }
 
+// 场景中一个 GameObject 解析后的结果，YAML 加载与离线烘焙共用
+#[derive(Debug, Clone)]
+pub struct SceneObject {
+    pub entity: Entity,
+    pub active: bool,
+    // 挂载了 Transform 组件
+    pub has_transform: bool,
+    pub transform: Transform,
+    pub parent: Option<Entity>,
+    pub is_light: bool,
+    // MeshFilter 引用的 mesh
+    pub mesh: Option<UnityReference>,
+    // MeshRenderer 的材质槽位
+    pub materials: Vec<UnityReference>,
+}
+
+// 解析场景中所有 GameObject 及其组件，按 fileID 排序
+pub fn collect_scene_objects(unity_scene: &UnityScene) -> anyhow::Result<Vec<SceneObject>> {
+    let indexs = &unity_scene.index; // 查看类型
+    let transforms_raw = &unity_scene.transforms_raw;
+    let mesh_renderers_raw = &unity_scene.mesh_renderers_raw;
+    let mesh_filters_raw = &unity_scene.mesh_filters_raw;
+
+    let mut entity_ids: Vec<&u32> = unity_scene.game_object_raw.keys().collect();
+    entity_ids.sort();
+
+    let mut objects = Vec::with_capacity(entity_ids.len());
+    for entity_id in entity_ids {
+        let game_object = &unity_scene.game_object_raw[entity_id];
+        let game_object = serde_yaml::from_str::<UnityGameObject>(game_object)
+            .map_err(|e| anyhow::anyhow!("GameObject {} 解析失败: {}", entity_id, e))?;
+        let mut object = SceneObject {
+            entity: Entity::new(*entity_id),
+            active: game_object.m_is_active == 1,
+            has_transform: false,
+            transform: Transform::new(),
+            parent: None,
+            is_light: false,
+            mesh: None,
+            materials: Vec::new(),
+        };
+
+        // 查看挂载的组件
+        for m_component in &game_object.m_component {
+            let file_id = m_component.component.file_id;
+            let s_type = indexs.get(&file_id);
+            match s_type {
+                // transform管理
+                Some(s) if s.as_str() == "Transform" => {
+                    let Some(transform_raw) = transforms_raw.get(&file_id) else {
+                        continue;
+                    };
+                    let unity_transform = serde_yaml::from_str::<UnityTransform>(transform_raw)
+                        .map_err(|e| {
+                            error!(" 解析错误:{:?} : {:?}", entity_id, e);
+                            e
+                        })?;
+                    // 通过transform查找children上的transform数据，transform对应;
+                    let local_transform = &mut object.transform;
+                    local_transform.set_position(&unity_transform.m_local_position);
+                    let unity_rot = &unity_transform.m_local_rotation;
+                    local_transform.set_rotation(cgmath::Quaternion::new(
+                        -unity_rot.w, // w 取负
+                        unity_rot.x,  // x 保持
+                        unity_rot.y,  // y 保持
+                        -unity_rot.z, // z 取负
+                    ));
+                    let unity_scale = &unity_transform.m_local_scale;
+                    local_transform.set_scale(Vector3::new(
+                        unity_scale.x,
+                        unity_scale.y,
+                        unity_scale.z,
+                    ));
+
+                    // 父节点为父 transform 挂载的 GameObject
+                    if let Some(m) = &unity_transform.m_father
+                        && let Some(transform_raw) = transforms_raw.get(&m.file_id) {
+                            match serde_yaml::from_str::<UnityTransform>(transform_raw) {
+                                Ok(transform) => {
+                                    object.parent = Some(Entity::new(transform.m_game_object.file_id));
+                                }
+                                Err(e) => {
+                                    error!("解析坐标异常错误:{:?} : {:?}", entity_id, e);
+                                    error!("Failed to deserialize transform: {}", e);
+                                }
+                            };
+                        }
+                    object.has_transform = true;
+                }
+                Some(s) if s.as_str() == "MeshRenderer" => {
+                    if let Some(content) = mesh_renderers_raw.get(&file_id) {
+                        match serde_yaml::from_str::<UnityMeshRenderer>(content) {
+                            Ok(mesh_render) => {
+                                object.materials = mesh_render.m_children;
+                            }
+                            Err(err) => {
+                                info!("Mesh Renderer{:?}", content);
+                                error!("Serde_yaml Failed to parse mesh renderer: {:?}", err);
+                            }
+                        };
+                    }
+                }
+                // 顶点数据
+                Some(s) if s.as_str() == "MeshFilter" => {
+                    if let Some(content) = mesh_filters_raw.get(&file_id) {
+                        match serde_yaml::from_str::<UnityMeshFilter>(content) {
+                            Ok(mesh_filter) => {
+                                object.mesh = Some(mesh_filter.m_mesh);
+                            }
+                            Err(e) => {
+                                info!("MeshFilter{:?}", e);
+                                error!("Serde_yaml Failed to parse mesh filter: {:?}", e);
+                                continue;
+                            }
+                        }
+                    }
+                }
+                Some(s) if s.as_str() == "Light"  => { // || s.as_str() == "SodaPointLight" 暂时不用
+                    object.is_light = true;
+                }
+                Some(s) if s.as_str() == "MonoBehaviour" => {
+                    // SodaPointLight是挂载在MonoBehaviour，需要特殊处理
+                }
+                _ => {}
+            }
+        }
+        objects.push(object);
+    }
+    Ok(objects)
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct SceneUniforms {
@@ -421,182 +566,94 @@ impl Scene {
         resource_manager: &mut ResourceManager,
         config: &SurfaceConfiguration,
     ) -> anyhow::Result<()> {
-        let indexs = &unity_scene.index; // 查看类型
-        let objects = &unity_scene.game_object_raw;
-        // let mut transforms: HashMap<u32, UnityTransform> = HashMap::new();
-        let transforms_raw = &unity_scene.transforms_raw;
-        // let mesh_renders = &unity_scene.mesh_renderers;
-        let mesh_renderers_raw = &unity_scene.mesh_renderers_raw;
-        // let mesh_filters = &unity_scene.mesh_filters;
-        let mesh_filters_raw = &unity_scene.mesh_filters_raw;
-        let test_id = 16188;
-        for (entity_id, game_object) in objects {
-            let game_object =
-                serde_yaml::from_str::<UnityGameObject>(game_object).map_err(|e| {
-                    error!("Failed to deserialize game object: {}: {:?}", entity_id, e);
-                    panic!("Failed to deserialize game object: {:?}", e);
-                    e
-                })?;
-            // 查看挂载的transform
-            let entity = Entity::new(*entity_id);
-            if game_object.m_is_active != 1 {
+        for object in collect_scene_objects(unity_scene)? {
+            let entity = object.entity;
+            if !object.active {
                 scene.hidden_entity(entity);
             }
-
-            if *entity_id == test_id {
-                info!("entity: {:?}", entity);
+            // 根据children设置父子关系
+            if object.has_transform && let Some(parent) = object.parent {
+                scene.transform_system.set_parent(parent, entity);
             }
-            let mut is_light = false;
-            let mut unity_mesh_render: Option<UnityMeshRenderer> = None;
-            let mut unity_mesh_filter: Option<UnityMeshFilter> = None;
-
-            let mut local_transform = Transform::new();
-
-            for m_component in &game_object.m_component {
-                let file_id = m_component.component.file_id.clone();
-                let s_type = indexs.get(&file_id);
-                match s_type {
-                    // transform管理
-                    Some(s) if s.as_str() == "Transform" => {
-                        let transform_raw = transforms_raw.get(&file_id);
-                        let Some(transform_raw) = transform_raw else {
-                            continue;
-                        };
-                        let unity_transform = serde_yaml::from_str::<UnityTransform>(transform_raw)
-                            .map_err(|e| {
-                                error!(" 解析错误:{:?} : {:?}", entity_id, e);
-                                e
-                            })?;
-                        if *entity_id == test_id {
-                            info!(
-                                "{}: transform: {:?}, gameobject : {:?}",
-                                *entity_id, unity_transform, &game_object
-                            );
-                        }
-                        // 通过transform查找children上的transform数据，transform对应;
-                        local_transform.set_position(&unity_transform.m_local_position);
-                        let unity_rot = &unity_transform.m_local_rotation;
-                        local_transform.set_rotation(cgmath::Quaternion::new(
-                            -unity_rot.w, // w 取负
-                            unity_rot.x,  // x 保持
-                            unity_rot.y,  // y 保持
-                            -unity_rot.z, // z 取负
-                        ));
-                        let unity_scale = &unity_transform.m_local_scale;
-                        local_transform.set_scale(Vector3::new(
-                            unity_scale.x,
-                            unity_scale.y,
-                            unity_scale.z,
-                        ));
-
-                        // 根据children设置父子关系
-                        if let Some(m) = &unity_transform.m_father {
-                            let transform_raw = transforms_raw.get(&m.file_id);
-
-                            if let Some(transform_raw) = transform_raw {
-                                match serde_yaml::from_str::<UnityTransform>(transform_raw) {
-                                    Ok(transform) => {
-                                        scene.transform_system.set_parent(
-                                            Entity::new(transform.m_game_object.file_id),
-                                            Entity::new(unity_transform.m_game_object.file_id),
-                                        );
-                                    }
-                                    Err(e) => {
-                                        error!("解析坐标异常错误:{:?} : {:?}", entity_id, e);
-                                        error!("Failed to deserialize transform: {}", e);
-                                    }
-                                };
-                            }
-                        }
-                        scene.entities.push(entity);
-                    }
-                    Some(s) if s.as_str() == "MeshRenderer" => {
-                        if let Some(content) = mesh_renderers_raw.get(&file_id) {
-                            match serde_yaml::from_str::<UnityMeshRenderer>(content) {
-                                Ok(mesh_render) => {
-                                    unity_mesh_render = Some(mesh_render);
-                                }
-                                Err(err) => {
-                                    info!("Mesh Renderer{:?}", content);
-                                    error!("Serde_yaml Failed to parse mesh renderer: {:?}", err);
-                                }
-                            };
-                        }
-                    }
-                    // 顶点数据
-                    Some(s) if s.as_str() == "MeshFilter" => {
-                        if let Some(content) = mesh_filters_raw.get(&file_id) {
-                            match serde_yaml::from_str::<UnityMeshFilter>(content) {
-                                Ok(mesh_filter) => {
-                                    unity_mesh_filter = Some(mesh_filter);
-                                }
-                                Err(e) => {
-                                    info!("MeshFilter{:?}", e);
-                                    error!("Serde_yaml Failed to parse mesh filter: {:?}", e);
-                                    continue;
-                                }
-                            }
-                        }
-                    }
-                    Some(s) if s.as_str() == "Light"  => { // || s.as_str() == "SodaPointLight" 暂时不用
-                        is_light = true;
-                    }
-                    Some(s) if s.as_str() == "MonoBehaviour" => {
-                        // SodaPointLight是挂载在MonoBehaviour，需要特殊处理
-                    }
-                    _ => {}
-                }
-                // println!("loading unity 顶点数据: {:?}", unity_mesh_filter);
-            }
-            if *entity_id == test_id {
-                info!("entity: {:?} End Ground", entity);
-            }
-            // 光照暂时不渲染
-            if is_light {
+            // 光照暂时不渲染，只登记实体，与烘焙包中没有世界矩阵的实体一致
+            if object.is_light {
+                scene.entities.push(entity);
                 continue;
             }
 
-            scene.add_entity(entity, local_transform);
+            scene.add_entity(entity, object.transform);
 
-            let Some(mesh_filter) = unity_mesh_filter else {
-                continue;
-            };
-            let Some(mesh_mesh_reference) = unity_mesh_render else {
+            let Some(m_mesh) = &object.mesh else {
                 continue;
             };
             // mesh隐藏不加载，地图有隐藏提前加载的物品是unity场景优化部分,存在顶部mesh不渲染，子gameobject渲染，但是实际同一个材质
             // if mesh_mesh_reference.m_enabled == 0u8 {
             //     scene.hidden_entity(entity);
-            //     println!("entity mesh_renderer {} 隐藏: {:?}", game_object.m_name, entity);
             //     continue;
             // }
 
-            if *entity_id == test_id {
-                info!("entity: {:?}", entity);
+            // 材质球，每个槽位对应一个子网格
+            resource_manager
+                .load_materials(entity, &object.materials, device, queue)
+                .await?;
+
+            let Some(material) = resource_manager.get_material(&entity).map(Arc::clone) else {
+                continue;
+            };
+
+            resource_manager
+                .load_mesh(m_mesh, entity, device, scene, &material, config)
+                .await?;
+        }
+
+        Self::finish_loading(scene, resource_manager);
+        Ok(())
+    }
+
+    // 加载烘焙包：实体已经带有世界矩阵与最终的显示状态，mesh 与材质无需再解析
+    pub async fn loading_baked(
+        device: &Device,
+        queue: &Queue,
+        scene: &mut Scene,
+        baked: &BakedScene,
+        resource_manager: &mut ResourceManager,
+        config: &SurfaceConfiguration,
+    ) -> anyhow::Result<()> {
+        for baked_entity in &baked.entities {
+            let entity = Entity::new(baked_entity.id);
+            if !baked_entity.visible {
+                scene.hidden_entity(entity);
             }
-            // 材质球
-            let Some(mesh_render) = mesh_mesh_reference.m_children.get(0) else {
+            // 没有世界矩阵的实体（父节点是光照等）与 YAML 路径一样不参与渲染
+            let Some(world) = baked_entity.world else {
+                scene.entities.push(entity);
                 continue;
             };
+            scene.add_entity(entity, Transform::from_matrix(world.into()));
 
+            let Some(mesh) = baked_entity.mesh.and_then(|i| baked.meshes.get(i as usize)) else {
+                continue;
+            };
+            let slots = baked.material_slots(baked_entity);
             resource_manager
-                .load_material(entity, &mesh_render.guid, device, queue)
+                .load_baked_materials(entity, &slots, device, queue)
                 .await?;
 
-            let material = { Arc::clone(resource_manager.get_material(&entity).unwrap()) };
+            let Some(material) = resource_manager.get_material(&entity).map(Arc::clone) else {
+                continue;
+            };
 
             resource_manager
-                .load_mesh(
-                    &mesh_filter.m_mesh,
-                    entity,
-                    device,
-                    scene,
-                    &material,
-                    config,
-                )
+                .load_baked_mesh(&mesh.reference, mesh.data.as_ref(), entity, device, scene, &material, config)
                 .await?;
         }
 
+        Self::finish_loading(scene, resource_manager);
+        Ok(())
+    }
+
+    // 实体加载完成后更新世界矩阵并重建渲染批次
+    fn finish_loading(scene: &mut Scene, resource_manager: &ResourceManager) {
         set_loading_state(SceneLoadingState::LoadingAssets, 0.7, "Loading Materials");
 
         scene.transform_system.update(&mut scene.entity_display_map);
@@ -621,8 +678,6 @@ impl Scene {
             "scene actually rendered all {} entities",
             scene.total_show_entities()
         );
-
-        Ok(())
     }
 
     pub fn add_entity(&mut self, entity: Entity, transform: Transform) {
@@ -861,11 +916,14 @@ impl Scene {
                 // println!("Mesh does not exist for {:?}", entity);
                 continue; // 没有 mesh 就跳过
             };
+            let Some(sub_mesh) = mesh.sub_meshes.get(batch.sub_mesh) else {
+                continue;
+            };
 
             render_pass.set_pipeline(&mesh.render_pipeline);
             render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
             render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
-            render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            render_pass.set_index_buffer(mesh.index_buffer.slice(..), mesh.index_format);
 
             // 设置渲染管线- 动态管线
             // bind_group全局资源
@@ -877,10 +935,10 @@ impl Scene {
             // print!("pipeline {},", batch.entities.len());
 
             render_pass.set_bind_group(2, &material.bind_group, &[]);
-            // info!("index_count: {:?}, 实例数: {:?}", mesh.index_count, batch.instance_count);
+            // info!("index_count: {:?}, 实例数: {:?}", sub_mesh.index_count, batch.instance_count);
             // 创建pipeline 布局等等，设置buffer之类
             // 使用 instance_count 而不是 entities.len()，因为视锥剔除后实际实例数可能更少
-            render_pass.draw_indexed(0..mesh.index_count, 0, 0..batch.instance_count);
+            render_pass.draw_indexed(sub_mesh.index_range(mesh.index_format), sub_mesh.base_vertex, 0..batch.instance_count);
 
             // 记录本帧使用的资源
             used_resources.push((batch.mesh_id.clone(), batch.material_id.clone()));
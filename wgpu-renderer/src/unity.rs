@@ -1,7 +1,5 @@
-use serde::{de, Deserialize, Deserializer, Serialize};
-use std::collections::{HashMap, HashSet};
-use std::{fs};
-use std::io::{BufRead, BufReader};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use cgmath::{Point3, Vector3};
 use log::info;
@@ -69,6 +67,8 @@ pub struct UnityScene {
     pub soda_lights: HashMap<u32, UnitySodaPointLight>,
     pub box_colliders: HashMap<u32, UnityBoxCollider>,// 不太需要
     pub index: HashMap<u32, String>,// 只保留索引
+    // 预制体实例，fileID 可能超出 u32
+    pub prefab_instances_raw: Vec<(i64, String)>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -114,7 +114,7 @@ impl UnityAsset for UnityTransform {
 }
 
 
-#[derive(Debug, Clone, Serialize, Default, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Default, Deserialize)]
 pub struct UnityReference {
     #[serde(rename = "fileID")]
     pub file_id: i64,
@@ -381,96 +381,118 @@ impl UnityScene {
             // lights: HashMap::new(),
             lights_raw:  HashMap::new(),
             soda_lights: HashMap::new(),
+            prefab_instances_raw: Vec::new(),
         }
     }
     // 从str返回一个Unity场景对象，多个Object，解析.unity
     pub async fn from_str(&mut self, file_path: PathBuf) -> anyhow::Result<Self> {
-        let mut start: bool = false;
-        let mut content = String::new();
-        let mut old_component_id: u32 = 0;
-        let mut old_component_name: Option<String> = None;
-        // 场景资源
-        let mut unity_scene = UnityScene::new();
         use crate::resource::{ResourceManager};
         // // 根据平台使用不同的加载方式
         info!("Scene 加载: {}", file_path.display());
         let bytes = ResourceManager::load_binary(file_path.to_str().unwrap()).await?;
 
-        let reader = BufReader::new(&bytes[..]);
-        
-        for line in reader.lines() {
-            let line = line?;
-            // --- 开始追加捕获
-            if line.starts_with("---") {
-                start = true;
-        
-                // 为空表示首次数据
-                if !content.trim().is_empty() {
-                    // let processed = preprocess_yaml(&content);
-                    let name = &old_component_name;
-                    match name {
-                        Some(name) if name.as_str() == "GameObject" => {
-                            unity_scene.game_object_raw.insert(old_component_id, content.clone());
-                        }
-
-                        Some(name) if name.as_str() == "MeshCollider" => {
-                            unity_scene.mesh_colliders_raw.insert(old_component_id, content.clone());
-                        }
-                        Some(name) if name.as_str() == "Transform" => {
-                            unity_scene.transforms_raw.insert(old_component_id, content.clone());
-                        }
-                        Some(name) if name.as_str() == "MeshRenderer" => {
-                            unity_scene.mesh_renderers_raw.insert(old_component_id, content.clone());
-                        }
-                        // Some(name) if name.as_str() == "BoxCollider" => {
-                        //
-                        // }
-                        Some(name) if name.as_str() == "Light" => {
-                            unity_scene.lights_raw.insert(old_component_id, content.clone());
-                        }
-                        Some(name) if name.as_str() == "MeshFilter" => {
-                            unity_scene.mesh_filters_raw.insert(old_component_id, content.clone());
-                        }
-                        _ => {}
-                    }
-                    unity_scene.index.insert(old_component_id, name.clone().unwrap().to_string());
-                    content.clear(); // 清空内容
-                }
-                let tags = line.split(' ');
-                // 当前组件的id，一般都是从Object 开始遍历，不用担心顺序的问题
-                match tags
-                    .collect::<Vec<_>>()
-                    .get(2)
-                    .unwrap()
-                    .replace("&", "")
-                    .to_string()
-                    .parse::<i32>()
-                {
-                    Ok(o_id) => old_component_id = o_id as u32,
-                    Err(_) => continue,
-                }
-        
+        // 清洗数据，包含light等都要清洗
+        // fs::write("./save_j_lab_2.json", serde_json::to_string_pretty(&unity_scene).unwrap())?;
+
+        Ok(Self::from_text(std::str::from_utf8(&bytes)?))
+    }
+
+    // 按文档归类场景内容，预制体实例留给 resolve_prefabs 处理；
+    // stripped 文档与展开后的对象 fileID 相同，内容没有用处，直接跳过
+    pub fn from_text(text: &str) -> Self {
+        let mut unity_scene = UnityScene::new();
+        for document in split_documents(text) {
+            if document.stripped {
                 continue;
             }
-            if !start {
+            if document.class_name == "PrefabInstance" {
+                unity_scene.prefab_instances_raw.push((document.file_id, document.content));
                 continue;
             }
-            if !line.starts_with(' ') {
-                old_component_name = Some(line.replace(':', ""));
+            // 场景自身的对象 fileID 都比较小，超出范围的暂不处理
+            let Ok(file_id) = u32::try_from(document.file_id) else {
                 continue;
-            }
-            content = content + &line;
-            content.push('\n');
+            };
+            unity_scene.insert_raw(file_id, &document.class_name, document.content);
         }
+        unity_scene
+    }
 
-        // 清洗数据，包含light等都要清洗
-        // fs::write("./save_j_lab_2.json", serde_json::to_string_pretty(&unity_scene).unwrap())?;
+    // 按类名放入对应的原始数据表，并记录索引
+    pub(crate) fn insert_raw(&mut self, file_id: u32, class_name: &str, content: String) {
+        match class_name {
+            "GameObject" => {
+                self.game_object_raw.insert(file_id, content);
+            }
+            "MeshCollider" => {
+                self.mesh_colliders_raw.insert(file_id, content);
+            }
+            "Transform" => {
+                self.transforms_raw.insert(file_id, content);
+            }
+            "MeshRenderer" => {
+                self.mesh_renderers_raw.insert(file_id, content);
+            }
+            "Light" => {
+                self.lights_raw.insert(file_id, content);
+            }
+            "MeshFilter" => {
+                self.mesh_filters_raw.insert(file_id, content);
+            }
+            _ => {}
+        }
+        self.index.insert(file_id, class_name.to_string());
+    }
+}
 
-        Ok(unity_scene)
+/// YAML 中的一个文档：`--- !u!<classID> &<fileID> [stripped]` 之后的类名与缩进内容
+#[derive(Debug, Clone)]
+pub struct UnityDocument {
+    pub file_id: i64,
+    pub class_name: String,
+    // 预制体实例中对象的占位，只有 m_CorrespondingSourceObject 与 m_PrefabInstance
+    pub stripped: bool,
+    pub content: String,
+}
+
+// 将 .unity / .prefab 文本拆分为文档
+pub fn split_documents(text: &str) -> Vec<UnityDocument> {
+    let mut documents = Vec::new();
+    let mut current: Option<UnityDocument> = None;
+
+    for line in text.lines() {
+        // --- 开始追加捕获
+        if line.starts_with("---") {
+            documents.extend(current.take().filter(|document| !document.class_name.is_empty()));
+            let tags: Vec<&str> = line.split(' ').collect();
+            // 当前组件的id，一般都是从Object 开始遍历，不用担心顺序的问题
+            let Some(Ok(file_id)) = tags.get(2).map(|tag| tag.trim_start_matches('&').parse::<i64>()) else {
+                continue;
+            };
+            current = Some(UnityDocument {
+                file_id,
+                class_name: String::new(),
+                stripped: tags.get(3) == Some(&"stripped"),
+                content: String::new(),
+            });
+            continue;
+        }
+        let Some(document) = current.as_mut() else {
+            continue;
+        };
+        if !line.starts_with(' ') {
+            document.class_name = line.trim_end().trim_end_matches(':').to_string();
+            continue;
+        }
+        document.content.push_str(line);
+        document.content.push('\n');
     }
+    documents.extend(current.filter(|document| !document.class_name.is_empty()));
+
+    documents
 }
 
-// Unity 顶点属性对应
+// Unity 顶点属性对应，值为 m_Channels 中的下标
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum UnityVertexAttribute {
@@ -482,14 +504,18 @@ pub enum UnityVertexAttribute {
     TexCoord1 = 5,
     TexCoord2 = 6,
     TexCoord3 = 7,
-    BlendWeight = 8,
-    BlendIndices = 9,
+    TexCoord4 = 8,
+    TexCoord5 = 9,
+    TexCoord6 = 10,
+    TexCoord7 = 11,
+    BlendWeight = 12,
+    BlendIndices = 13,
 }
 
 impl UnityVertexAttribute {
-    // 反向转换（可选）
-    pub fn from_u8(value: u8) -> Self {
-        match value {
+    // 未知的下标返回错误，不能当作 Position 覆盖真实的顶点位置
+    pub fn from_u8(value: u8) -> anyhow::Result<Self> {
+        let attribute = match value {
             0 => UnityVertexAttribute::Position,
             1 => UnityVertexAttribute::Normal,
             2 => UnityVertexAttribute::Tangent,
@@ -498,10 +524,15 @@ impl UnityVertexAttribute {
             5 => UnityVertexAttribute::TexCoord1,
             6 => UnityVertexAttribute::TexCoord2,
             7 => UnityVertexAttribute::TexCoord3,
-            8 => UnityVertexAttribute::BlendWeight,
-            9 => UnityVertexAttribute::BlendIndices,
-            _ => UnityVertexAttribute::Position,
-        }
+            8 => UnityVertexAttribute::TexCoord4,
+            9 => UnityVertexAttribute::TexCoord5,
+            10 => UnityVertexAttribute::TexCoord6,
+            11 => UnityVertexAttribute::TexCoord7,
+            12 => UnityVertexAttribute::BlendWeight,
+            13 => UnityVertexAttribute::BlendIndices,
+            _ => anyhow::bail!("Unknown vertex attribute {}", value),
+        };
+        Ok(attribute)
     }
 }
 
@@ -517,8 +548,12 @@ impl From<UnityVertexAttribute> for u8 {
             UnityVertexAttribute::TexCoord1 => 5,
             UnityVertexAttribute::TexCoord2 => 6,
             UnityVertexAttribute::TexCoord3 => 7,
-            UnityVertexAttribute::BlendWeight => 8,
-            UnityVertexAttribute::BlendIndices => 9,
+            UnityVertexAttribute::TexCoord4 => 8,
+            UnityVertexAttribute::TexCoord5 => 9,
+            UnityVertexAttribute::TexCoord6 => 10,
+            UnityVertexAttribute::TexCoord7 => 11,
+            UnityVertexAttribute::BlendWeight => 12,
+            UnityVertexAttribute::BlendIndices => 13,
         }
     }
 }
@@ -542,8 +577,9 @@ pub enum UnityVertexFormat {
 }
 
 impl UnityVertexFormat {
-    pub fn from_u8(value: u8) -> Self {
-        match value {
+    // u8::MAX 为 Zero 写入烘焙包后的值，其余未知的格式返回错误
+    pub fn from_u8(value: u8) -> anyhow::Result<Self> {
+        let format = match value {
             0 => UnityVertexFormat::Float32,
             1 => UnityVertexFormat::Float16,
             2 => UnityVertexFormat::UNorm8,
@@ -556,12 +592,52 @@ impl UnityVertexFormat {
             9 => UnityVertexFormat::SInt16,
             10 => UnityVertexFormat::UInt32,
             11 => UnityVertexFormat::SInt32,
-            _ => UnityVertexFormat::Zero,
+            u8::MAX => UnityVertexFormat::Zero,
+            _ => anyhow::bail!("Unknown vertex format {}", value),
+        };
+        Ok(format)
+    }
+
+    // 单个分量的字节数，0 代表不存在
+    pub fn size_in_bytes(&self) -> u8 {
+        match self {
+            UnityVertexFormat::Float32 => 4,
+            UnityVertexFormat::Float16 => 2,
+            UnityVertexFormat::UNorm8
+            | UnityVertexFormat::SNorm8
+            | UnityVertexFormat::UInt8
+            | UnityVertexFormat::SInt8 => 1,
+            UnityVertexFormat::UNorm16
+            | UnityVertexFormat::SNorm16
+            | UnityVertexFormat::UInt16
+            | UnityVertexFormat::SInt16 => 2,
+            UnityVertexFormat::UInt32 | UnityVertexFormat::SInt32  => 4,
+            UnityVertexFormat::Zero => 0,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+impl From<UnityVertexFormat> for u8 {
+    fn from(format: UnityVertexFormat) -> u8 {
+        match format {
+            UnityVertexFormat::Float32 => 0,
+            UnityVertexFormat::Float16 => 1,
+            UnityVertexFormat::UNorm8 => 2,
+            UnityVertexFormat::SNorm8 => 3,
+            UnityVertexFormat::UNorm16 => 4,
+            UnityVertexFormat::SNorm16 => 5,
+            UnityVertexFormat::UInt8 => 6,
+            UnityVertexFormat::SInt8 => 7,
+            UnityVertexFormat::UInt16 => 8,
+            UnityVertexFormat::SInt16 => 9,
+            UnityVertexFormat::UInt32 => 10,
+            UnityVertexFormat::SInt32 => 11,
+            UnityVertexFormat::Zero => u8::MAX,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UnityVertexAttributeDescriptor {
     // 表示为m_channels的下标 顺序表示
     pub attribute: UnityVertexAttribute,
@@ -572,28 +648,14 @@ pub struct UnityVertexAttributeDescriptor {
 }
 
 impl UnityVertexAttributeDescriptor {
+    // 分量个数，dimension 高 4 位是 unity 的附加标记（52 也是 4）
+    pub fn components(&self) -> u8 {
+        self.dimension & 0x0F
+    }
+
     pub fn size_in_bytes(&self) -> u8 {
         // 0 代表不存在
-        let code = match self.format {
-            UnityVertexFormat::Float32 => 4,
-            UnityVertexFormat::Float16 => 2,
-            UnityVertexFormat::UNorm8
-            | UnityVertexFormat::SNorm8
-            | UnityVertexFormat::UInt8
-            | UnityVertexFormat::SInt8 => 1,
-            UnityVertexFormat::UNorm16
-            | UnityVertexFormat::SNorm16
-            | UnityVertexFormat::UInt16
-            | UnityVertexFormat::SInt16 => 2,
-            UnityVertexFormat::UInt32 | UnityVertexFormat::SInt32  => 4,
-            UnityVertexFormat::Zero => 0,
-        };
-        let dimension = if self.dimension == 52 {
-            4
-        } else {
-            self.dimension
-        };
-        code * dimension
+        self.format.size_in_bytes() * self.components()
     }
 
     // 转换至wgpu需要的格式
@@ -641,8 +703,12 @@ impl UnityVertexAttributeDescriptor {
             UnityVertexAttribute::TexCoord1 => 5,
             UnityVertexAttribute::TexCoord2 => 6,
             UnityVertexAttribute::TexCoord3 => 7,
-            UnityVertexAttribute::BlendWeight => 8,
-            UnityVertexAttribute::BlendIndices => 9,
+            UnityVertexAttribute::TexCoord4 => 8,
+            UnityVertexAttribute::TexCoord5 => 9,
+            UnityVertexAttribute::TexCoord6 => 10,
+            UnityVertexAttribute::TexCoord7 => 11,
+            UnityVertexAttribute::BlendWeight => 12,
+            UnityVertexAttribute::BlendIndices => 13,
         }
     }
 }
@@ -675,6 +741,9 @@ pub struct VertexDataRaw{
 pub struct MeshRaw{
     #[serde(rename(deserialize = "m_Name"))]
     pub m_name: String,
+    // 0 为 UInt16，1 为 UInt32，旧版本资源没有该字段
+    #[serde(rename(deserialize = "m_IndexFormat"), default)]
+    pub index_format: u8,
     #[serde(rename(deserialize = "m_IndexBuffer"))]
     pub index_buffer: String,
     #[serde(rename(deserialize = "m_VertexData"))]
@@ -0,0 +1,295 @@
+use half::f16;
+use crate::unity::{UnityVertexAttribute, UnityVertexAttributeDescriptor, UnityVertexFormat};
+
+// 按 m_Channels 的描述解码 _typelessdata，统一转换为 MeshVertex 布局并翻转 Z 轴（unity 左手系 -> 右手系）
+
+/// 上传到 GPU 的统一顶点布局，对应 shader.wgsl 的 VertexInput
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub tangent: [f32; 4],
+    pub color: [f32; 4],
+    pub uv0: [f32; 2],
+}
+
+impl Default for MeshVertex {
+    fn default() -> Self {
+        Self {
+            position: [0.0; 3],
+            normal: [0.0; 3],
+            tangent: [0.0; 4],
+            color: [1.0; 4],
+            uv0: [0.0; 2],
+        }
+    }
+}
+
+impl MeshVertex {
+    /// MeshVertex 对应的顶点描述，用于生成 pipeline 的 buffer layout
+    pub fn descriptors() -> Vec<UnityVertexAttributeDescriptor> {
+        [
+            (UnityVertexAttribute::Position, 0, 3),
+            (UnityVertexAttribute::Normal, 12, 3),
+            (UnityVertexAttribute::Tangent, 24, 4),
+            (UnityVertexAttribute::Color, 40, 4),
+            (UnityVertexAttribute::TexCoord0, 56, 2),
+        ]
+        .into_iter()
+        .map(|(attribute, offset, dimension)| UnityVertexAttributeDescriptor {
+            attribute,
+            stream: 0,
+            offset,
+            format: UnityVertexFormat::Float32,
+            dimension,
+        })
+        .collect()
+    }
+
+    // shader 会用到的属性，其余通道跳过
+    fn uses(attribute: UnityVertexAttribute) -> bool {
+        matches!(
+            attribute,
+            UnityVertexAttribute::Position
+                | UnityVertexAttribute::Normal
+                | UnityVertexAttribute::Tangent
+                | UnityVertexAttribute::Color
+                | UnityVertexAttribute::TexCoord0
+        )
+    }
+
+    fn set(&mut self, attribute: UnityVertexAttribute, value: [f32; 4], components: usize) {
+        match attribute {
+            UnityVertexAttribute::Position => self.position = [value[0], value[1], -value[2]],
+            UnityVertexAttribute::Normal => self.normal = [value[0], value[1], -value[2]],
+            // 切线的 w 是手性，翻转 Z 后同样取反
+            UnityVertexAttribute::Tangent => self.tangent = [value[0], value[1], -value[2], -value[3]],
+            UnityVertexAttribute::Color => self.color[..components].copy_from_slice(&value[..components]),
+            // 贴图采样使用 ClampToEdge，uv 取小数部分映射到 [0,1]
+            UnityVertexAttribute::TexCoord0 => self.uv0 = [value[0].fract().abs(), value[1].fract().abs()],
+            _ => {}
+        }
+    }
+}
+
+/// 一个 stream 在 _typelessdata 中的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct StreamLayout {
+    offset: usize,
+    stride: usize,
+}
+
+/// unity 的多个 stream 依次存放，每个 stream 的起始位置按 16 字节对齐
+fn stream_layouts(descriptors: &[UnityVertexAttributeDescriptor], vertex_count: usize) -> Vec<StreamLayout> {
+    let stream_count = descriptors
+        .iter()
+        .filter(|desc| desc.components() > 0)
+        .map(|desc| desc.stream as usize + 1)
+        .max()
+        .unwrap_or(0);
+
+    let mut layouts = Vec::with_capacity(stream_count);
+    let mut offset = 0;
+    for stream in 0..stream_count {
+        let stride = descriptors
+            .iter()
+            .filter(|desc| desc.stream as usize == stream && desc.components() > 0)
+            .map(|desc| desc.offset as usize + desc.size_in_bytes() as usize)
+            .max()
+            .unwrap_or(0);
+        layouts.push(StreamLayout { offset, stride });
+        offset += stride * vertex_count;
+        offset = (offset + 15) & !15;
+    }
+    layouts
+}
+
+/// 读取一个分量并转换为 f32，归一化格式映射到 [0,1] / [-1,1]
+pub fn read_component(format: UnityVertexFormat, bytes: &[u8]) -> anyhow::Result<f32> {
+    let size = format.size_in_bytes() as usize;
+    if size == 0 {
+        anyhow::bail!("Unsupported vertex format: {:?}", format);
+    }
+    let Some(bytes) = bytes.get(..size) else {
+        anyhow::bail!("Vertex component {:?} needs {} bytes, got {}", format, size, bytes.len());
+    };
+    let value = match format {
+        UnityVertexFormat::Float32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        UnityVertexFormat::Float16 => f16::from_le_bytes([bytes[0], bytes[1]]).to_f32(),
+        UnityVertexFormat::UNorm8 => bytes[0] as f32 / u8::MAX as f32,
+        UnityVertexFormat::SNorm8 => (bytes[0] as i8 as f32 / i8::MAX as f32).max(-1.0),
+        UnityVertexFormat::UNorm16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f32 / u16::MAX as f32,
+        UnityVertexFormat::SNorm16 => (i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / i16::MAX as f32).max(-1.0),
+        UnityVertexFormat::UInt8 => bytes[0] as f32,
+        UnityVertexFormat::SInt8 => bytes[0] as i8 as f32,
+        UnityVertexFormat::UInt16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+        UnityVertexFormat::SInt16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+        UnityVertexFormat::UInt32 => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32,
+        UnityVertexFormat::SInt32 => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32,
+        UnityVertexFormat::Zero => unreachable!(),
+    };
+    Ok(value)
+}
+
+/// 按顶点描述解码 _typelessdata
+pub fn decode_vertices(data: &[u8], vertex_count: usize, descriptors: &[UnityVertexAttributeDescriptor]) -> anyhow::Result<Vec<MeshVertex>> {
+    let channels: Vec<&UnityVertexAttributeDescriptor> = descriptors
+        .iter()
+        .filter(|desc| desc.components() > 0 && MeshVertex::uses(desc.attribute))
+        .collect();
+    if !channels.iter().any(|desc| desc.attribute == UnityVertexAttribute::Position) {
+        anyhow::bail!("Vertex data has no position channel");
+    }
+    for desc in &channels {
+        if desc.format == UnityVertexFormat::Zero {
+            anyhow::bail!("Unsupported vertex format in channel {:?}", desc.attribute);
+        }
+        if desc.components() > 4 {
+            anyhow::bail!("Channel {:?} has {} components", desc.attribute, desc.components());
+        }
+    }
+
+    let streams = stream_layouts(descriptors, vertex_count);
+    for (i, stream) in streams.iter().enumerate() {
+        let end = stream.offset + stream.stride * vertex_count;
+        if end > data.len() {
+            anyhow::bail!("Vertex data too short: stream {} needs {} bytes, got {}", i, end, data.len());
+        }
+    }
+
+    let mut vertices = vec![MeshVertex::default(); vertex_count];
+    for desc in channels {
+        let stream = streams[desc.stream as usize];
+        let size = desc.format.size_in_bytes() as usize;
+        let components = desc.components() as usize;
+        for (i, vertex) in vertices.iter_mut().enumerate() {
+            let start = stream.offset + i * stream.stride + desc.offset as usize;
+            let mut value = [0.0; 4];
+            for (c, v) in value.iter_mut().take(components).enumerate() {
+                *v = read_component(desc.format, &data[start + c * size..])?;
+            }
+            vertex.set(desc.attribute, value, components);
+        }
+    }
+
+    Ok(vertices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn desc(attribute: UnityVertexAttribute, stream: u8, offset: u8, format: UnityVertexFormat, dimension: u8) -> UnityVertexAttributeDescriptor {
+        UnityVertexAttributeDescriptor { attribute, stream, offset, format, dimension }
+    }
+
+    #[test]
+    fn test_read_every_format() {
+        let cases: [(UnityVertexFormat, Vec<u8>, f32); 12] = [
+            (UnityVertexFormat::Float32, 1.5f32.to_le_bytes().to_vec(), 1.5),
+            (UnityVertexFormat::Float16, f16::from_f32(-0.25).to_le_bytes().to_vec(), -0.25),
+            (UnityVertexFormat::UNorm8, vec![255], 1.0),
+            (UnityVertexFormat::SNorm8, vec![0x80], -1.0),
+            (UnityVertexFormat::UNorm16, 0u16.to_le_bytes().to_vec(), 0.0),
+            (UnityVertexFormat::SNorm16, i16::MAX.to_le_bytes().to_vec(), 1.0),
+            (UnityVertexFormat::UInt8, vec![200], 200.0),
+            (UnityVertexFormat::SInt8, vec![0xFE], -2.0),
+            (UnityVertexFormat::UInt16, 40000u16.to_le_bytes().to_vec(), 40000.0),
+            (UnityVertexFormat::SInt16, (-300i16).to_le_bytes().to_vec(), -300.0),
+            (UnityVertexFormat::UInt32, 70000u32.to_le_bytes().to_vec(), 70000.0),
+            (UnityVertexFormat::SInt32, (-70000i32).to_le_bytes().to_vec(), -70000.0),
+        ];
+        for (format, bytes, expected) in cases {
+            assert_eq!(bytes.len(), format.size_in_bytes() as usize, "{:?}", format);
+            assert_eq!(read_component(format, &bytes).unwrap(), expected, "{:?}", format);
+            assert!(read_component(format, &bytes[1..]).is_err(), "{:?}", format);
+        }
+        assert!(read_component(UnityVertexFormat::Zero, &[0; 4]).is_err());
+    }
+
+    #[test]
+    fn test_decode_flips_z() {
+        // position f32x3, normal f16x4, tangent snorm8x4, color unorm8x4, uv0 unorm16x2
+        let descriptors = vec![
+            desc(UnityVertexAttribute::Position, 0, 0, UnityVertexFormat::Float32, 3),
+            desc(UnityVertexAttribute::Normal, 0, 12, UnityVertexFormat::Float16, 52),
+            desc(UnityVertexAttribute::Tangent, 0, 20, UnityVertexFormat::SNorm8, 4),
+            desc(UnityVertexAttribute::Color, 0, 24, UnityVertexFormat::UNorm8, 4),
+            desc(UnityVertexAttribute::TexCoord0, 0, 28, UnityVertexFormat::UNorm16, 2),
+            desc(UnityVertexAttribute::TexCoord1, 0, 0, UnityVertexFormat::Float32, 0),
+        ];
+        let mut data = Vec::new();
+        for v in [1.0f32, 2.0, 3.0] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        for v in [0.0f32, 0.0, 1.0, 0.0] {
+            data.extend_from_slice(&f16::from_f32(v).to_le_bytes());
+        }
+        data.extend_from_slice(&[127, 0, 127, 0x81]);
+        data.extend_from_slice(&[255, 0, 255, 255]);
+        data.extend_from_slice(&u16::MAX.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+
+        let vertices = decode_vertices(&data, 1, &descriptors).unwrap();
+        assert_eq!(vertices, vec![MeshVertex {
+            position: [1.0, 2.0, -3.0],
+            normal: [0.0, 0.0, -1.0],
+            tangent: [1.0, 0.0, -1.0, 1.0],
+            color: [1.0, 0.0, 1.0, 1.0],
+            uv0: [0.0, 0.0],
+        }]);
+    }
+
+    #[test]
+    fn test_decode_streams() {
+        // stream 0: position，stream 1: uv0，stream 1 从 16 字节对齐处开始
+        let descriptors = vec![
+            desc(UnityVertexAttribute::Position, 0, 0, UnityVertexFormat::Float32, 3),
+            desc(UnityVertexAttribute::TexCoord0, 1, 0, UnityVertexFormat::Float16, 2),
+        ];
+        let mut data = Vec::new();
+        for i in 0..3 {
+            for v in [i as f32, 0.0, 1.0] {
+                data.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        data.resize(48, 0xFF);
+        for i in 0..3 {
+            for v in [i as f32 * 0.25, 0.5] {
+                data.extend_from_slice(&f16::from_f32(v).to_le_bytes());
+            }
+        }
+
+        let vertices = decode_vertices(&data, 3, &descriptors).unwrap();
+        let positions: Vec<[f32; 3]> = vertices.iter().map(|v| v.position).collect();
+        let uvs: Vec<[f32; 2]> = vertices.iter().map(|v| v.uv0).collect();
+        assert_eq!(positions, vec![[0.0, 0.0, -1.0], [1.0, 0.0, -1.0], [2.0, 0.0, -1.0]]);
+        assert_eq!(uvs, vec![[0.0, 0.5], [0.25, 0.5], [0.5, 0.5]]);
+        // 缺省的颜色为白色
+        assert_eq!(vertices[0].color, [1.0; 4]);
+
+        assert!(decode_vertices(&data[..data.len() - 1], 3, &descriptors).is_err());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let uv_only = vec![desc(UnityVertexAttribute::TexCoord0, 0, 0, UnityVertexFormat::Float32, 2)];
+        assert!(decode_vertices(&[0; 8], 1, &uv_only).is_err());
+
+        let zero = vec![desc(UnityVertexAttribute::Position, 0, 0, UnityVertexFormat::Zero, 3)];
+        assert!(decode_vertices(&[0; 12], 1, &zero).is_err());
+
+        let position = vec![desc(UnityVertexAttribute::Position, 0, 0, UnityVertexFormat::Float32, 3)];
+        assert!(decode_vertices(&[0; 23], 2, &position).is_err());
+        assert_eq!(decode_vertices(&[0; 24], 2, &position).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_layout_matches_descriptors() {
+        let descriptors = MeshVertex::descriptors();
+        let stride: usize = descriptors.iter().map(|desc| desc.size_in_bytes() as usize).sum();
+        assert_eq!(stride, size_of::<MeshVertex>());
+        assert!(descriptors.iter().all(|desc| desc.to_wgpu_format().is_some()));
+    }
+}